@@ -0,0 +1,308 @@
+//! Address-line test for aliasing and address decoder faults.
+//!
+//! Fill-then-verify patterns write every word, so a broken address line that
+//! makes two offsets share one cell goes unnoticed as long as both receive
+//! the same value. The address-line test (memtest86's test 1) probes only
+//! the block's base and its power-of-two offsets: it writes a marker to one
+//! of them at a time and checks that none of the others changed. Each probe
+//! differs from the base in exactly one address bit, so a write that lands
+//! elsewhere points at that bit.
+//!
+//! Offsets map one-to-one to physical address bits up to the page size, and
+//! beyond that only within huge pages.
+
+use crate::cache;
+use crate::error::AddressLine;
+use std::fmt;
+
+/// Background and marker values, run with both polarities.
+const POLARITIES: [(u64, u64); 2] = [(0, u64::MAX), (u64::MAX, 0)];
+
+/// A probe write that disturbed another probe, or did not stick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressFault {
+    /// Word index that was written.
+    pub written: usize,
+    /// Word index that read back wrong; equal to `written` if the marker
+    /// did not stick.
+    pub changed: usize,
+    /// Value `changed` should have held.
+    pub expected: u64,
+    /// Value actually read.
+    pub actual: u64,
+}
+
+impl AddressFault {
+    /// Returns the byte-address bit implicated, if any.
+    ///
+    /// An address line stuck high or low makes the base and offset `1 << n`
+    /// share a cell, which points at bit `n`. When two power-of-two offsets
+    /// alias, the bit of the written one is reported.
+    pub fn bit(&self) -> Option<u32> {
+        let offset = match (self.written, self.changed) {
+            (written, changed) if written == changed => return None,
+            (0, changed) => changed,
+            (written, _) => written,
+        };
+        Some((offset * std::mem::size_of::<u64>()).trailing_zeros())
+    }
+
+    /// Summarizes the fault for error reports.
+    pub fn line(&self) -> AddressLine {
+        AddressLine {
+            written_offset: self.written * std::mem::size_of::<u64>(),
+            bit: self.bit(),
+        }
+    }
+}
+
+impl fmt::Display for AddressFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = std::mem::size_of::<u64>();
+        match self.bit() {
+            Some(bit) => write!(
+                f,
+                "address bit {}: writing offset 0x{:X} changed offset 0x{:X}",
+                bit,
+                self.written * size,
+                self.changed * size
+            )?,
+            None => write!(
+                f,
+                "writing offset 0x{:X} did not stick",
+                self.written * size
+            )?,
+        }
+        write!(
+            f,
+            " (expected 0x{:016X}, got 0x{:016X})",
+            self.expected, self.actual
+        )
+    }
+}
+
+/// Word-addressable memory the address-line test can run against.
+trait AddressCells {
+    fn len(&self) -> usize;
+    fn read(&self, index: usize) -> u64;
+    fn write(&mut self, index: usize, value: u64);
+
+    /// Pushes one word out of the CPU caches.
+    fn flush(&self, _index: usize) {}
+}
+
+impl AddressCells for [u64] {
+    fn len(&self) -> usize {
+        <[u64]>::len(self)
+    }
+
+    fn read(&self, index: usize) -> u64 {
+        cache::read_word(&self[index])
+    }
+
+    fn write(&mut self, index: usize, value: u64) {
+        cache::write_word(&mut self[index], value);
+    }
+
+    fn flush(&self, index: usize) {
+        cache::flush(std::slice::from_ref(&self[index]));
+    }
+}
+
+/// Returns the word indices probed in a block of `len` words: the base and
+/// every power-of-two offset inside the block.
+pub fn probes(len: usize) -> Vec<usize> {
+    if len == 0 {
+        return Vec::new();
+    }
+    std::iter::once(0)
+        .chain(
+            (0..usize::BITS)
+                .map(|bit| 1usize << bit)
+                .take_while(|&i| i < len),
+        )
+        .collect()
+}
+
+/// Runs the address-line test over a memory block.
+///
+/// Every probe write is flushed to DRAM before the other probes are read
+/// back, so aliasing in the caches cannot hide aliasing in the DIMMs. Probe
+/// words are left holding all ones; the rest of the block is untouched.
+///
+/// # Returns
+/// * `Ok(())` if no probe write disturbed another probe
+/// * `Err(AddressFault)` describing the first one that did
+pub fn run(block: &mut [u64]) -> Result<(), AddressFault> {
+    run_cells(block)
+}
+
+fn run_cells<C: AddressCells + ?Sized>(cells: &mut C) -> Result<(), AddressFault> {
+    let probes = probes(cells.len());
+    for (background, marker) in POLARITIES {
+        for &index in &probes {
+            cells.write(index, background);
+            cells.flush(index);
+        }
+        for &written in &probes {
+            cells.write(written, marker);
+            cells.flush(written);
+            for &changed in &probes {
+                let expected = if changed == written {
+                    marker
+                } else {
+                    background
+                };
+                let actual = cells.read(changed);
+                if actual != expected {
+                    return Err(AddressFault {
+                        written,
+                        changed,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+            cells.write(written, background);
+            cells.flush(written);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_BLOCK_SIZE: usize = 1024;
+
+    /// Memory whose decoder ignores or forces one word-address bit.
+    struct BrokenLineCells {
+        cells: Vec<u64>,
+        bit: u32,
+        stuck_high: bool,
+    }
+
+    impl BrokenLineCells {
+        fn decode(&self, index: usize) -> usize {
+            if self.stuck_high {
+                index | 1 << self.bit
+            } else {
+                index & !(1 << self.bit)
+            }
+        }
+    }
+
+    impl AddressCells for BrokenLineCells {
+        fn len(&self) -> usize {
+            self.cells.len()
+        }
+
+        fn read(&self, index: usize) -> u64 {
+            self.cells[self.decode(index)]
+        }
+
+        fn write(&mut self, index: usize, value: u64) {
+            let cell = self.decode(index);
+            self.cells[cell] = value;
+        }
+    }
+
+    /// Memory where two address lines are shorted together.
+    struct BridgedCells {
+        cells: Vec<u64>,
+        bits: (u32, u32),
+    }
+
+    impl AddressCells for BridgedCells {
+        fn len(&self) -> usize {
+            self.cells.len()
+        }
+
+        fn read(&self, index: usize) -> u64 {
+            self.cells[index]
+        }
+
+        fn write(&mut self, index: usize, value: u64) {
+            // Driving either line high drives both
+            let both = 1 << self.bits.0 | 1 << self.bits.1;
+            let index = if index & both != 0 {
+                index | both
+            } else {
+                index
+            };
+            self.cells[index] = value;
+        }
+    }
+
+    #[test]
+    fn test_probes() {
+        assert!(probes(0).is_empty());
+        assert_eq!(probes(1), vec![0]);
+        assert_eq!(probes(5), vec![0, 1, 2, 4]);
+        assert_eq!(probes(1024).len(), 11);
+    }
+
+    #[test]
+    fn test_passes_on_good_memory() {
+        let mut block: Vec<u64> = (0..TEST_BLOCK_SIZE as u64).collect();
+        assert!(run(&mut block).is_ok());
+        // Only the probes are written
+        assert_eq!(block[3], 3);
+        assert_eq!(block[512], u64::MAX);
+    }
+
+    #[test]
+    fn test_stuck_low_line() {
+        let mut cells = BrokenLineCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            bit: 6,
+            stuck_high: false,
+        };
+
+        let fault = run_cells(&mut cells).unwrap_err();
+        assert_eq!(fault.written, 0);
+        assert_eq!(fault.changed, 64);
+        // Word bit 6 is byte-address bit 9
+        assert_eq!(fault.bit(), Some(9));
+        assert_eq!(
+            fault.line(),
+            AddressLine {
+                written_offset: 0,
+                bit: Some(9)
+            }
+        );
+        assert!(fault
+            .to_string()
+            .starts_with("address bit 9: writing offset 0x0 changed offset 0x200"));
+    }
+
+    #[test]
+    fn test_stuck_high_line() {
+        let mut cells = BrokenLineCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            bit: 2,
+            stuck_high: true,
+        };
+
+        let fault = run_cells(&mut cells).unwrap_err();
+        assert_eq!(fault.written, 0);
+        assert_eq!(fault.changed, 4);
+        assert_eq!(fault.bit(), Some(5));
+    }
+
+    #[test]
+    fn test_bridged_lines() {
+        let mut cells = BridgedCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            bits: (3, 8),
+        };
+
+        let fault = run_cells(&mut cells).unwrap_err();
+        assert_eq!(fault.written, 8);
+        assert_eq!(fault.changed, 8);
+        assert_eq!(fault.bit(), None);
+        assert_eq!(fault.expected, u64::MAX);
+        assert!(fault.to_string().contains("did not stick"));
+    }
+}
@@ -0,0 +1,186 @@
+//! CPU core selection and thread pinning.
+//!
+//! Pinning each worker to one core makes errors attributable to a core: a
+//! fault that follows one core's load/store unit looks different from one
+//! that stays with a physical page. Pinning uses `sched_setaffinity` and is
+//! only available on Linux; elsewhere workers float freely.
+
+/// Returns whether threads can be pinned on this platform.
+pub fn supported() -> bool {
+    cfg!(target_os = "linux")
+}
+
+/// Parses a core list such as `0-7,16` into sorted, de-duplicated core IDs.
+pub fn parse_cpu_list(s: &str) -> Result<Vec<usize>, String> {
+    let invalid = || format!("Invalid CPU list: '{}'. Use e.g. 0-7,16.", s);
+    let mut cpus = Vec::new();
+    for part in s.split(',').map(str::trim) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let first: usize = first.trim().parse().map_err(|_| invalid())?;
+                let last: usize = last.trim().parse().map_err(|_| invalid())?;
+                if first > last {
+                    return Err(invalid());
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(part.parse().map_err(|_| invalid())?),
+        }
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(cpus)
+}
+
+/// Formats core IDs as a compact list, collapsing runs into ranges (`0-7,16`).
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < cpus.len() {
+        let start = cpus[i];
+        while i + 1 < cpus.len() && cpus[i + 1] == cpus[i] + 1 {
+            i += 1;
+        }
+        if cpus[i] == start {
+            parts.push(start.to_string());
+        } else {
+            parts.push(format!("{}-{}", start, cpus[i]));
+        }
+        i += 1;
+    }
+    parts.join(",")
+}
+
+/// Returns the cores this process may run on.
+#[cfg(target_os = "linux")]
+pub fn available_cpus() -> Vec<usize> {
+    // SAFETY: cpu_set_t is plain data, so all-zero is a valid (empty) set.
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    // SAFETY: `set` is a valid cpu_set_t and the size passed matches it.
+    let ret =
+        unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) };
+    if ret != 0 {
+        return (0..num_cpus::get()).collect();
+    }
+    (0..libc::CPU_SETSIZE as usize)
+        // SAFETY: `cpu` is below CPU_SETSIZE, so CPU_ISSET stays within `set`.
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .collect()
+}
+
+/// Returns the cores this process may run on.
+#[cfg(not(target_os = "linux"))]
+pub fn available_cpus() -> Vec<usize> {
+    (0..num_cpus::get()).collect()
+}
+
+/// Pins the calling thread to `cpu`.
+pub fn pin_current_thread(cpu: usize) -> Result<(), String> {
+    restrict_current_thread(&[cpu])
+}
+
+/// Restricts the calling thread to run only on `cpus`.
+#[cfg(target_os = "linux")]
+pub fn restrict_current_thread(cpus: &[usize]) -> Result<(), String> {
+    if cpus.is_empty() {
+        return Err("No CPUs to run on".to_string());
+    }
+    if let Some(cpu) = cpus.iter().find(|&&cpu| cpu >= libc::CPU_SETSIZE as usize) {
+        return Err(format!("CPU {} is out of range", cpu));
+    }
+    // SAFETY: cpu_set_t is plain data, so all-zero is a valid (empty) set.
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus {
+        // SAFETY: every `cpu` was checked against CPU_SETSIZE above.
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    // SAFETY: `set` is a valid cpu_set_t and the size passed matches it;
+    // pid 0 means the calling thread.
+    let ret = unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+    if ret != 0 {
+        return Err(format!(
+            "Failed to pin thread to CPU {}: {}",
+            format_cpu_list(cpus),
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// Restricts the calling thread to run only on `cpus`.
+#[cfg(not(target_os = "linux"))]
+pub fn restrict_current_thread(cpus: &[usize]) -> Result<(), String> {
+    Err(format!(
+        "Cannot pin thread to CPU {}: thread pinning is only supported on Linux",
+        format_cpu_list(cpus)
+    ))
+}
+
+/// Returns the core the calling thread is running on, if the platform reports it.
+#[cfg(target_os = "linux")]
+pub fn current_cpu() -> Option<usize> {
+    // SAFETY: sched_getcpu has no preconditions.
+    let cpu = unsafe { libc::sched_getcpu() };
+    usize::try_from(cpu).ok()
+}
+
+/// Returns the core the calling thread is running on, if the platform reports it.
+#[cfg(not(target_os = "linux"))]
+pub fn current_cpu() -> Option<usize> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("3"), Ok(vec![3]));
+        assert_eq!(parse_cpu_list("0-3,16"), Ok(vec![0, 1, 2, 3, 16]));
+        assert_eq!(parse_cpu_list("5, 1-2,2"), Ok(vec![1, 2, 5]));
+        assert!(parse_cpu_list("").is_err());
+        assert!(parse_cpu_list("4-2").is_err());
+        assert!(parse_cpu_list("a-b").is_err());
+    }
+
+    #[test]
+    fn test_format_cpu_list() {
+        assert_eq!(format_cpu_list(&[0, 1, 2, 3, 16]), "0-3,16");
+        assert_eq!(format_cpu_list(&[1, 3, 4]), "1,3-4");
+        assert_eq!(format_cpu_list(&[]), "");
+    }
+
+    #[test]
+    fn test_available_cpus() {
+        let cpus = available_cpus();
+        assert!(!cpus.is_empty());
+        assert!(cpus.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pin_current_thread() {
+        let cpu = available_cpus()[0];
+        std::thread::spawn(move || {
+            pin_current_thread(cpu).unwrap();
+            assert_eq!(current_cpu(), Some(cpu));
+        })
+        .join()
+        .unwrap();
+        assert!(pin_current_thread(usize::MAX).is_err());
+        assert!(restrict_current_thread(&[]).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_restrict_current_thread() {
+        let cpus = available_cpus();
+        std::thread::spawn(move || {
+            restrict_current_thread(&cpus).unwrap();
+            assert!(cpus.contains(&current_cpu().unwrap()));
+        })
+        .join()
+        .unwrap();
+    }
+}
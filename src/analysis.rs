@@ -0,0 +1,648 @@
+//! Bit-level analysis of detected memory errors.
+//!
+//! This module interprets the expected and actual values of memory errors:
+//! which bits flipped, in which direction, and whether the flips cluster on
+//! one bit position or byte lane. The resulting classification tells a stuck
+//! data line apart from a failing chip or random flips from an unstable
+//! overclock. Errors from the address-line test implicate an address bit
+//! instead and are diagnosed as such; errors from the retention test are
+//! bit flips like any other, but also record how long the cells sat idle.
+
+use crate::error::{MemoryErrorInfo, TestKind};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+/// Number of bits in a tested word.
+const WORD_BITS: usize = 64;
+
+/// Number of byte lanes in a tested word.
+const BYTE_LANES: usize = 8;
+
+/// Bit flips between an expected and an actual word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitFlips {
+    /// Bits that differ (`expected ^ actual`).
+    pub mask: u64,
+    /// Bits that read as 1 but were written as 0.
+    pub zero_to_one: u64,
+    /// Bits that read as 0 but were written as 1.
+    pub one_to_zero: u64,
+}
+
+impl BitFlips {
+    /// Computes the flipped bits between `expected` and `actual`.
+    pub fn new(expected: u64, actual: u64) -> Self {
+        Self {
+            mask: expected ^ actual,
+            zero_to_one: !expected & actual,
+            one_to_zero: expected & !actual,
+        }
+    }
+
+    /// Returns the number of flipped bits.
+    pub fn count(&self) -> u32 {
+        self.mask.count_ones()
+    }
+}
+
+/// Returns `count` followed by `one` if it is 1, or by `many` otherwise.
+pub(crate) fn plural(count: u64, one: &str, many: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, one)
+    } else {
+        format!("{} {}", count, many)
+    }
+}
+
+/// Classification of the errors seen over a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaultClass {
+    /// No errors with values were analyzed.
+    None,
+    /// A single error, too little to tell a fault from a one-off flip;
+    /// `bits` is the number of bits that flipped in it.
+    Isolated { bits: u32 },
+    /// The same bit always reads as `value`, regardless of what was written.
+    StuckAt {
+        bit: u32,
+        value: bool,
+        addresses: usize,
+    },
+    /// The same bit flips in both directions.
+    DataLine { bit: u32, addresses: usize },
+    /// All flips fall within one byte lane, across several bits.
+    ByteLane { lane: u32, addresses: usize },
+    /// Single-bit flips spread over different bit positions.
+    RandomSingleBit { errors: u64, bits: u32 },
+    /// Several bits flip at once with no common bit or lane.
+    MultiBit { errors: u64 },
+    /// The address-line test found writes landing at the wrong offset;
+    /// `bits` lists the address bits implicated, if any.
+    AddressLine { bits: Vec<u32>, errors: u64 },
+}
+
+impl fmt::Display for FaultClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "no errors to analyze"),
+            Self::Isolated { bits } => write!(
+                f,
+                "isolated error with {} flipped (a one-off flip or the first sign of a fault; run longer to tell)",
+                plural(u64::from(*bits), "bit", "bits")
+            ),
+            Self::StuckAt {
+                bit,
+                value,
+                addresses,
+            } => write!(
+                f,
+                "stuck-at-{} on bit {} across {} (bad data line, DIMM pin or chip)",
+                u8::from(*value),
+                bit,
+                plural(*addresses as u64, "address", "addresses")
+            ),
+            Self::DataLine { bit, addresses } => write!(
+                f,
+                "bit {} flips in both directions across {} (unstable data line)",
+                bit,
+                plural(*addresses as u64, "address", "addresses")
+            ),
+            Self::ByteLane { lane, addresses } => write!(
+                f,
+                "errors confined to byte lane {} (bits {}-{}) across {} (one DRAM chip)",
+                lane,
+                lane * 8,
+                lane * 8 + 7,
+                plural(*addresses as u64, "address", "addresses")
+            ),
+            Self::RandomSingleBit { errors, bits } => write!(
+                f,
+                "random single-bit flips ({} errors on {} different bits; unstable overclock or marginal timings)",
+                errors, bits
+            ),
+            Self::MultiBit { errors } => write!(
+                f,
+                "multi-bit corruption in {} words (failing module, addressing fault or heavy instability)",
+                errors
+            ),
+            Self::AddressLine { bits, errors } if bits.is_empty() => write!(
+                f,
+                "address decoder fault: {} did not stick (failing module or memory controller)",
+                plural(*errors, "probe write", "probe writes")
+            ),
+            Self::AddressLine { bits, errors } => {
+                let bits: Vec<String> = bits.iter().map(|b| b.to_string()).collect();
+                write!(
+                    f,
+                    "bad address line: address bit {} in {} (DIMM seating, slot contacts or memory controller)",
+                    bits.join(", "),
+                    plural(*errors, "error", "errors")
+                )
+            }
+        }
+    }
+}
+
+/// Where an analyzed error was found, for counting distinct addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Address {
+    /// Resolved physical address; the same word can be reached from
+    /// different threads or blocks.
+    Physical(u64),
+    /// Offset within one block tested by a thread or GPU.
+    Tested {
+        source_id: usize,
+        block: usize,
+        offset: usize,
+    },
+}
+
+/// Accumulated bit-level statistics over a set of memory errors.
+#[derive(Debug, Clone)]
+pub struct ErrorAnalysis {
+    /// Number of errors analyzed.
+    pub errors: u64,
+    /// Number of errors with exactly one flipped bit.
+    pub single_bit_errors: u64,
+    /// Total 0→1 bit flips.
+    pub zero_to_one: u64,
+    /// Total 1→0 bit flips.
+    pub one_to_zero: u64,
+    /// 0→1 flips per bit position.
+    pub zero_to_one_by_bit: [u64; WORD_BITS],
+    /// 1→0 flips per bit position.
+    pub one_to_zero_by_bit: [u64; WORD_BITS],
+    /// Flipped bits per byte lane.
+    pub flips_by_lane: [u64; BYTE_LANES],
+    /// OR of every flip mask seen.
+    pub combined_mask: u64,
+    /// Number of errors caught by the address-line test.
+    pub address_line_errors: u64,
+    /// Address bits implicated by the address-line test, as a mask.
+    pub address_bits: u64,
+    /// Number of errors caught by the retention test.
+    pub retention_errors: u64,
+    /// Shortest idle period after which the retention test found an error.
+    pub shortest_idle: Option<Duration>,
+    addresses: HashSet<Address>,
+}
+
+impl ErrorAnalysis {
+    /// Creates an empty analysis.
+    pub fn new() -> Self {
+        Self {
+            errors: 0,
+            single_bit_errors: 0,
+            zero_to_one: 0,
+            one_to_zero: 0,
+            zero_to_one_by_bit: [0; WORD_BITS],
+            one_to_zero_by_bit: [0; WORD_BITS],
+            flips_by_lane: [0; BYTE_LANES],
+            combined_mask: 0,
+            address_line_errors: 0,
+            address_bits: 0,
+            retention_errors: 0,
+            shortest_idle: None,
+            addresses: HashSet::new(),
+        }
+    }
+
+    /// Builds an analysis from every error that carries expected and actual values.
+    pub fn from_errors<'a>(errors: impl IntoIterator<Item = &'a MemoryErrorInfo>) -> Self {
+        let mut analysis = Self::new();
+        for error in errors {
+            analysis.add_error(error);
+        }
+        analysis
+    }
+
+    /// Adds one error; errors without expected/actual values are ignored.
+    ///
+    /// Address-line errors only count towards the address diagnosis: their
+    /// whole-word flips say nothing about data bits.
+    pub fn add_error(&mut self, error: &MemoryErrorInfo) {
+        match &error.test {
+            TestKind::AddressLines(line) => {
+                self.address_line_errors += 1;
+                if let Some(bit) = line.bit {
+                    self.address_bits |= 1 << bit;
+                }
+                return;
+            }
+            &TestKind::Retention(idle) => {
+                self.retention_errors += 1;
+                self.shortest_idle = Some(self.shortest_idle.map_or(idle, |s| s.min(idle)));
+            }
+            _ => {}
+        }
+        if let (Some(expected), Some(actual)) = (error.expected, error.actual) {
+            let address = match error.physical_address {
+                Some(physical) => Address::Physical(physical),
+                None => Address::Tested {
+                    source_id: error.source_id,
+                    block: error.block,
+                    offset: error.offset,
+                },
+            };
+            self.add_at(address, expected, actual);
+        }
+    }
+
+    /// Adds one mismatching word seen by `source_id` at `offset` in its
+    /// first block.
+    pub fn add(&mut self, source_id: usize, offset: usize, expected: u64, actual: u64) {
+        let address = Address::Tested {
+            source_id,
+            block: 0,
+            offset,
+        };
+        self.add_at(address, expected, actual);
+    }
+
+    fn add_at(&mut self, address: Address, expected: u64, actual: u64) {
+        let flips = BitFlips::new(expected, actual);
+        if flips.mask == 0 {
+            return;
+        }
+
+        self.errors += 1;
+        if flips.count() == 1 {
+            self.single_bit_errors += 1;
+        }
+        self.zero_to_one += flips.zero_to_one.count_ones() as u64;
+        self.one_to_zero += flips.one_to_zero.count_ones() as u64;
+        self.combined_mask |= flips.mask;
+        self.addresses.insert(address);
+
+        for bit in 0..WORD_BITS {
+            if flips.zero_to_one & (1 << bit) != 0 {
+                self.zero_to_one_by_bit[bit] += 1;
+            }
+            if flips.one_to_zero & (1 << bit) != 0 {
+                self.one_to_zero_by_bit[bit] += 1;
+            }
+        }
+        for (lane, count) in self.flips_by_lane.iter_mut().enumerate() {
+            *count += ((flips.mask >> (lane * 8)) & 0xFF).count_ones() as u64;
+        }
+    }
+
+    /// Returns the number of distinct addresses with errors.
+    pub fn distinct_addresses(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// Returns the total flips (both directions) for one bit position.
+    pub fn flips_for_bit(&self, bit: usize) -> u64 {
+        self.zero_to_one_by_bit[bit] + self.one_to_zero_by_bit[bit]
+    }
+
+    /// Classifies the errors into the most likely fault type.
+    pub fn classify(&self) -> FaultClass {
+        if self.address_line_errors > 0 {
+            return FaultClass::AddressLine {
+                bits: (0..u64::BITS)
+                    .filter(|bit| self.address_bits & (1 << bit) != 0)
+                    .collect(),
+                errors: self.address_line_errors,
+            };
+        }
+        if self.errors == 0 {
+            return FaultClass::None;
+        }
+
+        let addresses = self.distinct_addresses();
+        let bits = self.combined_mask.count_ones();
+
+        if self.errors == 1 {
+            return FaultClass::Isolated { bits };
+        }
+
+        if bits == 1 {
+            let bit = self.combined_mask.trailing_zeros();
+            return match (self.zero_to_one > 0, self.one_to_zero > 0) {
+                (true, false) => FaultClass::StuckAt {
+                    bit,
+                    value: true,
+                    addresses,
+                },
+                (false, true) => FaultClass::StuckAt {
+                    bit,
+                    value: false,
+                    addresses,
+                },
+                _ => FaultClass::DataLine { bit, addresses },
+            };
+        }
+
+        let lanes = self.flips_by_lane.iter().filter(|&&c| c > 0).count();
+        if lanes == 1 && bits > 1 {
+            let lane = self.flips_by_lane.iter().position(|&c| c > 0).unwrap_or(0) as u32;
+            return FaultClass::ByteLane { lane, addresses };
+        }
+
+        if self.single_bit_errors == self.errors {
+            return FaultClass::RandomSingleBit {
+                errors: self.errors,
+                bits,
+            };
+        }
+
+        FaultClass::MultiBit {
+            errors: self.errors,
+        }
+    }
+}
+
+impl Default for ErrorAnalysis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for ErrorAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Diagnosis: {}", self.classify())?;
+        writeln!(
+            f,
+            "Bit flips: {} total (0->1: {}, 1->0: {}) in {}, {} single-bit",
+            self.zero_to_one + self.one_to_zero,
+            self.zero_to_one,
+            self.one_to_zero,
+            plural(self.errors, "word", "words"),
+            self.single_bit_errors
+        )?;
+        if let Some(idle) = self.shortest_idle {
+            writeln!(
+                f,
+                "Retention: {}, the first after {} idle",
+                plural(self.retention_errors, "error", "errors"),
+                humantime::format_duration(idle)
+            )?;
+        }
+
+        writeln!(f, "Flips by bit position:")?;
+        for bit in (0..WORD_BITS).filter(|&b| self.flips_for_bit(b) > 0) {
+            writeln!(
+                f,
+                "  Bit {:2}: {} (0->1: {}, 1->0: {})",
+                bit,
+                self.flips_for_bit(bit),
+                self.zero_to_one_by_bit[bit],
+                self.one_to_zero_by_bit[bit]
+            )?;
+        }
+
+        writeln!(f, "Flips by byte lane:")?;
+        for (lane, count) in self.flips_by_lane.iter().enumerate() {
+            if *count > 0 {
+                writeln!(
+                    f,
+                    "  Lane {} (bits {:2}-{:2}): {}",
+                    lane,
+                    lane * 8,
+                    lane * 8 + 7,
+                    count
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AddressLine;
+    use crate::patterns::TestPattern;
+
+    #[test]
+    fn test_bit_flips() {
+        let flips = BitFlips::new(0b1100, 0b1010);
+        assert_eq!(flips.mask, 0b0110);
+        assert_eq!(flips.zero_to_one, 0b0010);
+        assert_eq!(flips.one_to_zero, 0b0100);
+        assert_eq!(flips.count(), 2);
+    }
+
+    #[test]
+    fn test_empty_analysis() {
+        let analysis = ErrorAnalysis::new();
+        assert_eq!(analysis.classify(), FaultClass::None);
+        assert_eq!(analysis.distinct_addresses(), 0);
+    }
+
+    #[test]
+    fn test_values_required() {
+        let errors = vec![MemoryErrorInfo::new(TestPattern::AllOnes, 0, 0)];
+        let analysis = ErrorAnalysis::from_errors(&errors);
+        assert_eq!(analysis.errors, 0);
+    }
+
+    #[test]
+    fn test_distinct_addresses_across_blocks() {
+        let error = MemoryErrorInfo::with_values(TestPattern::AllOnes, 0x40, 0, u64::MAX, !1);
+        let errors = vec![
+            error.clone(),
+            MemoryErrorInfo {
+                block: 1,
+                ..error.clone()
+            },
+        ];
+        assert_eq!(ErrorAnalysis::from_errors(&errors).distinct_addresses(), 2);
+
+        // The same physical word counts once, whichever block reached it
+        let errors: Vec<MemoryErrorInfo> = errors
+            .into_iter()
+            .map(|e| e.with_physical_address(0x1234_0040))
+            .collect();
+        assert_eq!(ErrorAnalysis::from_errors(&errors).distinct_addresses(), 1);
+    }
+
+    #[test]
+    fn test_stuck_at_zero() {
+        let errors: Vec<MemoryErrorInfo> = (0..10)
+            .map(|i| {
+                MemoryErrorInfo::with_values(TestPattern::AllOnes, i * 8, 0, u64::MAX, !(1 << 17))
+            })
+            .collect();
+        let analysis = ErrorAnalysis::from_errors(&errors);
+
+        assert_eq!(analysis.one_to_zero, 10);
+        assert_eq!(analysis.zero_to_one, 0);
+        assert_eq!(analysis.one_to_zero_by_bit[17], 10);
+        assert_eq!(analysis.flips_by_lane[2], 10);
+        assert_eq!(
+            analysis.classify(),
+            FaultClass::StuckAt {
+                bit: 17,
+                value: false,
+                addresses: 10
+            }
+        );
+        assert!(analysis
+            .classify()
+            .to_string()
+            .contains("stuck-at-0 on bit 17 across 10 addresses"));
+    }
+
+    #[test]
+    fn test_stuck_at_one() {
+        let mut analysis = ErrorAnalysis::new();
+        analysis.add(0, 0, 0, 1 << 3);
+        analysis.add(1, 64, 0xF0, 0xF8);
+        assert_eq!(
+            analysis.classify(),
+            FaultClass::StuckAt {
+                bit: 3,
+                value: true,
+                addresses: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_data_line() {
+        let mut analysis = ErrorAnalysis::new();
+        analysis.add(0, 0, 0, 1 << 40);
+        analysis.add(0, 8, 1 << 40, 0);
+        assert_eq!(
+            analysis.classify(),
+            FaultClass::DataLine {
+                bit: 40,
+                addresses: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_byte_lane() {
+        let mut analysis = ErrorAnalysis::new();
+        analysis.add(0, 0, 0, 1 << 24);
+        analysis.add(0, 8, 0, 1 << 29);
+        analysis.add(0, 16, u64::MAX, !(0b11 << 26));
+        assert_eq!(
+            analysis.classify(),
+            FaultClass::ByteLane {
+                lane: 3,
+                addresses: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_random_single_bit() {
+        let mut analysis = ErrorAnalysis::new();
+        analysis.add(0, 0, 0, 1 << 2);
+        analysis.add(1, 8, 0, 1 << 33);
+        analysis.add(2, 16, u64::MAX, !(1 << 60));
+        assert_eq!(
+            analysis.classify(),
+            FaultClass::RandomSingleBit { errors: 3, bits: 3 }
+        );
+    }
+
+    #[test]
+    fn test_isolated() {
+        let mut analysis = ErrorAnalysis::new();
+        analysis.add(0, 0, 0, 1 << 2);
+        assert_eq!(analysis.classify(), FaultClass::Isolated { bits: 1 });
+        let text = analysis.to_string();
+        assert!(text.contains("Diagnosis: isolated error with 1 bit flipped"));
+        assert!(text.contains("in 1 word, 1 single-bit"));
+
+        analysis.add(0, 8, 0, 0b11 << 8);
+        assert_ne!(analysis.classify(), FaultClass::Isolated { bits: 3 });
+    }
+
+    #[test]
+    fn test_multi_bit() {
+        let mut analysis = ErrorAnalysis::new();
+        analysis.add(0, 0, 0, 0xFF00_0000_0000_00FF);
+        analysis.add(0, 8, 0, 1 << 9);
+        assert_eq!(analysis.classify(), FaultClass::MultiBit { errors: 2 });
+    }
+
+    #[test]
+    fn test_address_line() {
+        let line = AddressLine {
+            written_offset: 0,
+            bit: Some(12),
+        };
+        let errors = vec![
+            MemoryErrorInfo::with_values(TestPattern::AllOnes, 0x18, 0, u64::MAX, !(1 << 5)),
+            MemoryErrorInfo::with_values(TestKind::AddressLines(line), 0x1000, 0, 0, u64::MAX),
+            MemoryErrorInfo::with_values(
+                TestKind::AddressLines(AddressLine { bit: None, ..line }),
+                0x2000,
+                1,
+                0,
+                u64::MAX,
+            ),
+        ];
+        let analysis = ErrorAnalysis::from_errors(&errors);
+
+        assert_eq!(analysis.errors, 1);
+        assert_eq!(analysis.address_line_errors, 2);
+        assert_eq!(
+            analysis.classify(),
+            FaultClass::AddressLine {
+                bits: vec![12],
+                errors: 2
+            }
+        );
+        assert!(analysis
+            .classify()
+            .to_string()
+            .starts_with("bad address line: address bit 12 in 2 errors"));
+        assert!(FaultClass::AddressLine {
+            bits: Vec::new(),
+            errors: 1
+        }
+        .to_string()
+        .starts_with("address decoder fault: 1 probe write did not stick"));
+    }
+
+    #[test]
+    fn test_display_histograms() {
+        let mut analysis = ErrorAnalysis::new();
+        analysis.add(0, 0, u64::MAX, !(1 << 17));
+        analysis.add(0, 8, u64::MAX, !(1 << 17));
+        let text = analysis.to_string();
+        assert!(text.contains("Diagnosis: stuck-at-0 on bit 17"));
+        assert!(text.contains("Bit 17: 2 (0->1: 0, 1->0: 2)"));
+        assert!(text.contains("Lane 2 (bits 16-23): 2"));
+    }
+
+    #[test]
+    fn test_retention() {
+        let errors = vec![
+            MemoryErrorInfo::with_values(
+                TestKind::Retention(Duration::from_secs(150)),
+                0,
+                0,
+                u64::MAX,
+                !(1 << 3),
+            ),
+            MemoryErrorInfo::with_values(
+                TestKind::Retention(Duration::from_secs(120)),
+                8,
+                0,
+                u64::MAX,
+                !(1 << 3),
+            ),
+            MemoryErrorInfo::with_values(TestPattern::AllOnes, 16, 0, u64::MAX, !(1 << 3)),
+        ];
+        let analysis = ErrorAnalysis::from_errors(&errors);
+
+        // Faded cells still count towards the bit statistics
+        assert_eq!(analysis.errors, 3);
+        assert_eq!(analysis.retention_errors, 2);
+        assert_eq!(analysis.shortest_idle, Some(Duration::from_secs(120)));
+        assert!(analysis
+            .to_string()
+            .contains("Retention: 2 errors, the first after 2m idle"));
+        assert_eq!(ErrorAnalysis::new().shortest_idle, None);
+    }
+}
@@ -0,0 +1,365 @@
+//! Boot-time exclusions for faulty physical memory.
+//!
+//! This module turns the physical addresses of detected errors into regions
+//! the operating system can be told to avoid: Linux `memmap=SIZE$ADDR`
+//! reservations and a GRUB `GRUB_BADRAM` address/mask list.
+
+use crate::analysis::plural;
+use crate::error::MemoryErrorInfo;
+use std::fmt;
+
+/// Default exclusion granularity (one 4 KiB page).
+pub const DEFAULT_GRANULARITY: u64 = 4096;
+
+/// A contiguous range of physical memory to exclude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BadRamRegion {
+    /// First byte of the region.
+    pub start: u64,
+    /// Size of the region in bytes.
+    pub size: u64,
+}
+
+impl BadRamRegion {
+    /// Returns the byte just past the end of the region.
+    pub fn end(&self) -> u64 {
+        self.start + self.size
+    }
+
+    /// Splits the region into naturally aligned power-of-two blocks.
+    ///
+    /// GRUB's address/mask pairs can only describe such blocks.
+    pub fn aligned_blocks(&self) -> Vec<BadRamRegion> {
+        let mut blocks = Vec::new();
+        let mut start = self.start;
+        let end = self.end();
+        while start < end {
+            let align = if start == 0 {
+                1u64 << 63
+            } else {
+                1u64 << start.trailing_zeros()
+            };
+            let mut size = align;
+            while size > end - start {
+                size >>= 1;
+            }
+            blocks.push(BadRamRegion { start, size });
+            start += size;
+        }
+        blocks
+    }
+}
+
+impl fmt::Display for BadRamRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "0x{:016X}-0x{:016X} ({})",
+            self.start,
+            self.end() - 1,
+            format_size(self.size)
+        )
+    }
+}
+
+/// Parses a size such as `4096`, `4K`, `2M` or `1G` (binary units).
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let upper = s.to_uppercase();
+    let digits = upper
+        .trim_end_matches('B')
+        .trim_end_matches('I')
+        .to_string();
+    let (number, shift) = match digits.chars().last() {
+        Some('K') => (&digits[..digits.len() - 1], 10),
+        Some('M') => (&digits[..digits.len() - 1], 20),
+        Some('G') => (&digits[..digits.len() - 1], 30),
+        _ => (digits.as_str(), 0),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(|| format!("Invalid size: '{}'. Use e.g. 4K, 2M or 1G.", s))
+}
+
+/// Parses an exclusion granularity: a power of two of at least 4 KiB.
+pub fn parse_granularity(s: &str) -> Result<u64, String> {
+    let size = parse_size(s)?;
+    if !size.is_power_of_two() || size < DEFAULT_GRANULARITY {
+        return Err(format!(
+            "Invalid granularity: '{}'. Must be a power of two of at least 4K.",
+            s
+        ));
+    }
+    Ok(size)
+}
+
+/// Formats a size with the largest binary suffix that divides it exactly.
+///
+/// This is also the form the kernel's `memmap=` parser accepts.
+pub fn format_size(size: u64) -> String {
+    const UNITS: [(u32, &str); 3] = [(30, "G"), (20, "M"), (10, "K")];
+    for (shift, unit) in UNITS {
+        if size >= 1 << shift && size.is_multiple_of(1 << shift) {
+            return format!("{}{}", size >> shift, unit);
+        }
+    }
+    size.to_string()
+}
+
+/// Aligns each address down to `granularity` and merges adjacent or
+/// overlapping blocks into sorted regions.
+pub fn merge_regions(
+    addresses: impl IntoIterator<Item = u64>,
+    granularity: u64,
+) -> Vec<BadRamRegion> {
+    let mut starts: Vec<u64> = addresses
+        .into_iter()
+        .map(|addr| addr & !(granularity - 1))
+        .collect();
+    starts.sort_unstable();
+    starts.dedup();
+
+    let mut regions: Vec<BadRamRegion> = Vec::new();
+    for start in starts {
+        match regions.last_mut() {
+            Some(last) if last.end() >= start => {
+                last.size = last.size.max(start + granularity - last.start);
+            }
+            _ => regions.push(BadRamRegion {
+                start,
+                size: granularity,
+            }),
+        }
+    }
+    regions
+}
+
+/// Exclusions for every error with a known physical address.
+#[derive(Debug, Clone)]
+pub struct BadRamReport {
+    /// Granularity the regions are aligned to.
+    pub granularity: u64,
+    /// Merged regions to exclude.
+    pub regions: Vec<BadRamRegion>,
+    /// Errors whose physical address was unknown and could not be excluded.
+    pub unresolved: usize,
+}
+
+impl BadRamReport {
+    /// Builds exclusions from detected errors.
+    pub fn from_errors<'a>(
+        errors: impl IntoIterator<Item = &'a MemoryErrorInfo>,
+        granularity: u64,
+    ) -> Self {
+        let mut addresses = Vec::new();
+        let mut unresolved = 0;
+        for error in errors {
+            match error.physical_address {
+                Some(addr) => addresses.push(addr),
+                None => unresolved += 1,
+            }
+        }
+        Self {
+            granularity,
+            regions: merge_regions(addresses, granularity),
+            unresolved,
+        }
+    }
+
+    /// Returns the Linux `memmap=SIZE$ADDR` parameters, one per region.
+    pub fn memmap_params(&self) -> Vec<String> {
+        self.regions
+            .iter()
+            .map(|r| format!("memmap={}${:#x}", format_size(r.size), r.start))
+            .collect()
+    }
+
+    /// Returns the `GRUB_BADRAM=addr,mask,...` line, or `None` if there is nothing to exclude.
+    pub fn grub_badram(&self) -> Option<String> {
+        if self.regions.is_empty() {
+            return None;
+        }
+        let pairs: Vec<String> = self
+            .regions
+            .iter()
+            .flat_map(BadRamRegion::aligned_blocks)
+            .map(|b| format!("{:#x},{:#x}", b.start, !(b.size - 1)))
+            .collect();
+        Some(format!("GRUB_BADRAM={}", pairs.join(",")))
+    }
+}
+
+impl fmt::Display for BadRamReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.regions.is_empty() {
+            writeln!(
+                f,
+                "No physical addresses were resolved, so no exclusions can be generated."
+            )?;
+            writeln!(
+                f,
+                "Rerun as root (CAP_SYS_ADMIN) on Linux so /proc/self/pagemap exposes frame numbers."
+            )?;
+            return Ok(());
+        }
+
+        writeln!(
+            f,
+            "Reserved regions (granularity {}):",
+            format_size(self.granularity)
+        )?;
+        for region in &self.regions {
+            writeln!(f, "  {}", region)?;
+        }
+        if self.unresolved > 0 {
+            writeln!(
+                f,
+                "  (not covered: {} without a physical address)",
+                plural(self.unresolved as u64, "error", "errors")
+            )?;
+        }
+
+        let params = self.memmap_params();
+        writeln!(f)?;
+        writeln!(f, "Kernel command line:")?;
+        writeln!(f, "  {}", params.join(" "))?;
+        writeln!(
+            f,
+            "  In /etc/default/grub, escape '$' as '\\\\\\$': GRUB_CMDLINE_LINUX=\"{}\"",
+            params.join(" ").replace('$', "\\\\\\$")
+        )?;
+        writeln!(
+            f,
+            "  Alternatively, memtest=17 makes the kernel run its own boot-time test and reserve what it finds."
+        )?;
+
+        if let Some(line) = self.grub_badram() {
+            writeln!(f)?;
+            writeln!(f, "GRUB:")?;
+            writeln!(f, "  {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::TestPattern;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("4K"), Ok(4096));
+        assert_eq!(parse_size("2m"), Ok(2 << 20));
+        assert_eq!(parse_size("1GiB"), Ok(1 << 30));
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_parse_granularity() {
+        assert_eq!(parse_granularity("2M"), Ok(2 << 20));
+        assert!(parse_granularity("3K").is_err());
+        assert!(parse_granularity("1K").is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(4096), "4K");
+        assert_eq!(format_size(12288), "12K");
+        assert_eq!(format_size(2 << 20), "2M");
+        assert_eq!(format_size(1 << 30), "1G");
+        assert_eq!(format_size(100), "100");
+    }
+
+    #[test]
+    fn test_merge_regions() {
+        let regions = merge_regions([0x5008, 0x1000, 0x1FF8, 0x2010, 0x4000], 0x1000);
+        assert_eq!(
+            regions,
+            vec![
+                BadRamRegion {
+                    start: 0x1000,
+                    size: 0x2000
+                },
+                BadRamRegion {
+                    start: 0x4000,
+                    size: 0x2000
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_regions_granularity() {
+        let regions = merge_regions([0x1234_5678, 0x1230_0000], 2 << 20);
+        assert_eq!(
+            regions,
+            vec![BadRamRegion {
+                start: 0x1220_0000,
+                size: 2 << 20
+            }]
+        );
+    }
+
+    #[test]
+    fn test_aligned_blocks() {
+        let region = BadRamRegion {
+            start: 0x3000,
+            size: 0x5000,
+        };
+        assert_eq!(
+            region.aligned_blocks(),
+            vec![
+                BadRamRegion {
+                    start: 0x3000,
+                    size: 0x1000
+                },
+                BadRamRegion {
+                    start: 0x4000,
+                    size: 0x4000
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_lines() {
+        let errors = vec![
+            MemoryErrorInfo::with_values(TestPattern::AllOnes, 0, 0, u64::MAX, 0)
+                .with_physical_address(0x1_2345_6010),
+            MemoryErrorInfo::with_values(TestPattern::AllOnes, 8, 0, u64::MAX, 0)
+                .with_physical_address(0x1_2345_7020),
+            MemoryErrorInfo::with_values(TestPattern::AllOnes, 16, 1, u64::MAX, 0),
+        ];
+        let report = BadRamReport::from_errors(&errors, 4096);
+
+        assert_eq!(report.unresolved, 1);
+        assert_eq!(report.memmap_params(), vec!["memmap=8K$0x123456000"]);
+        assert_eq!(
+            report.grub_badram().unwrap(),
+            "GRUB_BADRAM=0x123456000,0xffffffffffffe000"
+        );
+
+        let text = report.to_string();
+        assert!(text.contains("memmap=8K\\\\\\$0x123456000"));
+        assert!(text.contains("not covered"));
+    }
+
+    #[test]
+    fn test_report_without_addresses() {
+        let errors = vec![MemoryErrorInfo::with_values(
+            TestPattern::AllOnes,
+            0,
+            0,
+            u64::MAX,
+            0,
+        )];
+        let report = BadRamReport::from_errors(&errors, 4096);
+        assert!(report.regions.is_empty());
+        assert!(report.grub_badram().is_none());
+        assert!(report.to_string().contains("Rerun as root"));
+    }
+}
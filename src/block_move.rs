@@ -0,0 +1,242 @@
+//! Block-move test for faults in burst transfers.
+//!
+//! Every other test writes and reads words in place, a store or a vector at
+//! a time. Bulk copies reach the memory controller differently: as long runs
+//! of full cache-line bursts, read from one region while written to another.
+//! The block-move test (memtest86's test 5) fills the lower half of a block
+//! with a shifting pattern, copies it to the upper half and back down
+//! rotated by one cache line, again and again, and then verifies both
+//! halves. It runs once with each copy method the CPU offers, so a failure
+//! names the method that exposed it.
+
+use crate::cache::{self, WriteMode};
+use crate::error::CopyMethod;
+use crate::simd::{self, SimdLevel};
+use std::fmt;
+
+/// Round trips between the halves per copy method; each one moves every
+/// word of the block once.
+pub const ROUNDS: usize = 16;
+
+/// Words each round trip rotates the data by: one cache line.
+const SHIFT: usize = 8;
+
+/// Returns the pattern written at `index` before the first copy: a single
+/// set bit that shifts one position per cache line, inverted in every other
+/// word.
+pub fn value(index: usize) -> u64 {
+    let bit = 1u64 << (index / SHIFT % 64);
+    if index.is_multiple_of(2) {
+        bit
+    } else {
+        !bit
+    }
+}
+
+/// Returns the copy methods this CPU offers, in the order they run.
+pub fn methods() -> Vec<CopyMethod> {
+    let mut methods = vec![CopyMethod::CopyNonoverlapping];
+    if cfg!(target_arch = "x86_64") {
+        methods.push(CopyMethod::RepMovsb);
+    }
+    if SimdLevel::detect() != SimdLevel::Scalar {
+        methods.push(CopyMethod::Simd);
+    }
+    methods
+}
+
+/// Copies `src` into `dst` with `method`.
+///
+/// # Panics
+/// Panics if the slices differ in length.
+pub fn copy(method: CopyMethod, dst: &mut [u64], src: &[u64]) {
+    assert_eq!(dst.len(), src.len(), "copy needs slices of equal length");
+    match method {
+        CopyMethod::CopyNonoverlapping => copy_nonoverlapping(dst, src),
+        CopyMethod::RepMovsb => rep_movsb(dst, src),
+        CopyMethod::Simd => simd::store(SimdLevel::detect(), WriteMode::Cached, dst, src),
+    }
+}
+
+fn copy_nonoverlapping(dst: &mut [u64], src: &[u64]) {
+    // SAFETY: the slices have equal length and cannot overlap, since `dst`
+    // is borrowed mutably.
+    unsafe { std::ptr::copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr(), src.len()) }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn rep_movsb(dst: &mut [u64], src: &[u64]) {
+    // SAFETY: `rep movsb` copies `rcx` bytes upward from `rsi` to `rdi` (the
+    // ABI guarantees the direction flag is clear); both slices hold that many
+    // bytes and do not overlap.
+    unsafe {
+        std::arch::asm!(
+            "rep movsb",
+            inout("rcx") std::mem::size_of_val(src) => _,
+            inout("rdi") dst.as_mut_ptr() => _,
+            inout("rsi") src.as_ptr() => _,
+            options(nostack, preserves_flags),
+        );
+    }
+}
+
+/// Without `rep movsb` this is a plain copy; [`methods`] does not offer it.
+#[cfg(not(target_arch = "x86_64"))]
+fn rep_movsb(dst: &mut [u64], src: &[u64]) {
+    copy_nonoverlapping(dst, src);
+}
+
+/// A word that did not hold its expected value after the moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveFailure {
+    /// Word index within the block.
+    pub index: usize,
+    /// Value the word should have held.
+    pub expected: u64,
+    /// Value actually read.
+    pub actual: u64,
+    /// Copy method the data was moved with.
+    pub method: CopyMethod,
+}
+
+impl fmt::Display for MoveFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} block move at word 0x{:X}: expected 0x{:016X}, got 0x{:016X}",
+            self.method, self.index, self.expected, self.actual
+        )
+    }
+}
+
+/// Runs the block-move test over a memory block, copying with `method`.
+///
+/// Both halves are flushed from the CPU caches before every copy out of
+/// them and before they are verified, so copies read from DRAM. A trailing
+/// word of an odd-length block is left untouched.
+///
+/// # Returns
+/// * `Ok(())` if both halves held the expected arrangement
+/// * `Err(MoveFailure)` describing the first word that did not
+pub fn run(block: &mut [u64], method: CopyMethod) -> Result<(), MoveFailure> {
+    run_with(block, method, |dst, src| copy(method, dst, src))
+}
+
+fn run_with(
+    block: &mut [u64],
+    method: CopyMethod,
+    mut copy: impl FnMut(&mut [u64], &[u64]),
+) -> Result<(), MoveFailure> {
+    let half = block.len() / 2;
+    if half == 0 {
+        return Ok(());
+    }
+    let shift = SHIFT % half;
+    let (low, high) = block[..2 * half].split_at_mut(half);
+    for (index, word) in low.iter_mut().enumerate() {
+        cache::write_word(word, value(index));
+    }
+
+    for _ in 0..ROUNDS {
+        cache::flush(low);
+        copy(high, low);
+        cache::flush(high);
+        // Back down, rotated so each word lands `shift` words lower
+        copy(&mut low[..half - shift], &high[shift..]);
+        copy(&mut low[half - shift..], &high[..shift]);
+    }
+
+    // The lower half is rotated by every round trip, the upper half by all
+    // but the last
+    cache::flush(low);
+    cache::flush(high);
+    let halves = [
+        (&*low, 0, ROUNDS * shift),
+        (&*high, half, (ROUNDS - 1) * shift),
+    ];
+    for (words, base, rotation) in halves {
+        for (i, word) in words.iter().enumerate() {
+            let expected = value((i + rotation) % half);
+            let actual = cache::read_word(word);
+            if actual != expected {
+                return Err(MoveFailure {
+                    index: base + i,
+                    expected,
+                    actual,
+                    method,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_BLOCK_SIZE: usize = 1024;
+
+    #[test]
+    fn test_values() {
+        assert_eq!(value(0), 1);
+        assert_eq!(value(1), !1);
+        assert_eq!(value(8), 2);
+        assert_eq!(value(9), !2);
+        assert_eq!(value(64 * 8), 1);
+    }
+
+    #[test]
+    fn test_methods() {
+        let methods = methods();
+        assert_eq!(methods[0], CopyMethod::CopyNonoverlapping);
+        #[cfg(target_arch = "x86_64")]
+        assert!(methods.contains(&CopyMethod::RepMovsb));
+    }
+
+    #[test]
+    fn test_copy_methods_match() {
+        let src: Vec<u64> = (0..1001).map(|i| i * 0x9E37_79B9).collect();
+        for method in [
+            CopyMethod::CopyNonoverlapping,
+            CopyMethod::RepMovsb,
+            CopyMethod::Simd,
+        ] {
+            let mut dst = vec![0u64; src.len()];
+            copy(method, &mut dst[1..], &src[1..]);
+            assert_eq!(dst[0], 0, "{}", method);
+            assert_eq!(dst[1..], src[1..], "{}", method);
+        }
+    }
+
+    #[test]
+    fn test_passes_on_good_memory() {
+        for method in methods() {
+            let mut block = vec![0u64; TEST_BLOCK_SIZE + 1];
+            assert!(run(&mut block, method).is_ok(), "{}", method);
+            // Sixteen round trips rotated the lower half by 128 words
+            assert_eq!(block[0], value(128));
+            assert_eq!(block[TEST_BLOCK_SIZE / 2], value(120));
+            assert_eq!(block[TEST_BLOCK_SIZE], 0);
+        }
+    }
+
+    #[test]
+    fn test_catches_corrupted_copy() {
+        let mut block = vec![0u64; TEST_BLOCK_SIZE];
+        let mut copies = 0;
+        let failure = run_with(&mut block, CopyMethod::Simd, |dst, src| {
+            dst.copy_from_slice(src);
+            copies += 1;
+            // Second round trip, first copy back down
+            if copies == 5 {
+                dst[3] ^= 1 << 20;
+            }
+        })
+        .unwrap_err();
+
+        assert_eq!(failure.method, CopyMethod::Simd);
+        assert_eq!(failure.expected ^ failure.actual, 1 << 20);
+        assert!(failure.to_string().starts_with("SIMD block move at word"));
+    }
+}
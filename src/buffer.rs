@@ -0,0 +1,469 @@
+//! Page-aligned test memory, optionally backed by huge pages.
+//!
+//! With 4 KB pages a 64 MB block spans 16384 TLB entries, which hides some
+//! faults behind TLB behaviour and makes physical address lookups expensive.
+//! A [`TestBuffer`] can instead be backed by explicit hugetlbfs pages
+//! (`MAP_HUGETLB`) or transparent huge pages (`madvise(MADV_HUGEPAGE)`).
+//! When the requested pages are unavailable it falls back to the next
+//! smaller kind and records what it actually got.
+//!
+//! Huge pages need Linux; elsewhere buffers live on the heap.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Huge pages requested for test memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HugePages {
+    /// Regular pages.
+    #[default]
+    Off,
+    /// Transparent huge pages, requested with `madvise(MADV_HUGEPAGE)`.
+    Transparent,
+    /// Explicit 2 MB hugetlbfs pages.
+    Huge2M,
+    /// Explicit 1 GB hugetlbfs pages.
+    Huge1G,
+}
+
+impl HugePages {
+    /// Returns the command-line name (e.g., "2m").
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Transparent => "thp",
+            Self::Huge2M => "2m",
+            Self::Huge1G => "1g",
+        }
+    }
+
+    /// Describes the pages for messages (e.g., "2 MB huge pages").
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Off => "regular pages",
+            Self::Transparent => "transparent huge pages",
+            Self::Huge2M => "2 MB huge pages",
+            Self::Huge1G => "1 GB huge pages",
+        }
+    }
+
+    /// Returns the next smaller kind, tried when this one is unavailable.
+    pub fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Off => None,
+            Self::Transparent => Some(Self::Off),
+            Self::Huge2M => Some(Self::Transparent),
+            Self::Huge1G => Some(Self::Huge2M),
+        }
+    }
+}
+
+impl fmt::Display for HugePages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.slug())
+    }
+}
+
+impl FromStr for HugePages {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "4k" | "none" => Ok(Self::Off),
+            "thp" | "transparent" => Ok(Self::Transparent),
+            "2m" | "2mb" => Ok(Self::Huge2M),
+            "1g" | "1gb" => Ok(Self::Huge1G),
+            _ => Err(format!(
+                "Unknown huge page setting: '{}'. Use off, thp, 2m or 1g.",
+                s
+            )),
+        }
+    }
+}
+
+/// Pages that actually back a buffer.
+///
+/// Ordered from least to most huge-page backing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Backing {
+    /// Regular pages of the given size in bytes.
+    Normal(usize),
+    /// Transparent huge pages were requested; the kernel decides per extent.
+    Transparent(usize),
+    /// Explicit hugetlbfs pages of the given size in bytes.
+    HugeTlb(usize),
+}
+
+impl Backing {
+    /// Returns the size of the pages backing the buffer (the huge page size
+    /// for transparent huge pages).
+    pub fn page_size(&self) -> usize {
+        match *self {
+            Self::Normal(size) | Self::Transparent(size) | Self::HugeTlb(size) => size,
+        }
+    }
+
+    /// Returns the setting that asks for this backing.
+    pub fn kind(&self) -> HugePages {
+        match *self {
+            Self::Normal(_) => HugePages::Off,
+            Self::Transparent(_) => HugePages::Transparent,
+            Self::HugeTlb(size) if size >= 1 << 30 => HugePages::Huge1G,
+            Self::HugeTlb(_) => HugePages::Huge2M,
+        }
+    }
+}
+
+impl fmt::Display for Backing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = format_page_size(self.page_size());
+        match self {
+            Self::Normal(_) => write!(f, "{} pages", size),
+            Self::Transparent(_) => write!(f, "{} transparent huge pages", size),
+            Self::HugeTlb(_) => write!(f, "{} huge pages", size),
+        }
+    }
+}
+
+/// Formats a page size as "4 KB", "2 MB" or "1 GB".
+fn format_page_size(bytes: usize) -> String {
+    match bytes {
+        b if b >= 1 << 30 => format!("{} GB", b >> 30),
+        b if b >= 1 << 20 => format!("{} MB", b >> 20),
+        b => format!("{} KB", b >> 10),
+    }
+}
+
+/// Zero-initialized, page-aligned memory for test blocks.
+///
+/// Dereferences to a `[u64]` slice.
+pub struct TestBuffer {
+    ptr: std::ptr::NonNull<u64>,
+    words: usize,
+    backing: Backing,
+    /// Start and length of the whole mapping, including guard regions.
+    #[cfg(target_os = "linux")]
+    mapping: (usize, usize),
+    #[cfg(not(target_os = "linux"))]
+    _heap: Vec<u64>,
+}
+
+impl TestBuffer {
+    /// Allocates `words` zeroed words, falling back from `huge_pages` to
+    /// smaller pages when the requested kind is unavailable.
+    #[cfg(target_os = "linux")]
+    pub fn allocate(words: usize, huge_pages: HugePages) -> Result<Self, String> {
+        let bytes = words.max(1) * std::mem::size_of::<u64>();
+        let mut request = Some(huge_pages);
+        while let Some(pages) = request {
+            let mapped = match pages {
+                HugePages::Off => map_anonymous(bytes, crate::pagemap::page_size(), false),
+                HugePages::Transparent => match transparent_page_size() {
+                    Some(size) => map_anonymous(bytes, size, true),
+                    None => Err("transparent huge pages are disabled".to_string()),
+                },
+                HugePages::Huge2M => map_hugetlb(bytes, 2 << 20, libc::MAP_HUGE_2MB),
+                HugePages::Huge1G => map_hugetlb(bytes, 1 << 30, libc::MAP_HUGE_1GB),
+            };
+            match mapped {
+                Ok((ptr, mapping, backing)) => {
+                    return Ok(Self {
+                        ptr,
+                        words,
+                        backing,
+                        mapping,
+                    })
+                }
+                Err(e) if pages == HugePages::Off => return Err(e),
+                Err(_) => request = pages.fallback(),
+            }
+        }
+        unreachable!("the fallback chain ends with regular pages")
+    }
+
+    /// Allocates `words` zeroed words; huge pages are not available on this platform.
+    #[cfg(not(target_os = "linux"))]
+    pub fn allocate(words: usize, _huge_pages: HugePages) -> Result<Self, String> {
+        let mut heap = vec![0u64; words];
+        Ok(Self {
+            ptr: std::ptr::NonNull::new(heap.as_mut_ptr()).expect("Vec pointers are non-null"),
+            words,
+            backing: Backing::Normal(crate::pagemap::page_size()),
+            _heap: heap,
+        })
+    }
+
+    /// Returns the pages backing this buffer.
+    pub fn backing(&self) -> Backing {
+        self.backing
+    }
+
+    /// Returns how many bytes are backed by huge pages, or `None` if unknown.
+    ///
+    /// Transparent huge pages are only assigned when memory is first touched,
+    /// so measure after filling the buffer.
+    pub fn huge_bytes(&self) -> Option<u64> {
+        let bytes = std::mem::size_of_val(&**self) as u64;
+        match self.backing {
+            Backing::Normal(_) => Some(0),
+            Backing::HugeTlb(_) => Some(bytes),
+            Backing::Transparent(_) => self.anon_huge_bytes().map(|huge| huge.min(bytes)),
+        }
+    }
+
+    /// Reads the `AnonHugePages` count of this buffer's mapping from `/proc/self/smaps`.
+    #[cfg(target_os = "linux")]
+    fn anon_huge_bytes(&self) -> Option<u64> {
+        let smaps = std::fs::read_to_string("/proc/self/smaps").ok()?;
+        parse_anon_huge_kb(&smaps, self.ptr.as_ptr() as usize).map(|kb| kb * 1024)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn anon_huge_bytes(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl std::ops::Deref for TestBuffer {
+    type Target = [u64];
+
+    fn deref(&self) -> &[u64] {
+        // SAFETY: `ptr` points to `words` zero-initialized u64s that this
+        // buffer owns and keeps mapped until it is dropped.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.words) }
+    }
+}
+
+impl std::ops::DerefMut for TestBuffer {
+    fn deref_mut(&mut self) -> &mut [u64] {
+        // SAFETY: as in `deref`; `&mut self` guarantees exclusive access.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.words) }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for TestBuffer {
+    fn drop(&mut self) {
+        let (start, len) = self.mapping;
+        // SAFETY: the mapping was created by mmap with exactly this range and
+        // no slice of it outlives the buffer.
+        unsafe { libc::munmap(start as *mut libc::c_void, len) };
+    }
+}
+
+impl fmt::Debug for TestBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestBuffer")
+            .field("ptr", &self.ptr)
+            .field("words", &self.words)
+            .field("backing", &self.backing)
+            .finish()
+    }
+}
+
+/// A mapped region: the usable memory, the whole mapping and its backing.
+#[cfg(target_os = "linux")]
+type Mapped = (std::ptr::NonNull<u64>, (usize, usize), Backing);
+
+/// Maps `bytes` of anonymous memory aligned to `align`, between two
+/// inaccessible guard regions.
+///
+/// The guards keep the kernel from merging the mapping with its neighbours,
+/// so `/proc/self/smaps` reports on this buffer alone. With `transparent`,
+/// the memory is advised to use transparent huge pages of size `align`.
+#[cfg(target_os = "linux")]
+fn map_anonymous(bytes: usize, align: usize, transparent: bool) -> Result<Mapped, String> {
+    let page_size = crate::pagemap::page_size();
+    let bytes = bytes.next_multiple_of(page_size);
+    let align = align.max(page_size);
+    let len = bytes + 2 * align;
+    // SAFETY: requesting a fresh private anonymous mapping has no preconditions.
+    let base = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE,
+            -1,
+            0,
+        )
+    };
+    if base == libc::MAP_FAILED {
+        return Err(format!(
+            "Failed to map {} bytes: {}",
+            bytes,
+            std::io::Error::last_os_error()
+        ));
+    }
+    let base = base as usize;
+    // At least one page of guard before the memory and `align` bytes after it
+    let start = (base + page_size).next_multiple_of(align);
+    let unmap = || {
+        // SAFETY: unmapping the region mapped above, which nothing references yet.
+        unsafe { libc::munmap(base as *mut libc::c_void, len) };
+    };
+
+    // SAFETY: [start, start + bytes) lies inside the mapping created above.
+    let ret = unsafe {
+        libc::mprotect(
+            start as *mut libc::c_void,
+            bytes,
+            libc::PROT_READ | libc::PROT_WRITE,
+        )
+    };
+    if ret != 0 {
+        let e = std::io::Error::last_os_error();
+        unmap();
+        return Err(format!("Failed to map {} bytes: {}", bytes, e));
+    }
+
+    let backing = if transparent {
+        // SAFETY: advising on part of our own mapping has no memory-safety effect.
+        let ret = unsafe { libc::madvise(start as *mut libc::c_void, bytes, libc::MADV_HUGEPAGE) };
+        if ret != 0 {
+            let e = std::io::Error::last_os_error();
+            unmap();
+            return Err(format!("Failed to request transparent huge pages: {}", e));
+        }
+        Backing::Transparent(align)
+    } else {
+        Backing::Normal(page_size)
+    };
+    let ptr = std::ptr::NonNull::new(start as *mut u64).expect("mmap never returns null");
+    Ok((ptr, (base, len), backing))
+}
+
+/// Maps `bytes` rounded up to whole hugetlbfs pages of `page_size`.
+#[cfg(target_os = "linux")]
+fn map_hugetlb(bytes: usize, page_size: usize, size_flag: libc::c_int) -> Result<Mapped, String> {
+    let len = bytes.next_multiple_of(page_size);
+    // SAFETY: requesting a fresh private anonymous mapping has no preconditions.
+    let base = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_HUGETLB | size_flag,
+            -1,
+            0,
+        )
+    };
+    if base == libc::MAP_FAILED {
+        return Err(format!(
+            "Failed to map {} bytes of {} huge pages: {}",
+            len,
+            format_page_size(page_size),
+            std::io::Error::last_os_error()
+        ));
+    }
+    let ptr = std::ptr::NonNull::new(base as *mut u64).expect("mmap never returns null");
+    Ok((ptr, (base as usize, len), Backing::HugeTlb(page_size)))
+}
+
+/// Extracts `AnonHugePages` of the mapping starting at `start` from `smaps`.
+#[cfg(target_os = "linux")]
+fn parse_anon_huge_kb(smaps: &str, start: usize) -> Option<u64> {
+    let header = format!("{:x}-", start);
+    smaps
+        .lines()
+        .skip_while(|line| !line.starts_with(&header))
+        .skip(1)
+        // Field lines start with "Key:"; the next mapping's header does not
+        .take_while(|line| {
+            line.split_whitespace()
+                .next()
+                .is_some_and(|key| key.ends_with(':'))
+        })
+        .find_map(|line| line.strip_prefix("AnonHugePages:"))
+        .and_then(|value| value.trim().strip_suffix("kB")?.trim().parse().ok())
+}
+
+/// Returns the transparent huge page size, or `None` if they are disabled.
+#[cfg(target_os = "linux")]
+fn transparent_page_size() -> Option<usize> {
+    const THP_PATH: &str = "/sys/kernel/mm/transparent_hugepage";
+    let enabled = std::fs::read_to_string(format!("{}/enabled", THP_PATH)).ok()?;
+    if enabled.contains("[never]") {
+        return None;
+    }
+    std::fs::read_to_string(format!("{}/hpage_pmd_size", THP_PATH))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_huge_pages() {
+        assert_eq!("off".parse(), Ok(HugePages::Off));
+        assert_eq!("THP".parse(), Ok(HugePages::Transparent));
+        assert_eq!("2m".parse(), Ok(HugePages::Huge2M));
+        assert_eq!("1GB".parse(), Ok(HugePages::Huge1G));
+        assert!("3m".parse::<HugePages>().is_err());
+        assert_eq!(HugePages::Huge2M.to_string(), "2m");
+    }
+
+    #[test]
+    fn test_backing_display() {
+        assert_eq!(Backing::Normal(4096).to_string(), "4 KB pages");
+        assert_eq!(
+            Backing::Transparent(2 << 20).to_string(),
+            "2 MB transparent huge pages"
+        );
+        assert_eq!(Backing::HugeTlb(1 << 30).to_string(), "1 GB huge pages");
+        assert!(Backing::Normal(4096) < Backing::Transparent(2 << 20));
+        assert_eq!(Backing::HugeTlb(2 << 20).kind(), HugePages::Huge2M);
+        assert_eq!(Backing::HugeTlb(1 << 30).kind(), HugePages::Huge1G);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_anon_huge_kb() {
+        let smaps = "\
+7f0000000000-7f0000400000 rw-p 00000000 00:00 0
+Size:               4096 kB
+AnonHugePages:      2048 kB
+VmFlags: rd wr mr mw me ac hg
+7f0000600000-7f0000800000 rw-p 00000000 00:00 0
+Size:               2048 kB
+VmFlags: rd wr mr mw me ac
+7f0000800000-7f0000a00000 rw-p 00000000 00:00 0
+AnonHugePages:      2048 kB
+";
+        assert_eq!(parse_anon_huge_kb(smaps, 0x7f00_0000_0000), Some(2048));
+        assert_eq!(parse_anon_huge_kb(smaps, 0x7f00_0060_0000), None);
+        assert_eq!(parse_anon_huge_kb(smaps, 0x7f00_0000_1000), None);
+    }
+
+    #[test]
+    fn test_allocate_regular_pages() {
+        let mut buffer = TestBuffer::allocate(1000, HugePages::Off).unwrap();
+        assert_eq!(buffer.len(), 1000);
+        assert!(buffer.iter().all(|&word| word == 0));
+        assert_eq!(buffer.as_ptr() as usize % crate::pagemap::page_size(), 0);
+        assert!(matches!(buffer.backing(), Backing::Normal(_)));
+
+        buffer.fill(u64::MAX);
+        assert!(buffer.iter().all(|&word| word == u64::MAX));
+        assert_eq!(buffer.huge_bytes(), Some(0));
+    }
+
+    #[test]
+    fn test_allocate_falls_back() {
+        // Every request yields a usable buffer, whatever the machine provides
+        for pages in [HugePages::Transparent, HugePages::Huge2M, HugePages::Huge1G] {
+            let mut buffer = TestBuffer::allocate(1 << 18, pages).unwrap();
+            buffer.fill(0xA5A5_A5A5_A5A5_A5A5);
+            assert!(buffer.iter().all(|&word| word == 0xA5A5_A5A5_A5A5_A5A5));
+            let huge = buffer.huge_bytes().unwrap_or(0);
+            assert!(huge <= 1 << 21);
+            if let Backing::HugeTlb(size) = buffer.backing() {
+                assert_eq!(buffer.as_ptr() as usize % size, 0);
+            }
+        }
+    }
+}
@@ -0,0 +1,496 @@
+//! Making sure verification reads come from DRAM rather than the CPU caches.
+//!
+//! Reading a block straight after filling it mostly hits the caches: on CPUs
+//! with a large last-level cache a whole 64 MB block fits. Test memory is
+//! therefore accessed with volatile reads and writes the compiler cannot drop
+//! or merge, and flushed out of the caches between writing and verifying:
+//! with `clflushopt`/`clflush` on x86_64, `dc civac` on aarch64, and by
+//! reading through an eviction buffer larger than the last-level cache
+//! elsewhere. [`measure_latency`] shows whether the flush really sent reads
+//! to DRAM.
+//!
+//! Fills can also skip the caches on the way in: with [`WriteMode::Streaming`]
+//! they use non-temporal stores, which reach DRAM in write-combined bursts
+//! instead of whenever the caches write lines back.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// How test memory is pushed out of the CPU caches before it is verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheBypass {
+    /// `clflushopt` on every cache line (x86_64).
+    Clflushopt,
+    /// `clflush` on every cache line (x86_64 without `clflushopt`).
+    Clflush,
+    /// `dc civac` on every cache line (aarch64).
+    DcCivac,
+    /// Reading through a buffer twice the size of the last-level cache.
+    Evict,
+}
+
+impl CacheBypass {
+    /// Returns the strategy this CPU supports best.
+    pub fn detect() -> Self {
+        static DETECTED: OnceLock<CacheBypass> = OnceLock::new();
+        *DETECTED.get_or_init(|| {
+            #[cfg(target_arch = "x86_64")]
+            {
+                // CPUID leaf 7, EBX bit 23 advertises clflushopt
+                if std::arch::x86_64::__cpuid_count(7, 0).ebx & (1 << 23) != 0 {
+                    CacheBypass::Clflushopt
+                } else {
+                    CacheBypass::Clflush
+                }
+            }
+            #[cfg(target_arch = "aarch64")]
+            {
+                CacheBypass::DcCivac
+            }
+            #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+            {
+                CacheBypass::Evict
+            }
+        })
+    }
+
+    /// Returns the short name used in reports (e.g., "clflushopt").
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Clflushopt => "clflushopt",
+            Self::Clflush => "clflush",
+            Self::DcCivac => "dc civac",
+            Self::Evict => "eviction",
+        }
+    }
+
+    /// Describes the strategy for messages.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Clflushopt | Self::Clflush | Self::DcCivac => "cache lines flushed",
+            Self::Evict => "caches evicted with a buffer larger than the last-level cache",
+        }
+    }
+}
+
+impl fmt::Display for CacheBypass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Reads `word` with a load the compiler cannot remove or reuse.
+#[inline(always)]
+pub fn read_word(word: &u64) -> u64 {
+    // SAFETY: `word` is a valid, aligned reference for the duration of the read.
+    unsafe { std::ptr::read_volatile(word) }
+}
+
+/// Writes `value` to `word` with a store the compiler cannot remove or merge.
+#[inline(always)]
+pub fn write_word(word: &mut u64, value: u64) {
+    // SAFETY: `word` is a valid, aligned, exclusive reference.
+    unsafe { std::ptr::write_volatile(word, value) }
+}
+
+/// How pattern fills write test memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Regular stores through the cache hierarchy; write-backs decide when
+    /// data reaches DRAM.
+    #[default]
+    Cached,
+    /// Non-temporal stores that bypass the caches: `movnti`/`movntdq` on
+    /// x86_64, `stnp` on aarch64.
+    Streaming,
+}
+
+impl WriteMode {
+    /// Returns the command-line name (e.g., "streaming").
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Self::Cached => "cached",
+            Self::Streaming => "streaming",
+        }
+    }
+
+    /// Describes the stores for messages.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Cached => "regular stores through the caches",
+            Self::Streaming => "non-temporal stores straight to DRAM",
+        }
+    }
+}
+
+impl fmt::Display for WriteMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.slug())
+    }
+}
+
+impl FromStr for WriteMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "cached" | "normal" => Ok(Self::Cached),
+            "streaming" | "stream" | "non-temporal" | "nt" => Ok(Self::Streaming),
+            _ => Err(format!(
+                "Unknown write mode: '{}'. Use cached or streaming.",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes `value` to `word` with a non-temporal store that bypasses the caches.
+///
+/// Targets without non-temporal stores use [`write_word`].
+///
+/// # Safety
+/// The calling thread must call [`store_fence`] before `word` is accessed
+/// again by any thread, including itself.
+#[inline(always)]
+pub unsafe fn stream_word(word: &mut u64, value: u64) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: movnti is part of SSE2, which every x86_64 CPU has; `word`
+        // is a valid, aligned, exclusive reference.
+        unsafe { std::arch::x86_64::_mm_stream_si64(word as *mut u64 as *mut i64, value as i64) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        // SAFETY: `word` is a valid, aligned, exclusive reference; the pair of
+        // 32-bit halves covers exactly its 8 bytes, low half first.
+        unsafe {
+            std::arch::asm!(
+                "stnp {lo:w}, {hi:w}, [{ptr}]",
+                ptr = in(reg) word as *mut u64,
+                lo = in(reg) value,
+                hi = in(reg) value >> 32,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    write_word(word, value);
+}
+
+/// Orders this thread's earlier non-temporal stores before every later
+/// memory access.
+#[inline]
+pub fn store_fence() {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: sfence is part of SSE, which every x86_64 CPU has.
+        unsafe { std::arch::x86_64::_mm_sfence() };
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        // SAFETY: a data memory barrier has no memory-safety effect.
+        unsafe { std::arch::asm!("dmb ish", options(nostack, preserves_flags)) };
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    std::sync::atomic::fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Pushes `memory` out of the CPU caches with the detected strategy, so the
+/// next reads of it come from DRAM.
+pub fn flush(memory: &[u64]) {
+    flush_with(CacheBypass::detect(), memory);
+}
+
+/// Pushes `memory` out of the CPU caches with `bypass`.
+///
+/// Flush instructions the CPU does not support fall back to eviction.
+pub fn flush_with(bypass: CacheBypass, memory: &[u64]) {
+    if memory.is_empty() {
+        return;
+    }
+    match bypass {
+        #[cfg(target_arch = "x86_64")]
+        CacheBypass::Clflushopt if CacheBypass::detect() == CacheBypass::Clflushopt => {
+            for line in lines(memory, 64) {
+                // SAFETY: `line` points into `memory`; clflushopt only writes
+                // the line back and invalidates it, leaving its contents intact.
+                unsafe {
+                    std::arch::asm!("clflushopt [{}]", in(reg) line, options(nostack, preserves_flags));
+                }
+            }
+            // SAFETY: mfence is part of SSE2, which every x86_64 CPU has. It
+            // orders the flushes before the reads that follow.
+            unsafe { std::arch::x86_64::_mm_mfence() };
+        }
+        #[cfg(target_arch = "x86_64")]
+        CacheBypass::Clflush | CacheBypass::Clflushopt => {
+            for line in lines(memory, 64) {
+                // SAFETY: as above; clflush is part of SSE2.
+                unsafe { std::arch::x86_64::_mm_clflush(line) };
+            }
+            // SAFETY: mfence is part of SSE2, which every x86_64 CPU has.
+            unsafe { std::arch::x86_64::_mm_mfence() };
+        }
+        #[cfg(target_arch = "aarch64")]
+        CacheBypass::DcCivac => {
+            for line in lines(memory, dcache_line_size()) {
+                // SAFETY: `line` points into `memory`; dc civac cleans and
+                // invalidates the line to the point of coherency, leaving its
+                // contents intact. Linux allows it at EL0.
+                unsafe {
+                    std::arch::asm!("dc civac, {}", in(reg) line, options(nostack, preserves_flags));
+                }
+            }
+            // SAFETY: a data synchronization barrier has no memory-safety effect.
+            unsafe { std::arch::asm!("dsb ish", options(nostack, preserves_flags)) };
+        }
+        _ => evict(),
+    }
+}
+
+/// Returns a pointer into every cache line of `memory`, assuming lines of `line_size` bytes.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn lines(memory: &[u64], line_size: usize) -> impl Iterator<Item = *const u8> {
+    let start = memory.as_ptr() as usize;
+    let end = start + std::mem::size_of_val(memory);
+    let first = start & !(line_size - 1);
+    (first..end)
+        .step_by(line_size)
+        .map(|line| line as *const u8)
+}
+
+/// Returns the smallest data cache line size from `CTR_EL0`.
+#[cfg(target_arch = "aarch64")]
+fn dcache_line_size() -> usize {
+    let ctr: u64;
+    // SAFETY: reading CTR_EL0 has no side effects and Linux allows it at EL0.
+    unsafe { std::arch::asm!("mrs {}, ctr_el0", out(reg) ctr, options(nomem, nostack)) };
+    4 << ((ctr >> 16) & 0xF)
+}
+
+/// Fallback last-level cache size when sysfs does not report one.
+const DEFAULT_LLC_BYTES: usize = 32 * 1024 * 1024;
+
+/// Returns the size of the largest CPU cache in bytes.
+pub fn last_level_cache_bytes() -> usize {
+    static LLC: OnceLock<usize> = OnceLock::new();
+    *LLC.get_or_init(|| {
+        let Ok(entries) = std::fs::read_dir("/sys/devices/system/cpu/cpu0/cache") else {
+            return DEFAULT_LLC_BYTES;
+        };
+        entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| std::fs::read_to_string(entry.path().join("size")).ok())
+            .filter_map(|size| parse_cache_size(&size))
+            .max()
+            .unwrap_or(DEFAULT_LLC_BYTES)
+    })
+}
+
+/// Parses a sysfs cache size such as `307200K`.
+fn parse_cache_size(s: &str) -> Option<usize> {
+    let s = s.trim();
+    let (digits, unit) = match s.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => s.split_at(i),
+        None => (s, ""),
+    };
+    let value: usize = digits.parse().ok()?;
+    match unit {
+        "" => Some(value),
+        "K" => Some(value * 1024),
+        "M" => Some(value * 1024 * 1024),
+        _ => None,
+    }
+}
+
+thread_local! {
+    /// Per-thread buffer read through to evict the caches. Nonzero, so its
+    /// pages are really allocated rather than mapped to the shared zero page.
+    static EVICTION_BUFFER: Vec<u64> =
+        vec![1u64; 2 * last_level_cache_bytes() / std::mem::size_of::<u64>()];
+}
+
+/// Evicts the caches by reading one word of every line of the eviction buffer.
+fn evict() {
+    EVICTION_BUFFER.with(|buffer| {
+        let mut sum = 0u64;
+        for line in buffer.chunks(8) {
+            sum = sum.wrapping_add(read_word(&line[0]));
+        }
+        std::hint::black_box(sum);
+    });
+}
+
+/// Read latency of cached and flushed memory, measured by pointer chasing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Latency {
+    /// Strategy used to flush the probe memory.
+    pub bypass: CacheBypass,
+    /// Average read latency of cached memory in nanoseconds.
+    pub cached_ns: f64,
+    /// Average read latency right after flushing in nanoseconds.
+    pub flushed_ns: f64,
+}
+
+/// Extra latency a DRAM read adds over a cache hit, at the very least.
+///
+/// Real DRAM reads take 50-150 ns against a few ns for a cache hit; an
+/// absolute margin also holds in unoptimized builds, where loop overhead
+/// inflates both timings.
+const DRAM_MARGIN_NS: f64 = 25.0;
+
+impl Latency {
+    /// Returns whether flushed reads were clearly slower than cached ones,
+    /// i.e. whether verification reads reach DRAM.
+    pub fn reaches_dram(&self) -> bool {
+        self.flushed_ns >= self.cached_ns + DRAM_MARGIN_NS
+    }
+}
+
+/// Cache lines visited by the latency probe (1 MB).
+const PROBE_LINES: usize = 16384;
+
+/// Timed chases of each kind; the fastest counts, filtering out interruptions.
+const PROBE_ROUNDS: usize = 3;
+
+/// Measures read latency before and after flushing with the detected strategy.
+pub fn measure_latency() -> Latency {
+    measure_latency_with(CacheBypass::detect())
+}
+
+/// Measures read latency before and after flushing with `bypass`.
+///
+/// Every cache line of the probe holds the index of the next line to visit,
+/// in a shuffled cycle, so each read depends on the one before and the
+/// prefetcher cannot guess the next address.
+pub fn measure_latency_with(bypass: CacheBypass) -> Latency {
+    const WORDS_PER_LINE: usize = 8;
+    let mut probe = vec![0u64; PROBE_LINES * WORDS_PER_LINE];
+
+    // Sattolo's algorithm yields a single cycle through every line
+    let mut order: Vec<usize> = (0..PROBE_LINES).collect();
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    for i in (1..PROBE_LINES).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        order.swap(i, (state % i as u64) as usize);
+    }
+    for (line, &next) in order.iter().enumerate() {
+        write_word(&mut probe[line * WORDS_PER_LINE], next as u64);
+    }
+
+    let chase = |probe: &[u64]| {
+        let start = Instant::now();
+        let mut line = 0usize;
+        for _ in 0..PROBE_LINES {
+            line = read_word(&probe[line * WORDS_PER_LINE]) as usize;
+        }
+        std::hint::black_box(line);
+        start.elapsed().as_nanos() as f64 / PROBE_LINES as f64
+    };
+
+    // Warm the caches, then time cached and flushed reads
+    chase(&probe);
+    let cached_ns = (0..PROBE_ROUNDS)
+        .map(|_| chase(&probe))
+        .fold(f64::INFINITY, f64::min);
+    let flushed_ns = (0..PROBE_ROUNDS)
+        .map(|_| {
+            flush_with(bypass, &probe);
+            chase(&probe)
+        })
+        .fold(f64::INFINITY, f64::min);
+    Latency {
+        bypass,
+        cached_ns,
+        flushed_ns,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volatile_words() {
+        let mut word = 0u64;
+        write_word(&mut word, 0xDEAD_BEEF);
+        assert_eq!(read_word(&word), 0xDEAD_BEEF);
+    }
+
+    #[test]
+    fn test_stream_words() {
+        let mut memory = vec![0u64; 1024];
+        for (i, word) in memory.iter_mut().enumerate() {
+            // SAFETY: fenced below, before the words are read.
+            unsafe { stream_word(word, (i as u64) << 33 | i as u64) };
+        }
+        store_fence();
+        assert!(memory
+            .iter()
+            .enumerate()
+            .all(|(i, &w)| w == (i as u64) << 33 | i as u64));
+    }
+
+    #[test]
+    fn test_write_mode_from_str() {
+        for mode in [WriteMode::Cached, WriteMode::Streaming] {
+            assert_eq!(mode.slug().parse::<WriteMode>(), Ok(mode));
+        }
+        assert_eq!("NT".parse::<WriteMode>(), Ok(WriteMode::Streaming));
+        assert_eq!(WriteMode::default(), WriteMode::Cached);
+        assert!("uncached".parse::<WriteMode>().is_err());
+    }
+
+    #[test]
+    fn test_flush_keeps_contents() {
+        let memory: Vec<u64> = (0..4096).collect();
+        flush(&memory);
+        flush(&memory[3..5]);
+        flush(&[]);
+        assert!(memory.iter().enumerate().all(|(i, &w)| w == i as u64));
+    }
+
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    #[test]
+    fn test_lines() {
+        let memory = vec![0u64; 64];
+        let lines: Vec<*const u8> = lines(&memory[1..17], 64).collect();
+        let first = (memory[1..].as_ptr() as usize) & !63;
+        assert_eq!(lines.first(), Some(&(first as *const u8)));
+        // 128 bytes starting 8 bytes into a line span three lines at most
+        assert!(lines.len() == 2 || lines.len() == 3);
+    }
+
+    #[test]
+    fn test_parse_cache_size() {
+        assert_eq!(parse_cache_size("48K\n"), Some(48 * 1024));
+        assert_eq!(parse_cache_size("2M"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_cache_size("512"), Some(512));
+        assert_eq!(parse_cache_size("x"), None);
+    }
+
+    #[test]
+    fn test_measure_latency() {
+        let latency = measure_latency();
+        assert_eq!(latency.bypass, CacheBypass::detect());
+        assert!(latency.cached_ns > 0.0);
+        assert!(latency.flushed_ns > 0.0);
+
+        let latency = Latency {
+            bypass: CacheBypass::Clflush,
+            cached_ns: 70.0,
+            flushed_ns: 130.0,
+        };
+        assert!(latency.reaches_dram());
+        let latency = Latency {
+            cached_ns: 4.0,
+            flushed_ns: 12.0,
+            ..latency
+        };
+        assert!(!latency.reaches_dram());
+    }
+}
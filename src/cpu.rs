@@ -3,13 +3,29 @@
 //! This module provides multi-threaded CPU memory testing using
 //! the test patterns defined in the patterns module.
 
-use crate::error::FerritestError;
-use crate::patterns::TestPattern;
+use crate::address;
+use crate::affinity;
+use crate::block_move;
+use crate::buffer::{Backing, HugePages, TestBuffer};
+use crate::cache::{self, Latency, WriteMode};
+use crate::error::{CrossCheck, FerritestError, Hammering, MemoryErrorInfo, TestKind};
+use crate::inversions::{self, WordWidth};
+use crate::march::MarchTest;
+use crate::memlock;
+use crate::modulo;
+use crate::numa::{self, NumaNode};
+use crate::observer::{RunInfo, TestEvent, TestObserver};
+use crate::pagemap::{self, PageMap, PhysicalAddress};
+use crate::patterns::{derive_seed, TestPattern};
+use crate::retention::{self, Retention};
+use crate::rowhammer::{self, Rowhammer};
+use crate::simd::SimdLevel;
 use crate::stats::TestStats;
-use crate::traits::{MemoryTester, TestConfig, TestResult};
+use crate::traits::{MemoryTester, ProgressUpdate, TestConfig, TestResult};
 use crossbeam::channel;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use std::any::Any;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -17,20 +33,78 @@ use std::time::{Duration, Instant};
 /// Block size for memory testing (64 MB).
 pub const BLOCK_SIZE: usize = 64 * 1024 * 1024;
 
+/// Default cap on the number of errors recorded in one run.
+pub const DEFAULT_MAX_ERRORS: usize = 1000;
+
 /// Information about a detected memory error during CPU testing.
 #[derive(Debug)]
 pub struct CpuMemoryError {
-    /// The test pattern that detected the error.
-    pub pattern: TestPattern,
+    /// The test that detected the error.
+    pub test: TestKind,
     /// Byte offset within the block where the error was detected.
     pub offset: usize,
+    /// Index of the block within the thread's test memory.
+    pub block: usize,
     /// Thread ID that detected the error.
     pub thread_id: usize,
+    /// Value that was written.
+    pub expected: u64,
+    /// Value that was read back.
+    pub actual: u64,
+    /// Physical address of the failing word, resolved through the page map.
+    pub physical_address: PhysicalAddress,
+    /// Zero-based pass (iteration) in which the error was found.
+    pub pass: u64,
+    /// CPU core that read the failing word, when known.
+    pub core: Option<usize>,
+    /// Re-test of the failing page from another core (pinned pattern runs only).
+    pub cross_check: Option<CrossCheck>,
+}
+
+impl CpuMemoryError {
+    /// Creates an error found by `test` at byte `offset`, before the worker
+    /// resolves its physical address and records its pass and core.
+    pub fn new(
+        test: impl Into<TestKind>,
+        offset: usize,
+        thread_id: usize,
+        expected: u64,
+        actual: u64,
+    ) -> Self {
+        Self {
+            test: test.into(),
+            offset,
+            block: 0,
+            thread_id,
+            expected,
+            actual,
+            physical_address: PhysicalAddress::Unsupported,
+            pass: 0,
+            core: None,
+            cross_check: None,
+        }
+    }
+}
+
+impl From<&CpuMemoryError> for MemoryErrorInfo {
+    fn from(error: &CpuMemoryError) -> Self {
+        MemoryErrorInfo {
+            test: error.test.clone(),
+            offset: error.offset,
+            block: error.block,
+            source_id: error.thread_id,
+            expected: Some(error.expected),
+            actual: Some(error.actual),
+            physical_address: error.physical_address.known(),
+            pass: Some(error.pass),
+            core: error.core,
+            cross_check: error.cross_check,
+        }
+    }
 }
 
 /// CPU memory tester configuration.
 #[derive(Debug, Clone)]
-#[allow(dead_code)] // verbose field used in Issue #6
 pub struct CpuTesterConfig {
     /// Amount of memory to test in megabytes.
     pub memory_mb: usize,
@@ -42,6 +116,54 @@ pub struct CpuTesterConfig {
     pub timeout: Option<Duration>,
     /// Enable verbose output.
     pub verbose: bool,
+    /// Patterns to test (defaults to all patterns).
+    pub patterns: Vec<TestPattern>,
+    /// Spacing of the words the modulo-N pattern checks (at least 2).
+    pub modulo_n: usize,
+    /// Run the address-line test on each block after the patterns.
+    pub address_lines: bool,
+    /// Run the block-move test on each block after the address-line test.
+    pub block_move: bool,
+    /// March tests to run on each block after the patterns.
+    pub march_tests: Vec<MarchTest>,
+    /// Run the retention test on every block after the March tests.
+    pub retention: Option<Retention>,
+    /// Run the rowhammer test after the retention test (Linux x86_64 only;
+    /// ignored elsewhere).
+    pub rowhammer: Option<Rowhammer>,
+    /// Master seed from which every block seed is derived.
+    pub seed: u64,
+    /// Number of the first pass; block seeds derive from the pass number, so
+    /// starting at a failing pass replays it.
+    pub start_pass: u64,
+    /// Keep testing after an error instead of stopping at the first failing block.
+    pub keep_going: bool,
+    /// Stop once this many errors have been found (implies `keep_going` until then).
+    pub max_errors: Option<usize>,
+    /// Pin each worker thread to one CPU core (Linux only).
+    pub pin_threads: bool,
+    /// Cores to pin workers to, in order (defaults to every available core).
+    pub cpus: Vec<usize>,
+    /// NUMA nodes to bind worker memory to, assigned to workers round-robin
+    /// (empty leaves placement to the kernel; Linux only).
+    ///
+    /// Unpinned workers run on the CPUs of their memory's node.
+    pub numa_nodes: Vec<usize>,
+    /// Write each worker's blocks from their own node but verify them from
+    /// the next node, so every read crosses the interconnect.
+    ///
+    /// Applies to pattern tests; March tests run on the worker's thread.
+    pub cross_node: bool,
+    /// Lock test blocks into RAM with `mlock` so they cannot be swapped out.
+    pub lock_memory: bool,
+    /// Stop before testing if any block cannot be locked (implies `lock_memory`).
+    pub require_locked: bool,
+    /// Huge pages to back test memory with, falling back to smaller pages
+    /// when they are unavailable.
+    pub huge_pages: HugePages,
+    /// Stores used to fill blocks with patterns; March tests always use
+    /// regular stores.
+    pub write_mode: WriteMode,
 }
 
 impl Default for CpuTesterConfig {
@@ -52,6 +174,186 @@ impl Default for CpuTesterConfig {
             continuous: false,
             timeout: None,
             verbose: false,
+            patterns: TestPattern::all_patterns(),
+            modulo_n: modulo::DEFAULT_N,
+            address_lines: false,
+            block_move: false,
+            march_tests: Vec::new(),
+            retention: None,
+            rowhammer: None,
+            seed: rand::random(),
+            start_pass: 0,
+            keep_going: false,
+            max_errors: None,
+            pin_threads: false,
+            cpus: Vec::new(),
+            numa_nodes: Vec::new(),
+            cross_node: false,
+            lock_memory: true,
+            require_locked: false,
+            huge_pages: HugePages::Off,
+            write_mode: WriteMode::Cached,
+        }
+    }
+}
+
+impl CpuTesterConfig {
+    /// Returns the maximum number of errors recorded for the report.
+    pub fn error_cap(&self) -> usize {
+        self.max_errors.unwrap_or(DEFAULT_MAX_ERRORS)
+    }
+
+    /// Returns the cores workers are pinned to, or an empty list when unpinned.
+    pub fn pinned_cores(&self) -> Vec<usize> {
+        match (self.pin_threads, self.cpus.is_empty()) {
+            (false, _) => Vec::new(),
+            (true, true) => affinity::available_cpus(),
+            (true, false) => self.cpus.clone(),
+        }
+    }
+
+    /// Returns whether testing should stop once `errors` errors have been found.
+    pub fn stops_after(&self, errors: u64) -> bool {
+        match self.max_errors {
+            Some(max) => errors >= max as u64,
+            None => !self.keep_going && errors > 0,
+        }
+    }
+}
+
+/// Pattern totals of the workers pinned to one core.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreResult {
+    /// CPU core ID.
+    pub core: usize,
+    /// Bytes tested by this core's workers.
+    pub bytes_tested: u64,
+    /// Mismatching words this core's workers found.
+    pub errors_found: u64,
+}
+
+/// Pattern totals of the workers whose memory is bound to one NUMA node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeResult {
+    /// NUMA node ID.
+    pub node: usize,
+    /// Bytes tested in this node's memory.
+    pub bytes_tested: u64,
+    /// Mismatching words found in this node's memory.
+    pub errors_found: u64,
+    /// Bytes of test memory that could not be bound to this node and were
+    /// placed by the kernel instead.
+    pub unbound_bytes: u64,
+}
+
+/// How much of the tested memory was locked and resident in RAM.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Residency {
+    /// Bytes allocated for test blocks.
+    pub total_bytes: u64,
+    /// Bytes locked into RAM with `mlock`.
+    pub locked_bytes: u64,
+    /// Bytes resident in RAM at each worker's lowest measurement, or `None`
+    /// if residency could not be measured.
+    pub resident_bytes: Option<u64>,
+}
+
+impl Residency {
+    /// Returns whether every test block was locked.
+    pub fn fully_locked(&self) -> bool {
+        self.locked_bytes >= self.total_bytes
+    }
+
+    /// Returns the share of test memory that stayed resident, in percent.
+    pub fn resident_percent(&self) -> Option<f64> {
+        match (self.resident_bytes, self.total_bytes) {
+            (Some(_), 0) => Some(100.0),
+            (Some(resident), total) => Some(resident as f64 * 100.0 / total as f64),
+            (None, _) => None,
+        }
+    }
+
+    /// Returns an error unless every test block was locked.
+    pub fn ensure_locked(&self) -> Result<(), FerritestError> {
+        if self.fully_locked() {
+            return Ok(());
+        }
+        Err(FerritestError::MemoryLock {
+            locked_mb: self.locked_bytes / (1024 * 1024),
+            total_mb: self.total_bytes / (1024 * 1024),
+            limit: memlock::describe_limit(),
+        })
+    }
+}
+
+/// Which pages backed the tested memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageUsage {
+    /// Huge pages requested in the configuration.
+    pub requested: HugePages,
+    /// Pages backing the test memory; the smallest if workers got different ones.
+    pub backing: Backing,
+    /// Bytes allocated for test blocks.
+    pub total_bytes: u64,
+    /// Bytes backed by huge pages, or `None` if this could not be measured.
+    pub huge_bytes: Option<u64>,
+}
+
+impl PageUsage {
+    /// Returns whether some memory fell back to smaller pages than requested.
+    pub fn fell_back(&self) -> bool {
+        self.backing.kind() != self.requested
+    }
+
+    /// Returns the share of test memory backed by huge pages, in percent.
+    pub fn huge_percent(&self) -> Option<f64> {
+        match (self.huge_bytes, self.total_bytes) {
+            (Some(_), 0) => Some(0.0),
+            (Some(huge), total) => Some(huge as f64 * 100.0 / total as f64),
+            (None, _) => None,
+        }
+    }
+}
+
+/// Outcome of a CPU test run.
+#[derive(Debug)]
+pub struct CpuTestRun {
+    /// One result per pattern pass, ordered by pass and then pattern.
+    ///
+    /// Each result sums every thread's blocks; March tests are not included.
+    pub results: Vec<TestResult>,
+    /// Recorded errors, at most `CpuTesterConfig::error_cap()` of them.
+    pub errors: Vec<CpuMemoryError>,
+    /// Per-core pattern totals, ordered by core; empty unless threads are pinned.
+    pub cores: Vec<CoreResult>,
+    /// Per-node pattern totals, ordered by node; empty unless memory is bound to nodes.
+    pub nodes: Vec<NodeResult>,
+    /// How much of the test memory was locked and stayed resident.
+    pub residency: Residency,
+    /// Which pages backed the test memory.
+    pub pages: PageUsage,
+    /// How blocks were flushed from the CPU caches before verification, and
+    /// how much slower reads became after a flush.
+    pub cache: Latency,
+    /// Stores used to fill blocks with patterns.
+    pub write_mode: WriteMode,
+    /// Why a worker could not allocate its blocks, if one could not; the
+    /// other workers stop as soon as they notice.
+    pub allocation_error: Option<String>,
+    /// Errors found by the tests other than the patterns, keyed by
+    /// "address-lines", "block-move", the March test's name, "retention" or
+    /// "rowhammer"; tests that found none are absent.
+    ///
+    /// Unlike `errors`, these counts are not capped.
+    pub test_errors: BTreeMap<String, u64>,
+}
+
+impl CpuTestRun {
+    /// Returns an error if a worker could not allocate its blocks.
+    pub fn ensure_allocated(&self) -> Result<(), FerritestError> {
+        match &self.allocation_error {
+            Some(reason) => Err(FerritestError::Allocation(reason.clone())),
+            None => Ok(()),
         }
     }
 }
@@ -60,23 +362,50 @@ impl Default for CpuTesterConfig {
 ///
 /// Tests system RAM using multiple threads, each testing a portion
 /// of memory with various bit patterns.
-#[allow(dead_code)] // Methods used in Issue #6 (MemoryTester implementation)
 pub struct CpuTester {
     config: CpuTesterConfig,
     num_threads: usize,
+    /// Cores workers are pinned to, empty when unpinned.
+    cores: Vec<usize>,
+    /// NUMA topology, read only when memory is bound to nodes.
+    topology: Vec<NumaNode>,
+    observer: Option<Arc<dyn TestObserver>>,
+    /// Outcome of the last `run_tests` call.
+    last_run: Option<CpuTestRun>,
 }
 
-#[allow(dead_code)] // Methods used in Issue #6 (MemoryTester implementation)
 impl CpuTester {
     /// Creates a new CPU tester with the given configuration.
+    ///
+    /// The tester prints nothing; attach an observer to follow its progress.
     pub fn new(config: CpuTesterConfig) -> Self {
-        let num_threads = config.threads.unwrap_or_else(num_cpus::get);
+        let cores = config.pinned_cores();
+        let num_threads = match (config.threads, cores.len()) {
+            (Some(threads), _) => threads,
+            (None, 0) => num_cpus::get(),
+            (None, pinned) => pinned,
+        };
+        let topology = if config.numa_nodes.is_empty() {
+            Vec::new()
+        } else {
+            numa::nodes()
+        };
         Self {
             config,
             num_threads,
+            cores,
+            topology,
+            observer: None,
+            last_run: None,
         }
     }
 
+    /// Sends the events of every run to `observer`.
+    pub fn with_observer(mut self, observer: Arc<dyn TestObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
     /// Returns the name of this tester.
     pub fn name(&self) -> &'static str {
         "CPU/RAM"
@@ -84,11 +413,38 @@ impl CpuTester {
 
     /// Returns information about the device being tested.
     pub fn device_info(&self) -> String {
-        format!(
+        let info = format!(
             "{} threads, {} MB block size",
             self.num_threads,
             BLOCK_SIZE / (1024 * 1024)
-        )
+        );
+        let info = if self.cores.is_empty() {
+            info
+        } else {
+            format!(
+                "{}, pinned to cores {}",
+                info,
+                affinity::format_cpu_list(&self.cores)
+            )
+        };
+        match (self.config.numa_nodes.is_empty(), self.config.cross_node) {
+            (true, _) => info,
+            (false, false) => format!(
+                "{}, memory on NUMA node {}",
+                info,
+                affinity::format_cpu_list(&self.config.numa_nodes)
+            ),
+            (false, true) => format!(
+                "{}, memory on NUMA node {} verified from the next node",
+                info,
+                affinity::format_cpu_list(&self.config.numa_nodes)
+            ),
+        }
+    }
+
+    /// Returns the configuration this tester runs with.
+    pub fn config(&self) -> &CpuTesterConfig {
+        &self.config
     }
 
     /// Returns the number of threads used for testing.
@@ -96,74 +452,134 @@ impl CpuTester {
         self.num_threads
     }
 
+    /// Returns the cores workers are pinned to, or an empty slice when unpinned.
+    pub fn cores(&self) -> &[usize] {
+        &self.cores
+    }
+
+    /// Returns where worker `id` runs: its core, the core that cross-checks
+    /// its failures and the NUMA node its memory is bound to.
+    fn worker(&self, id: usize) -> Worker {
+        let core = |i: usize| self.cores.get(i % self.cores.len().max(1)).copied();
+        let node = match self.config.numa_nodes.as_slice() {
+            [] => None,
+            nodes => Some(nodes[id % nodes.len()]),
+        };
+        let cpus_of = |node: usize| {
+            self.topology
+                .iter()
+                .find(|n| n.id == node)
+                .map(|n| n.cpus.clone())
+                .unwrap_or_default()
+        };
+        let reader = node.map(|node| {
+            self.config
+                .cross_node
+                .then(|| numa::next_node(&self.topology, node))
+                .flatten()
+                .unwrap_or(node)
+        });
+        Worker {
+            id,
+            core: core(id),
+            cross_core: if self.cores.len() > 1 {
+                core(id + 1)
+            } else {
+                None
+            },
+            node,
+            node_cpus: reader.map(cpus_of).unwrap_or_default(),
+            writer_cpus: match (node, reader) {
+                (Some(node), Some(reader)) if node != reader => cpus_of(node),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    /// Returns the errors recorded by the last `MemoryTester::run_tests` call.
+    ///
+    /// `run` hands its errors back directly instead.
+    pub fn errors(&self) -> &[CpuMemoryError] {
+        self.last_run.as_ref().map_or(&[], |run| &run.errors)
+    }
+
+    /// Returns the whole outcome of the last `MemoryTester::run_tests` call,
+    /// with the per-core, per-node, residency, page and cache results that
+    /// the trait cannot carry.
+    pub fn last_run(&self) -> Option<&CpuTestRun> {
+        self.last_run.as_ref()
+    }
+
     /// Runs the CPU memory test.
     ///
-    /// Returns a vector of memory errors found, or empty if no errors.
-    pub fn run(&self, stats: Arc<TestStats>, should_stop: Arc<AtomicBool>) -> Vec<CpuMemoryError> {
+    /// Returns the per-pass results and the memory errors found.
+    pub fn run(&self, stats: Arc<TestStats>, should_stop: Arc<AtomicBool>) -> CpuTestRun {
         let total_blocks = (self.config.memory_mb * 1024 * 1024) / BLOCK_SIZE;
         let blocks_per_thread = total_blocks.div_ceil(self.num_threads);
-        let actual_memory_mb = (blocks_per_thread * self.num_threads * BLOCK_SIZE) / (1024 * 1024);
-
-        println!("Memory Stress Test");
-        println!("==================");
-        println!(
-            "Memory to test: {} MB (requested: {} MB)",
-            actual_memory_mb, self.config.memory_mb
-        );
-        println!("Block size: {} MB", BLOCK_SIZE / (1024 * 1024));
-        println!("Threads: {}", self.num_threads);
-        println!("Blocks per thread: {}", blocks_per_thread);
-        println!(
-            "Mode: {}",
-            if self.config.continuous {
-                "Continuous"
-            } else {
-                "Single pass"
+        let emit = |event| {
+            if let Some(observer) = &self.observer {
+                observer.on_event(&event);
             }
-        );
+        };
 
-        if let Some(timeout) = self.config.timeout {
-            println!("Duration: {:?}", timeout);
-        }
-        println!();
+        // Check up front that flushed reads really go to DRAM
+        let cache = cache::measure_latency();
+
+        emit(TestEvent::RunStarted(RunInfo {
+            device: self.name().to_string(),
+            memory_mb: (blocks_per_thread * self.num_threads * BLOCK_SIZE) / (1024 * 1024),
+            requested_mb: self.config.memory_mb,
+            block_size: BLOCK_SIZE,
+            threads: self.num_threads,
+            blocks_per_thread,
+            patterns: self.config.patterns.clone(),
+            modulo_n: self
+                .config
+                .patterns
+                .contains(&TestPattern::ModuloN)
+                .then_some(self.config.modulo_n),
+            address_lines: self.config.address_lines,
+            block_move: self.config.block_move,
+            march_tests: self
+                .config
+                .march_tests
+                .iter()
+                .map(|m| m.name().to_string())
+                .collect(),
+            retention: self.config.retention,
+            rowhammer: self.config.rowhammer.filter(|_| rowhammer::supported()),
+            seed: self.config.seed,
+            continuous: self.config.continuous,
+            timeout: self.config.timeout,
+            cores: self.cores.clone(),
+            numa_nodes: self.config.numa_nodes.clone(),
+            cross_node: self.config.cross_node,
+            huge_pages: self.config.huge_pages,
+            cache_bypass: cache.bypass,
+            simd: SimdLevel::detect(),
+            write_mode: self.config.write_mode,
+        }));
 
         let (error_tx, error_rx) = channel::bounded(10);
-        let multi_progress = MultiProgress::new();
 
         let start_time = Instant::now();
         let timeout = self.config.timeout;
-        let continuous = self.config.continuous;
 
-        // Stats monitoring thread
+        // Monitoring thread: enforces the timeout and publishes statistics
         let stats_clone = Arc::clone(&stats);
         let should_stop_clone = Arc::clone(&should_stop);
+        let observer = self.observer.clone();
         let stats_thread = std::thread::spawn(move || {
-            let stats_progress = ProgressBar::new_spinner();
-            stats_progress.set_style(
-                ProgressStyle::default_spinner()
-                    .template("{spinner:.green} {msg}")
-                    .unwrap(),
-            );
-
             while !should_stop_clone.load(Ordering::Relaxed) {
                 let elapsed = start_time.elapsed();
-                let bytes = stats_clone.get_bytes();
-                let errors = stats_clone.get_errors();
-                let tests = stats_clone.get_tests();
-                let mb_per_sec = if elapsed.as_secs_f64() > 0.0 {
-                    (bytes as f64 / (1024.0 * 1024.0)) / elapsed.as_secs_f64()
-                } else {
-                    0.0
-                };
-
-                stats_progress.set_message(format!(
-                    "Elapsed: {:.1}s | Tested: {} MB | Speed: {:.2} MB/s | Tests: {} | Errors: {}",
-                    elapsed.as_secs_f64(),
-                    bytes / (1024 * 1024),
-                    mb_per_sec,
-                    tests,
-                    errors
-                ));
+                if let Some(observer) = &observer {
+                    observer.on_event(&TestEvent::Stats {
+                        elapsed,
+                        bytes_tested: stats_clone.get_bytes(),
+                        tests_completed: stats_clone.get_tests(),
+                        errors_found: stats_clone.get_errors(),
+                    });
+                }
 
                 if let Some(timeout_duration) = timeout {
                     if elapsed >= timeout_duration {
@@ -174,7 +590,6 @@ impl CpuTester {
 
                 std::thread::sleep(Duration::from_millis(100));
             }
-            stats_progress.finish_with_message("Statistics reporting complete");
         });
 
         // Worker threads
@@ -183,26 +598,20 @@ impl CpuTester {
             let stats = Arc::clone(&stats);
             let should_stop = Arc::clone(&should_stop);
             let error_tx = error_tx.clone();
-            let progress = multi_progress.add(ProgressBar::new(
-                (blocks_per_thread * TestPattern::all_patterns().len()) as u64,
-            ));
-            progress.set_style(
-                ProgressStyle::default_bar()
-                    .template("{msg}\n{bar:40.cyan/blue} {pos}/{len}")
-                    .unwrap()
-                    .progress_chars("=>-"),
-            );
+            let config = self.config.clone();
+            let observer = self.observer.clone();
+            let worker = self.worker(thread_id);
 
             let handle = std::thread::spawn(move || {
                 worker_thread(
-                    thread_id,
+                    worker,
                     blocks_per_thread,
+                    &config,
                     stats,
                     should_stop,
                     error_tx,
-                    progress,
-                    continuous,
-                );
+                    observer.as_deref(),
+                )
             });
             thread_handles.push(handle);
         }
@@ -210,126 +619,939 @@ impl CpuTester {
         drop(error_tx);
 
         // Error collector thread
+        let error_cap = self.config.error_cap();
         let error_handler = std::thread::spawn(move || {
             let mut errors = Vec::new();
             while let Ok(error) = error_rx.recv() {
-                errors.push(error);
+                if errors.len() < error_cap {
+                    errors.push(error);
+                }
             }
             errors
         });
 
-        // Wait for all worker threads
-        for handle in thread_handles {
-            handle.join().expect("Thread panicked");
+        // Wait for all worker threads, merging their passes by (pass, pattern)
+        // and their totals by core and node
+        let mut passes: BTreeMap<(u64, usize), TestResult> = BTreeMap::new();
+        let mut cores: BTreeMap<usize, CoreResult> = BTreeMap::new();
+        let mut nodes: BTreeMap<usize, NodeResult> = BTreeMap::new();
+        let mut residency = Residency {
+            resident_bytes: Some(0),
+            ..Default::default()
+        };
+        let mut backing: Option<Backing> = None;
+        let mut huge_bytes = Some(0);
+        let mut allocation_error = None;
+        let mut test_errors: BTreeMap<String, u64> = BTreeMap::new();
+        for (thread_id, handle) in thread_handles.into_iter().enumerate() {
+            let outcome = handle.join().expect("Thread panicked");
+            if let Some(e) = outcome.allocation_error {
+                allocation_error.get_or_insert(e);
+                continue;
+            }
+            backing = Some(backing.map_or(outcome.backing, |b| b.min(outcome.backing)));
+            huge_bytes = huge_bytes
+                .zip(outcome.huge_bytes)
+                .map(|(sum, worker)| sum + worker);
+            for (name, found) in outcome.test_errors {
+                *test_errors.entry(name).or_default() += found;
+            }
+            residency.total_bytes += outcome.residency.total_bytes;
+            residency.locked_bytes += outcome.residency.locked_bytes;
+            residency.resident_bytes = residency
+                .resident_bytes
+                .zip(outcome.residency.resident_bytes)
+                .map(|(sum, worker)| sum + worker);
+            let worker_results = outcome.results;
+            let bytes_tested: u64 = worker_results.iter().map(|r| r.bytes_tested).sum();
+            let errors_found: u64 = worker_results.iter().map(|r| r.errors_found).sum();
+            let worker = self.worker(thread_id);
+            if let Some(core) = worker.core {
+                let totals = cores.entry(core).or_insert(CoreResult {
+                    core,
+                    bytes_tested: 0,
+                    errors_found: 0,
+                });
+                totals.bytes_tested += bytes_tested;
+                totals.errors_found += errors_found;
+            }
+            if let Some(node) = worker.node {
+                let totals = nodes.entry(node).or_insert(NodeResult {
+                    node,
+                    bytes_tested: 0,
+                    errors_found: 0,
+                    unbound_bytes: 0,
+                });
+                totals.bytes_tested += bytes_tested;
+                totals.errors_found += errors_found;
+                totals.unbound_bytes += outcome.unbound_bytes;
+            }
+            for result in worker_results {
+                let pattern_idx = self
+                    .config
+                    .patterns
+                    .iter()
+                    .position(|&p| p == result.pattern)
+                    .unwrap_or_default();
+                passes
+                    .entry((result.pass, pattern_idx))
+                    .and_modify(|merged| {
+                        merged.bytes_tested += result.bytes_tested;
+                        merged.errors_found += result.errors_found;
+                        merged.duration_ms = merged.duration_ms.max(result.duration_ms);
+                    })
+                    .or_insert(result);
+            }
         }
 
         should_stop.store(true, Ordering::Relaxed);
         stats_thread.join().expect("Stats thread panicked");
 
-        error_handler.join().expect("Error handler thread panicked")
+        let results: Vec<TestResult> = passes.into_values().collect();
+        emit(TestEvent::RunFinished {
+            duration: start_time.elapsed(),
+            results: results.clone(),
+        });
+
+        CpuTestRun {
+            results,
+            errors: error_handler.join().expect("Error handler thread panicked"),
+            cores: cores.into_values().collect(),
+            nodes: nodes.into_values().collect(),
+            residency,
+            pages: PageUsage {
+                requested: self.config.huge_pages,
+                backing: backing.unwrap_or(Backing::Normal(pagemap::page_size())),
+                total_bytes: residency.total_bytes,
+                huge_bytes,
+            },
+            cache,
+            write_mode: self.config.write_mode,
+            allocation_error,
+            test_errors,
+        }
+    }
+}
+
+/// Seed component separating the random-access indices from the pattern data.
+const RANDOM_ACCESS_STREAM: u64 = 1;
+
+/// Seed component separating the rowhammer aggressor picks from block seeds.
+const ROWHAMMER_STREAM: u64 = u64::MAX;
+
+/// Records every word in `block` that does not match `pattern`.
+///
+/// Each mismatch is counted in `stats` and in the returned total, but at most
+/// `max_records` are returned.
+fn collect_mismatches(
+    block: &[u64],
+    pattern: TestPattern,
+    seed: u64,
+    thread_id: usize,
+    stats: &TestStats,
+    max_records: usize,
+) -> (u64, Vec<CpuMemoryError>) {
+    if pattern.verify_block(block, seed).is_ok() {
+        return (0, Vec::new());
+    }
+
+    let mut found = 0;
+    let mut errors = Vec::new();
+    for mismatch in pattern.mismatches(block, seed) {
+        stats.add_error();
+        found += 1;
+        if errors.len() < max_records {
+            errors.push(CpuMemoryError::new(
+                TestKind::Pattern(pattern),
+                mismatch.index * std::mem::size_of::<u64>(),
+                thread_id,
+                mismatch.expected,
+                mismatch.actual,
+            ));
+        }
+    }
+    (found, errors)
+}
+
+/// Where and how a block is written before it is verified.
+#[derive(Debug, Clone, Copy, Default)]
+struct BlockWriter<'a> {
+    /// CPUs to write from; empty writes from the calling thread.
+    cpus: &'a [usize],
+    /// Stores used to write the pattern.
+    mode: WriteMode,
+}
+
+impl BlockWriter<'_> {
+    /// Fills `block` with `pattern`, from a thread on `cpus` if it can be
+    /// restricted to them and from the calling thread otherwise.
+    fn fill(&self, block: &mut [u64], pattern: TestPattern, seed: u64) {
+        let level = SimdLevel::detect();
+        let on_cpus = !self.cpus.is_empty()
+            && run_on(self.cpus, || {
+                pattern.fill_block_with(level, self.mode, block, seed)
+            })
+            .is_some();
+        if !on_cpus {
+            pattern.fill_block_with(level, self.mode, block, seed);
+        }
     }
 }
 
 /// Tests a single memory block with the given pattern.
+///
+/// The block is written by `writer`, flushed from the CPU caches and
+/// verified from the calling thread. Returns the number of
+/// mismatching words found and up to `max_records` of them.
+///
+/// Moving-inversions patterns run entirely on the calling thread.
 fn test_memory_block(
     block: &mut [u64],
     pattern: TestPattern,
     seed: u64,
     thread_id: usize,
     stats: &TestStats,
-) -> Option<CpuMemoryError> {
-    pattern.fill_block(block, seed);
+    max_records: usize,
+    writer: &BlockWriter,
+) -> (u64, Vec<CpuMemoryError>) {
+    if let Some(width) = pattern.inversion_width() {
+        return test_inversion_block(block, pattern, width, seed, thread_id, stats, max_records);
+    }
+
+    writer.fill(block, pattern, seed);
+    cache::flush(block);
 
     stats.add_bytes(std::mem::size_of_val(block) as u64);
 
-    if let Err(offset) = pattern.verify_block(block, seed) {
-        stats.add_error();
-        return Some(CpuMemoryError {
-            pattern,
-            offset,
-            thread_id,
-        });
+    let (found, errors) = collect_mismatches(block, pattern, seed, thread_id, stats, max_records);
+    if found > 0 {
+        return (found, errors);
     }
 
     // Random access test to stress the memory
     let block_len = block.len();
-    let mut rng = rand::thread_rng();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(derive_seed(seed, &[RANDOM_ACCESS_STREAM]));
+    cache::flush(block);
     for _ in 0..1000 {
         let idx = rng.gen_range(0..block_len);
-        let _read = block[idx];
+        cache::read_word(&block[idx]);
+    }
+
+    cache::flush(block);
+    let (found, errors) = collect_mismatches(block, pattern, seed, thread_id, stats, max_records);
+    if found == 0 {
+        stats.add_test();
+    }
+    (found, errors)
+}
+
+/// Runs a moving-inversions pattern over a memory block.
+///
+/// The seed picks the first rotation, so blocks and passes start from
+/// different bits. Returns at most the first failing read.
+fn test_inversion_block(
+    block: &mut [u64],
+    pattern: TestPattern,
+    width: WordWidth,
+    seed: u64,
+    thread_id: usize,
+    stats: &TestStats,
+    max_records: usize,
+) -> (u64, Vec<CpuMemoryError>) {
+    let first_rotation = (seed % u64::from(width.bits())) as u32;
+    let result = inversions::run(block, width, first_rotation);
+
+    stats.add_bytes(
+        (std::mem::size_of_val(block) * inversions::OPS_PER_WORD * width.bits() as usize) as u64,
+    );
+
+    let Err(failure) = result else {
+        stats.add_test();
+        return (0, Vec::new());
+    };
+    stats.add_error();
+    let mut errors = vec![CpuMemoryError::new(
+        TestKind::MovingInversions(pattern, failure.step),
+        failure.index * std::mem::size_of::<u64>(),
+        thread_id,
+        failure.expected,
+        failure.actual,
+    )];
+    errors.truncate(max_records);
+    (1, errors)
+}
+
+/// Runs the modulo-N pattern over a memory block with spacing `n`.
+///
+/// The seed picks the pattern, so blocks and passes write different values.
+/// Returns at most the first failing word.
+fn test_modulo_block(
+    block: &mut [u64],
+    n: usize,
+    seed: u64,
+    thread_id: usize,
+    stats: &TestStats,
+    max_records: usize,
+) -> (u64, Vec<CpuMemoryError>) {
+    let p = modulo::pattern(seed);
+    let result = modulo::run(block, n, p);
+
+    // Every phase rewrites the whole block, once per polarity
+    stats.add_bytes((std::mem::size_of_val(block) * n * 2) as u64);
+
+    let Err(failure) = result else {
+        stats.add_test();
+        return (0, Vec::new());
+    };
+    stats.add_error();
+    let mut errors = vec![CpuMemoryError::new(
+        TestKind::ModuloN(failure.phase),
+        failure.index * std::mem::size_of::<u64>(),
+        thread_id,
+        failure.expected,
+        failure.actual,
+    )];
+    errors.truncate(max_records);
+    (1, errors)
+}
+
+/// Runs a single March test over a memory block.
+fn test_march_block(
+    block: &mut [u64],
+    march: &MarchTest,
+    thread_id: usize,
+    stats: &TestStats,
+) -> Option<CpuMemoryError> {
+    let result = march.run(block);
+
+    stats.add_bytes((std::mem::size_of_val(block) * march.ops_per_word()) as u64);
+
+    if let Err(failure) = result {
+        stats.add_error();
+        return Some(CpuMemoryError::new(
+            TestKind::March(failure.to_string()),
+            failure.index * std::mem::size_of::<u64>(),
+            thread_id,
+            failure.expected,
+            failure.actual,
+        ));
     }
 
-    if let Err(offset) = pattern.verify_block(block, seed) {
+    stats.add_test();
+    None
+}
+
+/// Runs the address-line test over a memory block.
+fn test_address_block(
+    block: &mut [u64],
+    thread_id: usize,
+    stats: &TestStats,
+) -> Option<CpuMemoryError> {
+    let result = address::run(block);
+
+    stats.add_bytes((address::probes(block.len()).len() * std::mem::size_of::<u64>()) as u64);
+
+    if let Err(fault) = result {
         stats.add_error();
-        return Some(CpuMemoryError {
-            pattern,
-            offset,
+        return Some(CpuMemoryError::new(
+            TestKind::AddressLines(fault.line()),
+            fault.changed * std::mem::size_of::<u64>(),
             thread_id,
-        });
+            fault.expected,
+            fault.actual,
+        ));
+    }
+
+    stats.add_test();
+    None
+}
+
+/// Runs the block-move test over a memory block with every copy method,
+/// stopping at the first one that fails.
+fn test_block_move_block(
+    block: &mut [u64],
+    thread_id: usize,
+    stats: &TestStats,
+) -> Option<CpuMemoryError> {
+    for method in block_move::methods() {
+        let result = block_move::run(block, method);
+
+        stats.add_bytes((std::mem::size_of_val(block) * block_move::ROUNDS) as u64);
+
+        if let Err(failure) = result {
+            stats.add_error();
+            return Some(CpuMemoryError::new(
+                TestKind::BlockMove(method),
+                failure.index * std::mem::size_of::<u64>(),
+                thread_id,
+                failure.expected,
+                failure.actual,
+            ));
+        }
     }
 
     stats.add_test();
     None
 }
 
+/// Verifies a block the retention test left idle for `idle`.
+///
+/// Returns the number of mismatching words found and up to `max_records` of
+/// them.
+fn test_retention_block(
+    block: &[u64],
+    pattern: TestPattern,
+    idle: Duration,
+    thread_id: usize,
+    stats: &TestStats,
+    max_records: usize,
+) -> (u64, Vec<CpuMemoryError>) {
+    cache::flush(block);
+    stats.add_bytes(std::mem::size_of_val(block) as u64);
+
+    // Solid patterns ignore the seed
+    let (found, mut errors) = collect_mismatches(block, pattern, 0, thread_id, stats, max_records);
+    for error in &mut errors {
+        error.test = TestKind::Retention(idle);
+    }
+    if found == 0 {
+        stats.add_test();
+    }
+    (found, errors)
+}
+
+/// Checks a block for flips after an aggressor pair was hammered.
+///
+/// `hammering` describes the pair for a flip at a virtual address. Returns
+/// the number of flipped words found and up to `max_records` of them.
+fn test_rowhammer_block(
+    block: &[u64],
+    pattern: TestPattern,
+    mut hammering: impl FnMut(usize) -> Hammering,
+    thread_id: usize,
+    stats: &TestStats,
+    max_records: usize,
+) -> (u64, Vec<CpuMemoryError>) {
+    cache::flush(block);
+    stats.add_bytes(std::mem::size_of_val(block) as u64);
+
+    // Solid patterns ignore the seed
+    let (found, mut errors) = collect_mismatches(block, pattern, 0, thread_id, stats, max_records);
+    for error in &mut errors {
+        error.test = TestKind::Rowhammer(hammering(block.as_ptr() as usize + error.offset));
+    }
+    if found == 0 {
+        stats.add_test();
+    }
+    (found, errors)
+}
+
+/// Where a worker thread runs and where its memory lives.
+#[derive(Debug, Clone)]
+struct Worker {
+    /// Worker thread index.
+    id: usize,
+    /// Core the worker is pinned to.
+    core: Option<usize>,
+    /// Core that re-tests pages this worker finds failing.
+    cross_core: Option<usize>,
+    /// NUMA node the worker's blocks are bound to.
+    node: Option<usize>,
+    /// CPUs the worker runs on when not pinned to a core: those of its
+    /// memory's node, or of the next node in cross-node mode.
+    node_cpus: Vec<usize>,
+    /// CPUs that write the worker's blocks in cross-node mode.
+    writer_cpus: Vec<usize>,
+}
+
+/// Runs `f` on a scoped thread restricted to `cpus`.
+///
+/// Returns `None` if the thread could not be restricted.
+fn run_on<T: Send>(cpus: &[usize], f: impl FnOnce() -> T + Send) -> Option<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                affinity::restrict_current_thread(cpus).ok()?;
+                Some(f())
+            })
+            .join()
+            .expect("Pinned helper thread panicked")
+    })
+}
+
+/// Re-tests `block` with `pattern` from a thread pinned to `core`, writing
+/// it with `mode`.
+///
+/// Returns the addresses of the pages that failed again, divided by the page
+/// size, or `None` if the thread could not be pinned.
+fn cross_check_pages(
+    block: &mut [u64],
+    pattern: TestPattern,
+    seed: u64,
+    core: usize,
+    mode: WriteMode,
+) -> Option<HashSet<usize>> {
+    let page_size = pagemap::page_size();
+    let base = block.as_ptr() as usize;
+    run_on(&[core], || {
+        pattern.fill_block_with(SimdLevel::detect(), mode, block, seed);
+        cache::flush(block);
+        pattern
+            .mismatches(block, seed)
+            .map(|m| (base + m.index * std::mem::size_of::<u64>()) / page_size)
+            .collect()
+    })
+}
+
+/// What a worker thread hands back to `CpuTester::run`.
+struct WorkerOutcome {
+    /// One result per pattern pass the worker started.
+    results: Vec<TestResult>,
+    /// Locking and residency of the worker's blocks.
+    residency: Residency,
+    /// Pages backing the worker's blocks.
+    backing: Backing,
+    /// Bytes of the worker's blocks backed by huge pages, if known.
+    huge_bytes: Option<u64>,
+    /// Bytes of the worker's blocks that could not be bound to its node.
+    unbound_bytes: u64,
+    /// Why the worker's blocks could not be allocated, if they could not.
+    allocation_error: Option<String>,
+    /// Errors found by each test other than the patterns.
+    test_errors: BTreeMap<String, u64>,
+}
+
 /// Worker thread that tests memory blocks.
 fn worker_thread(
-    thread_id: usize,
+    worker: Worker,
     blocks_per_thread: usize,
+    config: &CpuTesterConfig,
     stats: Arc<TestStats>,
     should_stop: Arc<AtomicBool>,
     error_tx: channel::Sender<CpuMemoryError>,
-    progress: ProgressBar,
-    continuous: bool,
-) {
+    observer: Option<&dyn TestObserver>,
+) -> WorkerOutcome {
+    let thread_id = worker.id;
+    // Pin before allocating so first-touch places the blocks near this core
+    let pinned = worker
+        .core
+        .is_some_and(|core| affinity::pin_current_thread(core).is_ok());
+    if !pinned && !worker.node_cpus.is_empty() {
+        let _ = affinity::restrict_current_thread(&worker.node_cpus);
+    }
+
+    // One buffer holds all of the worker's blocks, so huge pages are not
+    // rounded up per block
     let block_size_u64 = BLOCK_SIZE / std::mem::size_of::<u64>();
-    let mut blocks: Vec<Vec<u64>> = (0..blocks_per_thread)
-        .map(|_| vec![0u64; block_size_u64])
-        .collect();
+    let mut memory =
+        match TestBuffer::allocate(blocks_per_thread * block_size_u64, config.huge_pages) {
+            Ok(memory) => memory,
+            Err(e) => {
+                should_stop.store(true, Ordering::Relaxed);
+                return WorkerOutcome {
+                    results: Vec::new(),
+                    residency: Residency {
+                        resident_bytes: Some(0),
+                        ..Default::default()
+                    },
+                    backing: Backing::Normal(pagemap::page_size()),
+                    huge_bytes: Some(0),
+                    unbound_bytes: 0,
+                    allocation_error: Some(e),
+                    test_errors: BTreeMap::new(),
+                };
+            }
+        };
+    let mut unbound_bytes = 0;
+    if let Some(node) = worker.node {
+        if numa::bind_memory(&mut memory, node).is_err() {
+            unbound_bytes = std::mem::size_of_val(&*memory) as u64;
+        }
+    }
+
+    // Lock after binding so the pages are faulted in on the right node
+    let mut residency = Residency {
+        total_bytes: std::mem::size_of_val(&*memory) as u64,
+        ..Default::default()
+    };
+    if config.lock_memory || config.require_locked {
+        residency.locked_bytes = memory
+            .chunks(block_size_u64)
+            .filter(|block| memlock::lock(block).is_ok())
+            .map(|block| std::mem::size_of_val(block) as u64)
+            .sum();
+        if config.require_locked && !residency.fully_locked() {
+            should_stop.store(true, Ordering::Relaxed);
+        }
+    }
 
-    progress.set_message(format!("Thread {} initializing", thread_id));
+    let emit = |event| {
+        if let Some(observer) = observer {
+            observer.on_event(&event);
+        }
+    };
+    let hammer = config.rowhammer.filter(|_| rowhammer::supported());
+    let tests_per_block = config.patterns.len()
+        + usize::from(config.address_lines)
+        + usize::from(config.block_move)
+        + config.march_tests.len()
+        + config.retention.map_or(0, |_| retention::PATTERNS.len())
+        + usize::from(hammer.is_some());
+    let pass_bytes = (blocks_per_thread * tests_per_block * BLOCK_SIZE) as u64;
+    let progress = |name: &str, pass: u64, blocks_done: usize| {
+        emit(TestEvent::BlockProgress(ProgressUpdate {
+            worker: thread_id,
+            pattern_name: name.to_string(),
+            bytes_processed: (blocks_done * BLOCK_SIZE) as u64,
+            total_bytes: pass_bytes,
+            current_pass: pass,
+        }));
+    };
+
+    let mut pagemap = PageMap::open_self().ok();
+    let base = memory.as_ptr() as usize;
+
+    // Resolves physical addresses, sends errors to the collector and reports
+    // whether this worker should stop.
+    let mut report = |block: &[u64], pass: u64, errors: Vec<CpuMemoryError>| -> bool {
+        for mut error in errors {
+            error.block = (block.as_ptr() as usize - base) / BLOCK_SIZE;
+            error.pass = pass;
+            error.core = if pinned {
+                worker.core
+            } else {
+                affinity::current_cpu()
+            };
+            error.physical_address =
+                pagemap::translate(pagemap.as_mut(), block.as_ptr() as usize + error.offset);
+            emit(TestEvent::ErrorFound(MemoryErrorInfo::from(&error)));
+            if error_tx.send(error).is_err() {
+                return true;
+            }
+        }
+        let total = stats.get_errors();
+        if total > 0 && config.stops_after(total) {
+            should_stop.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
+    };
 
-    let mut iteration = 0u64;
-    loop {
+    let mut results = Vec::new();
+    // Errors found by the tests other than the patterns, which have no
+    // pass results to count them in
+    let mut test_errors: BTreeMap<String, u64> = BTreeMap::new();
+    let mut count = |name: &str, found: u64| {
+        if found > 0 {
+            *test_errors.entry(name.to_string()).or_default() += found;
+        }
+    };
+    let mut iteration = config.start_pass;
+    'passes: loop {
         if should_stop.load(Ordering::Relaxed) {
             break;
         }
 
-        for pattern in TestPattern::all_patterns() {
+        let mut blocks_done = 0;
+        for &pattern in &config.patterns {
             if should_stop.load(Ordering::Relaxed) {
                 break;
             }
 
-            progress.set_message(format!(
-                "Thread {} - {} (iter {})",
-                thread_id,
-                pattern.name(),
-                iteration
-            ));
+            emit(TestEvent::PatternStarted {
+                worker: thread_id,
+                pattern,
+                pass: iteration,
+            });
 
-            for (block_idx, block) in blocks.iter_mut().enumerate() {
-                let seed = thread_id as u64 * 1000000 + block_idx as u64 + iteration;
+            let pattern_start = Instant::now();
+            let mut result = TestResult {
+                bytes_tested: 0,
+                errors_found: 0,
+                pattern,
+                pass: iteration,
+                duration_ms: 0,
+            };
+            let mut stop = false;
+            for (block_idx, block) in memory.chunks_mut(block_size_u64).enumerate() {
+                let seed = derive_seed(
+                    config.seed,
+                    &[thread_id as u64, block_idx as u64, iteration],
+                );
+                let max_records = config
+                    .error_cap()
+                    .saturating_sub(stats.get_errors() as usize);
 
-                if let Some(error) = test_memory_block(block, pattern, seed, thread_id, &stats) {
-                    if error_tx.send(error).is_err() {
-                        break;
+                let (found, mut errors) = match pattern {
+                    TestPattern::ModuloN => test_modulo_block(
+                        block,
+                        config.modulo_n,
+                        seed,
+                        thread_id,
+                        &stats,
+                        max_records,
+                    ),
+                    _ => test_memory_block(
+                        block,
+                        pattern,
+                        seed,
+                        thread_id,
+                        &stats,
+                        max_records,
+                        &BlockWriter {
+                            cpus: &worker.writer_cpus,
+                            mode: config.write_mode,
+                        },
+                    ),
+                };
+                // A fill and verify cannot replay moving inversions or
+                // modulo-N
+                let replayable =
+                    pattern.inversion_width().is_none() && pattern != TestPattern::ModuloN;
+                if let (false, Some(other)) = (
+                    errors.is_empty(),
+                    worker.cross_core.filter(|_| pinned && replayable),
+                ) {
+                    if let Some(pages) =
+                        cross_check_pages(block, pattern, seed, other, config.write_mode)
+                    {
+                        let page_size = pagemap::page_size();
+                        for error in &mut errors {
+                            let page = (block.as_ptr() as usize + error.offset) / page_size;
+                            error.cross_check = Some(CrossCheck {
+                                core: other,
+                                reproduced: pages.contains(&page),
+                            });
+                        }
                     }
-                    should_stop.store(true, Ordering::Relaxed);
-                    return;
+                }
+                result.bytes_tested += std::mem::size_of_val(block) as u64;
+                result.errors_found += found;
+                if report(block, iteration, errors) {
+                    stop = true;
+                    break;
                 }
 
-                progress.inc(1);
+                blocks_done += 1;
+                progress(pattern.name(), iteration, blocks_done);
             }
-        }
+            result.duration_ms = pattern_start.elapsed().as_millis() as u64;
+            emit(TestEvent::PatternFinished {
+                worker: thread_id,
+                result: result.clone(),
+            });
+            results.push(result);
+            if stop {
+                break 'passes;
+            }
+        }
+
+        if config.address_lines && !should_stop.load(Ordering::Relaxed) {
+            emit(TestEvent::AddressLinesStarted {
+                worker: thread_id,
+                pass: iteration,
+            });
+
+            for block in memory.chunks_mut(block_size_u64) {
+                let errors = test_address_block(block, thread_id, &stats);
+                count("address-lines", errors.is_some().into());
+                if report(block, iteration, errors.into_iter().collect()) {
+                    break 'passes;
+                }
+
+                blocks_done += 1;
+                progress("Address Lines", iteration, blocks_done);
+            }
+        }
+
+        if config.block_move && !should_stop.load(Ordering::Relaxed) {
+            emit(TestEvent::BlockMoveStarted {
+                worker: thread_id,
+                pass: iteration,
+            });
+
+            for block in memory.chunks_mut(block_size_u64) {
+                let errors = test_block_move_block(block, thread_id, &stats);
+                count("block-move", errors.is_some().into());
+                if report(block, iteration, errors.into_iter().collect()) {
+                    break 'passes;
+                }
+
+                blocks_done += 1;
+                progress("Block Move", iteration, blocks_done);
+            }
+        }
+
+        for march in &config.march_tests {
+            if should_stop.load(Ordering::Relaxed) {
+                break;
+            }
+
+            emit(TestEvent::MarchStarted {
+                worker: thread_id,
+                name: march.name().to_string(),
+                pass: iteration,
+            });
+
+            for block in memory.chunks_mut(block_size_u64) {
+                let errors = test_march_block(block, march, thread_id, &stats);
+                count(march.name(), errors.is_some().into());
+                if report(block, iteration, errors.into_iter().collect()) {
+                    break 'passes;
+                }
+
+                blocks_done += 1;
+                progress(march.name(), iteration, blocks_done);
+            }
+        }
+
+        if let Some(retention) = config.retention {
+            let idle = retention.idle_for(iteration);
+            let writer = BlockWriter {
+                cpus: &worker.writer_cpus,
+                mode: config.write_mode,
+            };
+            for pattern in retention::PATTERNS {
+                if should_stop.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                emit(TestEvent::RetentionStarted {
+                    worker: thread_id,
+                    pattern,
+                    idle,
+                    pass: iteration,
+                });
+
+                // Flush after filling so the pattern waits in DRAM, not in
+                // the caches
+                for block in memory.chunks_mut(block_size_u64) {
+                    writer.fill(block, pattern, 0);
+                    cache::flush(block);
+                }
+                let Some(idled) = retention::idle(idle, &should_stop) else {
+                    break 'passes;
+                };
+
+                for block in memory.chunks(block_size_u64) {
+                    let max_records = config
+                        .error_cap()
+                        .saturating_sub(stats.get_errors() as usize);
+                    let (found, errors) =
+                        test_retention_block(block, pattern, idled, thread_id, &stats, max_records);
+                    count("retention", found);
+                    if report(block, iteration, errors) {
+                        break 'passes;
+                    }
+
+                    blocks_done += 1;
+                    progress("Retention", iteration, blocks_done);
+                }
+            }
+        }
+
+        if let Some(rowhammer) = hammer.filter(|_| !should_stop.load(Ordering::Relaxed)) {
+            let pattern = rowhammer::pattern_for(iteration);
+            let mut translator = PageMap::open_self().ok();
+            let pairs = rowhammer::pick_pairs(
+                &memory,
+                rowhammer.pairs,
+                derive_seed(
+                    config.seed,
+                    &[thread_id as u64, ROWHAMMER_STREAM, iteration],
+                ),
+                |vaddr| pagemap::translate(translator.as_mut(), vaddr),
+            );
+            emit(TestEvent::RowhammerStarted {
+                worker: thread_id,
+                pairs: pairs.len(),
+                double_sided: pairs.iter().filter(|pair| pair.double_sided).count(),
+                pass: iteration,
+            });
+
+            let writer = BlockWriter {
+                cpus: &worker.writer_cpus,
+                mode: config.write_mode,
+            };
+            for block in memory.chunks_mut(block_size_u64) {
+                writer.fill(block, pattern, 0);
+                cache::flush(block);
+            }
+
+            // Every block is checked after each pair, so flips are pinned
+            // on the pair that caused them
+            for (pair_idx, pair) in pairs.iter().enumerate() {
+                if should_stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let activations =
+                    rowhammer::hammer(&memory, pair, rowhammer.per_pair(), &should_stop);
+                let aggressors = pair.addresses(&memory);
+
+                for block in memory.chunks_mut(block_size_u64) {
+                    let max_records = config
+                        .error_cap()
+                        .saturating_sub(stats.get_errors() as usize);
+                    let (found, errors) = test_rowhammer_block(
+                        block,
+                        pattern,
+                        |victim| {
+                            rowhammer::describe_flip(
+                                aggressors,
+                                pair.double_sided,
+                                activations,
+                                victim,
+                                |vaddr| pagemap::translate(translator.as_mut(), vaddr),
+                            )
+                        },
+                        thread_id,
+                        &stats,
+                        max_records,
+                    );
+                    count("rowhammer", found);
+                    if report(block, iteration, errors) {
+                        break 'passes;
+                    }
+                    // Restore the flipped words so the next pair starts clean
+                    if found > 0 {
+                        writer.fill(block, pattern, 0);
+                        cache::flush(block);
+                    }
+                }
+
+                progress(
+                    "Rowhammer",
+                    iteration,
+                    blocks_done + blocks_per_thread * (pair_idx + 1) / pairs.len(),
+                );
+            }
+        }
 
         iteration += 1;
+        // Keep the lowest residency seen at the end of any pass
+        residency.resident_bytes =
+            match (residency.resident_bytes, memlock::resident_bytes(&memory)) {
+                (Some(lowest), Some(now)) => Some(lowest.min(now)),
+                (_, now) => now,
+            };
 
-        if !continuous {
+        if !config.continuous {
             break;
         }
     }
 
-    progress.finish_with_message(format!("Thread {} complete", thread_id));
+    if iteration == config.start_pass {
+        residency.resident_bytes = memlock::resident_bytes(&memory);
+    }
+    WorkerOutcome {
+        results,
+        residency,
+        backing: memory.backing(),
+        huge_bytes: memory.huge_bytes(),
+        unbound_bytes,
+        allocation_error: None,
+        test_errors,
+    }
 }
 
 impl MemoryTester for CpuTester {
@@ -338,11 +1560,7 @@ impl MemoryTester for CpuTester {
     }
 
     fn device_info(&self) -> String {
-        format!(
-            "{} threads, {} MB block size",
-            self.num_threads,
-            BLOCK_SIZE / (1024 * 1024)
-        )
+        CpuTester::device_info(self)
     }
 
     fn max_testable_memory(&self) -> u64 {
@@ -356,32 +1574,24 @@ impl MemoryTester for CpuTester {
         stats: Arc<TestStats>,
         should_stop: Arc<AtomicBool>,
     ) -> Result<Vec<TestResult>, FerritestError> {
-        let start_time = Instant::now();
-        let mut results = Vec::new();
-
-        // Run each pattern and collect results
-        let errors = self.run(Arc::clone(&stats), Arc::clone(&should_stop));
-
-        // Create a result for each pattern tested
-        // Since run() tests all patterns, we create aggregate results
-        for pattern in &config.patterns {
-            let duration_ms =
-                start_time.elapsed().as_millis() as u64 / config.patterns.len() as u64;
-            let bytes_per_pattern = stats.get_bytes() / config.patterns.len() as u64;
-
-            results.push(TestResult {
-                bytes_tested: bytes_per_pattern,
-                errors_found: if errors.iter().any(|e| e.pattern == *pattern) {
-                    1
-                } else {
-                    0
-                },
-                pattern: *pattern,
-                duration_ms,
-            });
+        self.config.patterns = config.patterns.clone();
+        self.config.seed = config.seed;
+
+        let run = self.run(stats, should_stop);
+        let run = self.last_run.insert(run);
+        run.ensure_allocated()?;
+        if self.config.require_locked {
+            run.residency.ensure_locked()?;
         }
+        Ok(run.results.clone())
+    }
+
+    fn recorded_errors(&self) -> Vec<MemoryErrorInfo> {
+        self.errors().iter().map(MemoryErrorInfo::from).collect()
+    }
 
-        Ok(results)
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
     }
 }
 
@@ -405,6 +1615,33 @@ mod tests {
         assert!(!config.continuous);
         assert!(config.timeout.is_none());
         assert!(!config.verbose);
+        assert_eq!(config.patterns, TestPattern::all_patterns());
+        assert_eq!(config.modulo_n, modulo::DEFAULT_N);
+        assert!(config.march_tests.is_empty());
+        assert!(!config.keep_going);
+        assert!(config.max_errors.is_none());
+        assert_eq!(config.error_cap(), DEFAULT_MAX_ERRORS);
+    }
+
+    #[test]
+    fn test_cpu_tester_config_stops_after() {
+        let config = CpuTesterConfig::default();
+        assert!(!config.stops_after(0));
+        assert!(config.stops_after(1));
+
+        let config = CpuTesterConfig {
+            keep_going: true,
+            ..Default::default()
+        };
+        assert!(!config.stops_after(1_000_000));
+
+        let config = CpuTesterConfig {
+            max_errors: Some(10),
+            ..Default::default()
+        };
+        assert!(!config.stops_after(9));
+        assert!(config.stops_after(10));
+        assert_eq!(config.error_cap(), 10);
     }
 
     #[test]
@@ -419,19 +1656,709 @@ mod tests {
         assert!(info.contains("64 MB"));
     }
 
+    #[test]
+    fn test_cpu_tester_pinned_cores() {
+        let tester = CpuTester::new(CpuTesterConfig::default());
+        assert!(tester.cores().is_empty());
+        assert_eq!(tester.worker(0).core, None);
+
+        let config = CpuTesterConfig {
+            pin_threads: true,
+            cpus: vec![2, 5],
+            ..Default::default()
+        };
+        let tester = CpuTester::new(config);
+        assert_eq!(tester.num_threads(), 2);
+        assert_eq!(tester.cores(), &[2, 5]);
+        assert!(tester.device_info().ends_with("pinned to cores 2,5"));
+
+        let worker = tester.worker(1);
+        assert_eq!((worker.core, worker.cross_core), (Some(5), Some(2)));
+        let worker = tester.worker(2);
+        assert_eq!((worker.core, worker.cross_core), (Some(2), Some(5)));
+
+        let config = CpuTesterConfig {
+            pin_threads: true,
+            cpus: vec![3],
+            ..Default::default()
+        };
+        let worker = CpuTester::new(config).worker(0);
+        assert_eq!((worker.core, worker.cross_core), (Some(3), None));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_run_reports_each_core() {
+        let cpus: Vec<usize> = affinity::available_cpus().into_iter().take(2).collect();
+        let config = CpuTesterConfig {
+            memory_mb: 128,
+            patterns: vec![TestPattern::AllOnes],
+            seed: 1,
+            pin_threads: true,
+            cpus: cpus.clone(),
+            ..Default::default()
+        };
+        let tester = CpuTester::new(config);
+
+        let run = tester.run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+
+        let cores: Vec<usize> = run.cores.iter().map(|c| c.core).collect();
+        assert_eq!(cores, cpus);
+        let total: u64 = run.cores.iter().map(|c| c.bytes_tested).sum();
+        assert_eq!(total, 2 * BLOCK_SIZE as u64);
+        assert!(run.cores.iter().all(|c| c.errors_found == 0));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cross_check_pages_clean_block() {
+        let mut block = vec![0u64; 1024];
+        let core = affinity::available_cpus()[0];
+
+        let pages = cross_check_pages(
+            &mut block,
+            TestPattern::Checkerboard,
+            0,
+            core,
+            WriteMode::Streaming,
+        );
+
+        assert_eq!(pages, Some(HashSet::new()));
+        assert_eq!(
+            cross_check_pages(
+                &mut block,
+                TestPattern::AllOnes,
+                0,
+                usize::MAX,
+                WriteMode::Cached
+            ),
+            None
+        );
+    }
+
     #[test]
     fn test_memory_block_no_error() {
         let mut block = vec![0u64; 1024];
         let stats = TestStats::new();
 
-        let result = test_memory_block(&mut block, TestPattern::AllOnes, 0, 0, &stats);
+        let (found, result) = test_memory_block(
+            &mut block,
+            TestPattern::AllOnes,
+            0,
+            0,
+            &stats,
+            10,
+            &BlockWriter::default(),
+        );
 
-        assert!(result.is_none());
+        assert_eq!(found, 0);
+        assert!(result.is_empty());
         assert!(stats.get_bytes() > 0);
         assert_eq!(stats.get_errors(), 0);
         assert_eq!(stats.get_tests(), 1);
     }
 
+    #[test]
+    fn test_memory_block_moving_inversions() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+
+        let (found, result) = test_memory_block(
+            &mut block,
+            TestPattern::MovingInversions32,
+            7,
+            0,
+            &stats,
+            10,
+            &BlockWriter::default(),
+        );
+
+        assert_eq!(found, 0);
+        assert!(result.is_empty());
+        // Five operations per word for each of the 32 rotations
+        assert_eq!(stats.get_bytes(), 1024 * 8 * 5 * 32);
+        assert_eq!(stats.get_tests(), 1);
+        // The seed picked rotation 7 first, so rotation 6 came last
+        assert_eq!(block[0], inversions::value(WordWidth::Bits32, 6, 0));
+    }
+
+    #[test]
+    fn test_modulo_block_no_error() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+
+        let (found, result) = test_modulo_block(&mut block, 8, 3, 0, &stats, 10);
+
+        assert_eq!(found, 0);
+        assert!(result.is_empty());
+        // The whole block is rewritten in each of 8 phases, for both polarities
+        assert_eq!(stats.get_bytes(), 1024 * 8 * 8 * 2);
+        assert_eq!(stats.get_tests(), 1);
+        // The last phase checked offset 7 with ~P
+        assert_eq!(block[7], !modulo::pattern(3));
+    }
+
+    #[test]
+    fn test_memory_block_written_from_other_cpus() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+        let writer = affinity::available_cpus();
+
+        let (found, result) = test_memory_block(
+            &mut block,
+            TestPattern::Checkerboard,
+            0,
+            0,
+            &stats,
+            10,
+            &BlockWriter {
+                cpus: &writer,
+                mode: WriteMode::Streaming,
+            },
+        );
+
+        assert_eq!(found, 0);
+        assert!(result.is_empty());
+        assert!(TestPattern::Checkerboard.verify_block(&block, 0).is_ok());
+    }
+
+    #[test]
+    fn test_cpu_tester_numa_workers() {
+        let config = CpuTesterConfig {
+            numa_nodes: vec![0, 1],
+            cross_node: true,
+            ..Default::default()
+        };
+        let mut tester = CpuTester::new(config);
+        tester.topology = vec![
+            NumaNode {
+                id: 0,
+                cpus: vec![0, 1],
+                memory_mb: 1024,
+            },
+            NumaNode {
+                id: 1,
+                cpus: vec![2, 3],
+                memory_mb: 1024,
+            },
+        ];
+        assert!(tester
+            .device_info()
+            .ends_with("memory on NUMA node 0-1 verified from the next node"));
+
+        let worker = tester.worker(0);
+        assert_eq!(worker.node, Some(0));
+        assert_eq!(worker.node_cpus, vec![2, 3]);
+        assert_eq!(worker.writer_cpus, vec![0, 1]);
+        let worker = tester.worker(3);
+        assert_eq!(worker.node, Some(1));
+        assert_eq!(worker.node_cpus, vec![0, 1]);
+        assert_eq!(worker.writer_cpus, vec![2, 3]);
+
+        tester.config.cross_node = false;
+        let worker = tester.worker(1);
+        assert_eq!(worker.node_cpus, vec![2, 3]);
+        assert!(worker.writer_cpus.is_empty());
+
+        let worker = CpuTester::new(CpuTesterConfig::default()).worker(0);
+        assert_eq!(worker.node, None);
+        assert!(worker.node_cpus.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_run_reports_each_node() {
+        let Some(node) = numa::nodes().into_iter().find(|n| n.memory_mb > 0) else {
+            return;
+        };
+        let config = CpuTesterConfig {
+            memory_mb: 64,
+            threads: Some(1),
+            patterns: vec![TestPattern::AllZeros],
+            seed: 1,
+            numa_nodes: vec![node.id],
+            ..Default::default()
+        };
+        let tester = CpuTester::new(config);
+
+        let run = tester.run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+
+        assert_eq!(
+            run.nodes,
+            vec![NodeResult {
+                node: node.id,
+                bytes_tested: BLOCK_SIZE as u64,
+                errors_found: 0,
+                unbound_bytes: 0,
+            }]
+        );
+        assert!(run.cores.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_run_reports_allocation_failure() {
+        // More than any address space can map
+        let config = CpuTesterConfig {
+            memory_mb: 1 << 40,
+            threads: Some(1),
+            patterns: vec![TestPattern::AllZeros],
+            ..Default::default()
+        };
+        let mut tester = CpuTester::new(config);
+        let run = tester.run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+
+        assert!(run.results.is_empty());
+        assert!(run.allocation_error.is_some());
+        assert!(matches!(
+            run.ensure_allocated(),
+            Err(FerritestError::Allocation(_))
+        ));
+        let result = tester.run_tests(
+            &TestConfig::default(),
+            Arc::new(TestStats::new()),
+            Arc::new(AtomicBool::new(false)),
+        );
+        assert!(matches!(result, Err(FerritestError::Allocation(_))));
+    }
+
+    #[test]
+    fn test_run_reports_unbound_memory() {
+        // No system has this node, so binding fails and the kernel places the memory
+        let config = CpuTesterConfig {
+            memory_mb: 64,
+            threads: Some(1),
+            patterns: vec![TestPattern::AllZeros],
+            seed: 1,
+            numa_nodes: vec![1 << 20],
+            ..Default::default()
+        };
+        let run = CpuTester::new(config)
+            .run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+
+        assert_eq!(run.nodes.len(), 1);
+        assert_eq!(run.nodes[0].bytes_tested, BLOCK_SIZE as u64);
+        assert_eq!(run.nodes[0].unbound_bytes, run.residency.total_bytes);
+    }
+
+    #[test]
+    fn test_collect_mismatches_records_values() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+        TestPattern::AllZeros.fill_block(&mut block, 0);
+        block[10] = 0x4;
+        block[20] = 0x8;
+        block[30] = 0x10;
+
+        let (found, errors) = collect_mismatches(&block, TestPattern::AllZeros, 0, 3, &stats, 2);
+
+        assert_eq!(found, 3);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(stats.get_errors(), 3);
+        assert_eq!(errors[0].offset, 80);
+        assert_eq!(errors[0].thread_id, 3);
+        assert_eq!(errors[0].expected, 0);
+        assert_eq!(errors[0].actual, 0x4);
+        assert_eq!(errors[1].offset, 160);
+
+        let info = MemoryErrorInfo::from(&errors[1]);
+        assert_eq!(info.test, TestKind::Pattern(TestPattern::AllZeros));
+        assert_eq!(info.offset, 160);
+        assert_eq!(info.source_id, 3);
+        assert_eq!(info.expected, Some(0));
+        assert_eq!(info.actual, Some(0x8));
+    }
+
+    #[test]
+    fn test_collect_mismatches_clean_block() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+        TestPattern::Checkerboard.fill_block(&mut block, 0);
+
+        let (found, errors) =
+            collect_mismatches(&block, TestPattern::Checkerboard, 0, 0, &stats, 10);
+
+        assert_eq!(found, 0);
+        assert!(errors.is_empty());
+        assert_eq!(stats.get_errors(), 0);
+    }
+
+    #[test]
+    fn test_march_block_no_error() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+
+        let result = test_march_block(&mut block, &MarchTest::march_c_minus(), 0, &stats);
+
+        assert!(result.is_none());
+        assert_eq!(stats.get_bytes(), 1024 * 8 * 10);
+        assert_eq!(stats.get_errors(), 0);
+        assert_eq!(stats.get_tests(), 1);
+    }
+
+    #[test]
+    fn test_address_block_no_error() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+
+        let result = test_address_block(&mut block, 0, &stats);
+
+        assert!(result.is_none());
+        // The base and ten power-of-two offsets
+        assert_eq!(stats.get_bytes(), 11 * 8);
+        assert_eq!(stats.get_tests(), 1);
+    }
+
+    #[test]
+    fn test_block_move_block_no_error() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+
+        let result = test_block_move_block(&mut block, 0, &stats);
+
+        assert!(result.is_none());
+        // Every round trip moves the whole block once, for each method
+        let methods = block_move::methods().len() as u64;
+        assert_eq!(stats.get_bytes(), methods * 1024 * 8 * 16);
+        assert_eq!(stats.get_tests(), 1);
+    }
+
+    #[test]
+    fn test_retention_block_records_idle() {
+        let mut block = vec![u64::MAX; 1024];
+        let stats = TestStats::new();
+        let idle = Duration::from_secs(90);
+
+        let (found, errors) =
+            test_retention_block(&block, TestPattern::AllOnes, idle, 0, &stats, 10);
+        assert_eq!(found, 0);
+        assert!(errors.is_empty());
+        assert_eq!(stats.get_tests(), 1);
+
+        // A cell that faded to zero
+        block[5] = !(1 << 7);
+        let (found, errors) =
+            test_retention_block(&block, TestPattern::AllOnes, idle, 0, &stats, 10);
+        assert_eq!(found, 1);
+        assert_eq!(errors[0].offset, 40);
+        assert_eq!(errors[0].test, TestKind::Retention(idle));
+        assert_eq!(MemoryErrorInfo::from(&errors[0]).test.pattern(), None);
+        assert_eq!(stats.get_bytes(), 2 * 1024 * 8);
+    }
+
+    #[test]
+    fn test_rowhammer_block_records_pair() {
+        let mut block = vec![0u64; 1024];
+        let stats = TestStats::new();
+        let base = block.as_ptr() as usize;
+        let hammering = |address: usize| Hammering {
+            double_sided: false,
+            distances: [-0x40000, address as i64 - base as i64],
+            activations: 1000,
+        };
+
+        let (found, errors) =
+            test_rowhammer_block(&block, TestPattern::AllZeros, hammering, 0, &stats, 10);
+        assert_eq!(found, 0);
+        assert!(errors.is_empty());
+        assert_eq!(stats.get_tests(), 1);
+
+        // A bit that flipped to one in the victim row
+        block[9] = 1 << 33;
+        let (found, errors) =
+            test_rowhammer_block(&block, TestPattern::AllZeros, hammering, 0, &stats, 10);
+        assert_eq!(found, 1);
+        assert_eq!(errors[0].offset, 72);
+        let TestKind::Rowhammer(recorded) = errors[0].test else {
+            panic!("expected a rowhammer error");
+        };
+        assert_eq!(recorded.distances, [-0x40000, 72]);
+        assert_eq!(MemoryErrorInfo::from(&errors[0]).test.pattern(), None);
+    }
+
+    #[test]
+    fn test_run_reports_each_pass() {
+        let config = CpuTesterConfig {
+            memory_mb: 128,
+            threads: Some(2),
+            patterns: vec![TestPattern::AllZeros, TestPattern::Checkerboard],
+            seed: 1,
+            ..Default::default()
+        };
+        let tester = CpuTester::new(config);
+        let stats = Arc::new(TestStats::new());
+
+        let run = tester.run(Arc::clone(&stats), Arc::new(AtomicBool::new(false)));
+
+        assert!(run.errors.is_empty());
+        let patterns: Vec<TestPattern> = run.results.iter().map(|r| r.pattern).collect();
+        assert_eq!(
+            patterns,
+            vec![TestPattern::AllZeros, TestPattern::Checkerboard]
+        );
+        for result in &run.results {
+            assert_eq!(result.pass, 0);
+            assert_eq!(result.bytes_tested, 2 * BLOCK_SIZE as u64);
+            assert_eq!(result.errors_found, 0);
+        }
+        assert_eq!(stats.get_bytes(), 4 * BLOCK_SIZE as u64);
+        assert_eq!(run.residency.total_bytes, 2 * BLOCK_SIZE as u64);
+        if memlock::supported() {
+            assert!(run.residency.resident_bytes.is_some());
+        }
+        assert!(!run.pages.fell_back());
+        assert_eq!(run.pages.huge_bytes, Some(0));
+        assert_eq!(run.cache.bypass, cache::CacheBypass::detect());
+    }
+
+    #[test]
+    fn test_run_starts_at_pass() {
+        let config = CpuTesterConfig {
+            memory_mb: 64,
+            threads: Some(1),
+            patterns: vec![TestPattern::RandomPattern],
+            seed: 1,
+            start_pass: 3,
+            ..Default::default()
+        };
+        let run = CpuTester::new(config)
+            .run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+
+        assert!(run.errors.is_empty());
+        assert_eq!(run.results.len(), 1);
+        assert_eq!(run.results[0].pass, 3);
+    }
+
+    #[test]
+    fn test_run_with_huge_pages() {
+        let config = CpuTesterConfig {
+            memory_mb: 64,
+            threads: Some(1),
+            patterns: vec![TestPattern::RandomPattern],
+            seed: 1,
+            huge_pages: HugePages::Huge2M,
+            ..Default::default()
+        };
+        let run = CpuTester::new(config)
+            .run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+
+        // Whatever pages the machine provides, the test runs on them
+        assert!(run.errors.is_empty());
+        assert_eq!(run.results[0].bytes_tested, BLOCK_SIZE as u64);
+        assert_eq!(run.pages.requested, HugePages::Huge2M);
+        assert!(run.pages.backing.kind() != HugePages::Huge1G);
+        assert_eq!(run.pages.total_bytes, BLOCK_SIZE as u64);
+    }
+
+    #[test]
+    fn test_run_with_streaming_writes() {
+        let config = CpuTesterConfig {
+            memory_mb: 64,
+            threads: Some(1),
+            patterns: vec![TestPattern::Checkerboard, TestPattern::Sequential],
+            seed: 1,
+            write_mode: WriteMode::Streaming,
+            ..Default::default()
+        };
+        let run = CpuTester::new(config)
+            .run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+
+        assert!(run.errors.is_empty());
+        assert_eq!(run.results.len(), 2);
+        assert_eq!(run.write_mode, WriteMode::Streaming);
+    }
+
+    #[test]
+    fn test_page_usage() {
+        let pages = PageUsage {
+            requested: HugePages::Huge1G,
+            backing: Backing::HugeTlb(2 << 20),
+            total_bytes: 4 << 20,
+            huge_bytes: Some(4 << 20),
+        };
+        assert!(pages.fell_back());
+        assert_eq!(pages.huge_percent(), Some(100.0));
+
+        let pages = PageUsage {
+            requested: HugePages::Transparent,
+            backing: Backing::Transparent(2 << 20),
+            total_bytes: 4 << 20,
+            huge_bytes: Some(1 << 20),
+        };
+        assert!(!pages.fell_back());
+        assert_eq!(pages.huge_percent(), Some(25.0));
+    }
+
+    #[test]
+    fn test_residency() {
+        let residency = Residency {
+            total_bytes: 4096,
+            locked_bytes: 4096,
+            resident_bytes: Some(1024),
+        };
+        assert!(residency.fully_locked());
+        assert!(residency.ensure_locked().is_ok());
+        assert_eq!(residency.resident_percent(), Some(25.0));
+
+        let residency = Residency {
+            locked_bytes: 2 * 1024 * 1024,
+            total_bytes: 64 * 1024 * 1024,
+            resident_bytes: None,
+        };
+        assert!(!residency.fully_locked());
+        assert_eq!(residency.resident_percent(), None);
+        match residency.ensure_locked() {
+            Err(FerritestError::MemoryLock {
+                locked_mb,
+                total_mb,
+                ..
+            }) => assert_eq!((locked_mb, total_mb), (2, 64)),
+            other => panic!("Expected MemoryLock, got {:?}", other),
+        }
+        assert_eq!(Residency::default().resident_percent(), None);
+    }
+
+    #[test]
+    fn test_run_emits_events() {
+        let config = CpuTesterConfig {
+            memory_mb: 128,
+            threads: Some(1),
+            patterns: vec![TestPattern::AllZeros, TestPattern::AllOnes],
+            seed: 1,
+            ..Default::default()
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tester = CpuTester::new(config).with_observer(Arc::new(tx));
+
+        tester.run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+        drop(tester);
+
+        let events: Vec<TestEvent> = rx.iter().collect();
+        match events.first() {
+            Some(TestEvent::RunStarted(info)) => {
+                assert_eq!(info.threads, 1);
+                assert_eq!(info.blocks_per_thread, 2);
+                assert_eq!(info.patterns.len(), 2);
+            }
+            other => panic!("Expected RunStarted, got {:?}", other),
+        }
+        match events.last() {
+            Some(TestEvent::RunFinished { results, .. }) => assert_eq!(results.len(), 2),
+            other => panic!("Expected RunFinished, got {:?}", other),
+        }
+
+        let progress: Vec<&ProgressUpdate> = events
+            .iter()
+            .filter_map(|e| match e {
+                TestEvent::BlockProgress(update) => Some(update),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(progress.len(), 4);
+        let last = progress.last().unwrap();
+        assert_eq!(last.bytes_processed, last.total_bytes);
+        assert_eq!(last.pattern_name, TestPattern::AllOnes.name());
+
+        let started = events
+            .iter()
+            .filter(|e| matches!(e, TestEvent::PatternStarted { .. }))
+            .count();
+        let finished = events
+            .iter()
+            .filter(|e| matches!(e, TestEvent::PatternFinished { .. }))
+            .count();
+        assert_eq!((started, finished), (2, 2));
+        assert!(!events.iter().any(|e| matches!(e, TestEvent::ErrorFound(_))));
+    }
+
+    #[test]
+    fn test_run_with_address_lines() {
+        let config = CpuTesterConfig {
+            memory_mb: 64,
+            threads: Some(1),
+            patterns: vec![TestPattern::Checkerboard],
+            address_lines: true,
+            seed: 1,
+            ..Default::default()
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let run = CpuTester::new(config)
+            .with_observer(Arc::new(tx))
+            .run(Arc::new(TestStats::new()), Arc::new(AtomicBool::new(false)));
+
+        assert!(run.errors.is_empty());
+        assert!(run.test_errors.is_empty());
+        assert_eq!(run.results.len(), 1);
+        let events: Vec<TestEvent> = rx.iter().collect();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, TestEvent::AddressLinesStarted { worker: 0, pass: 0 })));
+        match events.iter().rev().find_map(|e| match e {
+            TestEvent::BlockProgress(update) => Some(update),
+            _ => None,
+        }) {
+            Some(last) => {
+                assert_eq!(last.pattern_name, "Address Lines");
+                assert_eq!(last.bytes_processed, last.total_bytes);
+            }
+            None => panic!("Expected BlockProgress events"),
+        }
+    }
+
+    #[test]
+    fn test_run_with_retention() {
+        let config = CpuTesterConfig {
+            memory_mb: 64,
+            threads: Some(1),
+            patterns: vec![TestPattern::Checkerboard],
+            retention: Some(Retention::new(Duration::from_millis(50))),
+            seed: 1,
+            ..Default::default()
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let stats = Arc::new(TestStats::new());
+        let run = CpuTester::new(config)
+            .with_observer(Arc::new(tx))
+            .run(Arc::clone(&stats), Arc::new(AtomicBool::new(false)));
+
+        assert!(run.errors.is_empty());
+        // Retention has no pass results of its own
+        assert_eq!(run.results.len(), 1);
+        assert_eq!(stats.get_tests(), 3);
+        let idles: Vec<TestPattern> = rx
+            .iter()
+            .filter_map(|e| match e {
+                TestEvent::RetentionStarted { pattern, idle, .. } => {
+                    assert_eq!(idle, Duration::from_millis(50));
+                    Some(pattern)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(idles, retention::PATTERNS);
+    }
+
+    #[test]
+    fn test_retention_stops_while_idle() {
+        let config = CpuTesterConfig {
+            memory_mb: 64,
+            threads: Some(1),
+            patterns: Vec::new(),
+            retention: Some(Retention::new(Duration::from_secs(3600))),
+            timeout: Some(Duration::from_millis(200)),
+            seed: 1,
+            ..Default::default()
+        };
+        let stats = Arc::new(TestStats::new());
+        let start = Instant::now();
+        let run = CpuTester::new(config).run(Arc::clone(&stats), Arc::new(AtomicBool::new(false)));
+
+        assert!(start.elapsed() < Duration::from_secs(60));
+        assert!(run.errors.is_empty());
+        // Stopped before anything was verified
+        assert_eq!(stats.get_tests(), 0);
+    }
+
     #[test]
     fn test_cpu_tester_memory_tester_trait() {
         let config = CpuTesterConfig {
@@ -3,63 +3,317 @@
 //! This module provides unified error handling for both CPU and GPU memory testing.
 
 use crate::patterns::TestPattern;
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
+/// Result of re-testing a failing page from a different CPU core.
+///
+/// A page that fails from both cores points to the RAM; one that only fails
+/// from the original core points to that core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CrossCheck {
+    /// Core the page was re-tested from.
+    pub core: usize,
+    /// Whether the page failed again from that core.
+    pub reproduced: bool,
+}
+
+impl fmt::Display for CrossCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.reproduced {
+            write!(f, "page also fails from core {} (likely RAM)", self.core)
+        } else {
+            write!(f, "page passes from core {} (likely CPU core)", self.core)
+        }
+    }
+}
+
+/// Address bit implicated by the address-line test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AddressLine {
+    /// Byte offset whose write changed the failing word.
+    pub written_offset: usize,
+    /// Byte-address bit that links the two offsets, or `None` if the write
+    /// did not stick at all.
+    pub bit: Option<u32>,
+}
+
+impl fmt::Display for AddressLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bit {
+            Some(bit) => write!(
+                f,
+                "address bit {} (write to offset 0x{:X})",
+                bit, self.written_offset
+            ),
+            None => write!(
+                f,
+                "write to offset 0x{:X} did not stick",
+                self.written_offset
+            ),
+        }
+    }
+}
+
+/// Step of a moving-inversions pattern that caught an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InversionStep {
+    /// Rotation of the shifting bit: the bit set in the block's first word.
+    pub rotation: u32,
+    /// Whether the failing read was in the downward walk, which expects the
+    /// inverted pattern, rather than the upward walk.
+    pub inverted: bool,
+}
+
+impl fmt::Display for InversionStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.inverted {
+            write!(f, "rotation {}, ⇓(r~P,wP)", self.rotation)
+        } else {
+            write!(f, "rotation {}, ⇑(rP,w~P)", self.rotation)
+        }
+    }
+}
+
+/// Offset phase of the modulo-N pattern that caught an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ModuloPhase {
+    /// Offset of the checked words: those whose index modulo `n` equals it.
+    pub offset: usize,
+    /// Spacing of the checked words.
+    pub n: usize,
+}
+
+impl fmt::Display for ModuloPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "offset phase {} of {}", self.offset, self.n)
+    }
+}
+
+/// Copy the block-move test moved data with when it caught an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CopyMethod {
+    /// `std::ptr::copy_nonoverlapping`, whatever the compiler makes of it.
+    CopyNonoverlapping,
+    /// One `rep movsb` instruction per copy (x86_64).
+    RepMovsb,
+    /// Vector loads and stores at the detected SIMD level.
+    Simd,
+}
+
+impl CopyMethod {
+    /// Returns the method's display name (e.g., "rep movsb").
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CopyNonoverlapping => "copy_nonoverlapping",
+            Self::RepMovsb => "rep movsb",
+            Self::Simd => "SIMD",
+        }
+    }
+}
+
+impl fmt::Display for CopyMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Aggressor pair the rowhammer test had just hammered when it found a flip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Hammering {
+    /// Whether the aggressors sandwiched a victim row rather than being
+    /// picked at random.
+    pub double_sided: bool,
+    /// Byte distance from the flipped word to each aggressor, negative
+    /// below it: physical when addresses could be resolved, virtual
+    /// otherwise.
+    pub distances: [i64; 2],
+    /// Times each aggressor was read.
+    pub activations: u64,
+}
+
+impl fmt::Display for Hammering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [first, second] = self.distances.map(|distance| {
+            let sign = if distance < 0 { '-' } else { '+' };
+            format!("{}0x{:X}", sign, distance.unsigned_abs())
+        });
+        write!(
+            f,
+            "{} hammering at {} and {} ({} activations)",
+            if self.double_sided {
+                "double-sided"
+            } else {
+                "single-sided"
+            },
+            first,
+            second,
+            self.activations
+        )
+    }
+}
+
+/// Test that detected a memory error, with what it knows about the failure.
+///
+/// Only pattern errors name a [`TestPattern`]; the other tests write values
+/// of their own and are identified by their variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestKind {
+    /// A fill-and-verify pattern.
+    Pattern(TestPattern),
+    /// A moving-inversions pattern, with the rotation and walk that failed.
+    MovingInversions(TestPattern, InversionStep),
+    /// The modulo-N pattern, with the offset phase that failed.
+    ModuloN(ModuloPhase),
+    /// A March test, with the failing element.
+    March(String),
+    /// The address-line test, with the address line it implicated.
+    AddressLines(AddressLine),
+    /// The block-move test, with the copy method that moved the data.
+    BlockMove(CopyMethod),
+    /// The retention test, with the time the word sat idle before it was
+    /// verified.
+    Retention(Duration),
+    /// The rowhammer test, with the aggressor pair hammered before the flip.
+    Rowhammer(Hammering),
+}
+
+impl TestKind {
+    /// Returns the pattern that detected the error, or `None` for tests
+    /// that are not patterns.
+    pub fn pattern(&self) -> Option<TestPattern> {
+        match self {
+            Self::Pattern(pattern) | Self::MovingInversions(pattern, _) => Some(*pattern),
+            Self::ModuloN(_) => Some(TestPattern::ModuloN),
+            _ => None,
+        }
+    }
+
+    /// Returns the test's display name (e.g., "Walking Ones", "Retention").
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::March(_) => "March",
+            Self::AddressLines(_) => "Address Lines",
+            Self::BlockMove(_) => "Block Move",
+            Self::Retention(_) => "Retention",
+            Self::Rowhammer(_) => "Rowhammer",
+            _ => self.pattern().map_or("", |pattern| pattern.name()),
+        }
+    }
+
+    /// Returns the test's identifier: the pattern's slug, or the name of the
+    /// option that enables the test (e.g., "address-lines").
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Self::March(_) => "march",
+            Self::AddressLines(_) => "address-lines",
+            Self::BlockMove(_) => "block-move",
+            Self::Retention(_) => "retention",
+            Self::Rowhammer(_) => "rowhammer",
+            _ => self.pattern().map_or("", |pattern| pattern.slug()),
+        }
+    }
+}
+
+impl From<TestPattern> for TestKind {
+    fn from(pattern: TestPattern) -> Self {
+        Self::Pattern(pattern)
+    }
+}
+
 /// Information about a detected memory error.
 #[derive(Debug, Clone)]
-#[allow(dead_code)] // Will be used in Phase 2 when integrating with MemoryTester trait
 pub struct MemoryErrorInfo {
-    /// The test pattern that detected the error.
-    pub pattern: TestPattern,
-    /// Byte offset where the error was detected.
+    /// The test that detected the error.
+    pub test: TestKind,
+    /// Byte offset where the error was detected, within block `block`.
     pub offset: usize,
+    /// Index of the block `offset` falls in, among the blocks tested by
+    /// `source_id` (0 for testers that test a single buffer).
+    pub block: usize,
     /// Thread or GPU that detected the error.
     pub source_id: usize,
     /// Expected value (if available).
     pub expected: Option<u64>,
     /// Actual value read (if available).
     pub actual: Option<u64>,
+    /// Physical address of the failing word (if it could be resolved).
+    pub physical_address: Option<u64>,
+    /// Zero-based pass in which the error was found (if tracked).
+    pub pass: Option<u64>,
+    /// CPU core that read the failing word (if known).
+    pub core: Option<usize>,
+    /// Re-test of the failing page from another core (if one was run).
+    pub cross_check: Option<CrossCheck>,
 }
 
-#[allow(dead_code)] // Will be used in Phase 2
 impl MemoryErrorInfo {
     /// Creates a new MemoryErrorInfo with minimal information.
-    pub fn new(pattern: TestPattern, offset: usize, source_id: usize) -> Self {
+    pub fn new(test: impl Into<TestKind>, offset: usize, source_id: usize) -> Self {
         Self {
-            pattern,
+            test: test.into(),
             offset,
+            block: 0,
             source_id,
             expected: None,
             actual: None,
+            physical_address: None,
+            pass: None,
+            core: None,
+            cross_check: None,
         }
     }
 
     /// Creates a MemoryErrorInfo with expected and actual values.
     pub fn with_values(
-        pattern: TestPattern,
+        test: impl Into<TestKind>,
         offset: usize,
         source_id: usize,
         expected: u64,
         actual: u64,
     ) -> Self {
         Self {
-            pattern,
-            offset,
-            source_id,
             expected: Some(expected),
             actual: Some(actual),
+            ..Self::new(test, offset, source_id)
         }
     }
+
+    /// Attaches the physical address of the failing word.
+    pub fn with_physical_address(mut self, physical_address: u64) -> Self {
+        self.physical_address = Some(physical_address);
+        self
+    }
+
+    /// Attaches the pass in which the error was found.
+    pub fn with_pass(mut self, pass: u64) -> Self {
+        self.pass = Some(pass);
+        self
+    }
+
+    /// Attaches the CPU core that read the failing word.
+    pub fn with_core(mut self, core: usize) -> Self {
+        self.core = Some(core);
+        self
+    }
+
+    /// Attaches the result of re-testing the page from another core.
+    pub fn with_cross_check(mut self, cross_check: CrossCheck) -> Self {
+        self.cross_check = Some(cross_check);
+        self
+    }
 }
 
 /// Unified error type for ferritest operations.
 #[derive(Error, Debug)]
-#[allow(dead_code)] // Will be used in Phase 2 when integrating with MemoryTester trait
 pub enum FerritestError {
     /// Memory error detected during testing.
-    #[error("Memory error: {pattern} at offset 0x{offset:X} (source {source_id})")]
+    #[error("Memory error: {test} at offset 0x{offset:X} (source {source_id})")]
     Memory {
-        pattern: String,
+        test: String,
         offset: usize,
         source_id: usize,
     },
@@ -68,6 +322,18 @@ pub enum FerritestError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    /// Test memory could not be allocated.
+    #[error("Failed to allocate test memory: {0}")]
+    Allocation(String),
+
+    /// Test memory could not be locked into RAM while locking was required.
+    #[error("Memory lock failed: only {locked_mb} of {total_mb} MB could be locked into RAM (RLIMIT_MEMLOCK: {limit}); raise it with `ulimit -l` or run as root")]
+    MemoryLock {
+        locked_mb: u64,
+        total_mb: u64,
+        limit: String,
+    },
+
     /// I/O error.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -79,7 +345,6 @@ pub enum FerritestError {
 
 /// GPU-specific errors.
 #[derive(Error, Debug)]
-#[allow(dead_code)] // Will be used in Phase 3/4 for GPU testing
 pub enum GpuError {
     /// No GPU adapter found.
     #[error("No GPU adapter found")]
@@ -103,6 +368,10 @@ pub enum GpuError {
         available_mb: u64,
     },
 
+    /// The pattern has no GPU shader.
+    #[error("Pattern {0} is not implemented on the GPU")]
+    UnsupportedPattern(String),
+
     /// Shader compilation failed.
     #[error("Shader compilation failed: {0}")]
     ShaderCompilation(String),
@@ -127,7 +396,7 @@ pub enum GpuError {
 impl From<MemoryErrorInfo> for FerritestError {
     fn from(info: MemoryErrorInfo) -> Self {
         FerritestError::Memory {
-            pattern: info.pattern.name().to_string(),
+            test: info.test.name().to_string(),
             offset: info.offset,
             source_id: info.source_id,
         }
@@ -141,17 +410,133 @@ mod tests {
     #[test]
     fn test_memory_error_info_new() {
         let info = MemoryErrorInfo::new(TestPattern::AllZeros, 1024, 0);
-        assert_eq!(info.pattern, TestPattern::AllZeros);
+        assert_eq!(info.test, TestKind::Pattern(TestPattern::AllZeros));
         assert_eq!(info.offset, 1024);
         assert_eq!(info.source_id, 0);
         assert!(info.expected.is_none());
         assert!(info.actual.is_none());
+        assert!(info.physical_address.is_none());
+    }
+
+    #[test]
+    fn test_memory_error_info_with_physical_address() {
+        let info = MemoryErrorInfo::with_values(TestPattern::AllOnes, 8, 0, 0xFF, 0x7F)
+            .with_physical_address(0x1_2345_6000);
+        assert_eq!(info.physical_address, Some(0x1_2345_6000));
+        assert_eq!(info.expected, Some(0xFF));
+        assert!(info.pass.is_none());
+        assert_eq!(info.with_pass(3).pass, Some(3));
+    }
+
+    #[test]
+    fn test_memory_error_info_with_core() {
+        let check = CrossCheck {
+            core: 3,
+            reproduced: false,
+        };
+        let info = MemoryErrorInfo::new(TestPattern::AllZeros, 0, 0)
+            .with_core(1)
+            .with_cross_check(check);
+        assert_eq!(info.core, Some(1));
+        assert_eq!(info.cross_check, Some(check));
+        assert_eq!(
+            check.to_string(),
+            "page passes from core 3 (likely CPU core)"
+        );
+    }
+
+    #[test]
+    fn test_test_kind_identifies_test() {
+        let info = MemoryErrorInfo::new(TestPattern::AllZeros, 0, 0);
+        assert_eq!(info.test, TestKind::Pattern(TestPattern::AllZeros));
+        assert_eq!(info.test.pattern(), Some(TestPattern::AllZeros));
+        assert_eq!(info.test.slug(), "all-zeros");
+
+        let info = MemoryErrorInfo::new(TestKind::March("M2 (r0,w1) at word 3".into()), 0, 0);
+        assert_eq!(info.test.pattern(), None);
+        assert_eq!(info.test.name(), "March");
+        assert_eq!(info.test.slug(), "march");
+
+        let retention = TestKind::Retention(Duration::from_secs(90));
+        assert_eq!(retention.pattern(), None);
+        assert_eq!(retention.slug(), "retention");
+    }
+
+    #[test]
+    fn test_address_line_display() {
+        let line = AddressLine {
+            written_offset: 0x1000,
+            bit: Some(12),
+        };
+        let info = MemoryErrorInfo::new(TestKind::AddressLines(line), 0, 0);
+        assert_eq!(info.test.slug(), "address-lines");
+        assert_eq!(line.to_string(), "address bit 12 (write to offset 0x1000)");
+        assert_eq!(
+            AddressLine { bit: None, ..line }.to_string(),
+            "write to offset 0x1000 did not stick"
+        );
+    }
+
+    #[test]
+    fn test_inversion_step_display() {
+        let step = InversionStep {
+            rotation: 5,
+            inverted: false,
+        };
+        let test = TestKind::MovingInversions(TestPattern::MovingInversions64, step);
+        assert_eq!(test.pattern(), Some(TestPattern::MovingInversions64));
+        assert_eq!(step.to_string(), "rotation 5, ⇑(rP,w~P)");
+        assert_eq!(
+            InversionStep {
+                inverted: true,
+                ..step
+            }
+            .to_string(),
+            "rotation 5, ⇓(r~P,wP)"
+        );
+    }
+
+    #[test]
+    fn test_modulo_phase_display() {
+        let phase = ModuloPhase { offset: 3, n: 20 };
+        let test = TestKind::ModuloN(phase);
+        assert_eq!(test.pattern(), Some(TestPattern::ModuloN));
+        assert_eq!(test.name(), "Modulo-N");
+        assert_eq!(phase.to_string(), "offset phase 3 of 20");
+    }
+
+    #[test]
+    fn test_copy_method_display() {
+        let test = TestKind::BlockMove(CopyMethod::RepMovsb);
+        assert_eq!(test.pattern(), None);
+        assert_eq!(test.slug(), "block-move");
+        assert_eq!(CopyMethod::RepMovsb.to_string(), "rep movsb");
+        assert_eq!(
+            serde_json::to_value(CopyMethod::CopyNonoverlapping).unwrap(),
+            "copy-nonoverlapping"
+        );
+    }
+
+    #[test]
+    fn test_hammering_display() {
+        let hammering = Hammering {
+            double_sided: true,
+            distances: [-0x40000, 0x40000],
+            activations: 1_200_000,
+        };
+        let test = TestKind::Rowhammer(hammering);
+        assert_eq!(test.pattern(), None);
+        assert_eq!(test.name(), "Rowhammer");
+        assert_eq!(
+            hammering.to_string(),
+            "double-sided hammering at -0x40000 and +0x40000 (1200000 activations)"
+        );
     }
 
     #[test]
     fn test_memory_error_info_with_values() {
         let info = MemoryErrorInfo::with_values(TestPattern::AllOnes, 2048, 1, 0xFF, 0x00);
-        assert_eq!(info.pattern, TestPattern::AllOnes);
+        assert_eq!(info.test, TestKind::Pattern(TestPattern::AllOnes));
         assert_eq!(info.offset, 2048);
         assert_eq!(info.source_id, 1);
         assert_eq!(info.expected, Some(0xFF));
@@ -161,7 +546,7 @@ mod tests {
     #[test]
     fn test_ferritest_error_display() {
         let err = FerritestError::Memory {
-            pattern: "All Zeros".to_string(),
+            test: "All Zeros".to_string(),
             offset: 0x1000,
             source_id: 0,
         };
@@ -249,11 +634,11 @@ mod tests {
 
         match err {
             FerritestError::Memory {
-                pattern,
+                test,
                 offset,
                 source_id,
             } => {
-                assert_eq!(pattern, "Checkerboard");
+                assert_eq!(test, "Checkerboard");
                 assert_eq!(offset, 512);
                 assert_eq!(source_id, 2);
             }
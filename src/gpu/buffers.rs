@@ -43,7 +43,6 @@ pub struct ErrorInfo {
 /// - Params buffer: Shader uniform parameters
 /// - Error buffer: GPU-side error tracking
 /// - Error staging buffer: CPU-readable copy of errors
-#[allow(dead_code)] // Used in Issue #14
 pub struct BufferManager {
     /// The main test buffer (GPU memory to test).
     test_buffer: Buffer,
@@ -59,7 +58,6 @@ pub struct BufferManager {
     element_count: u32,
 }
 
-#[allow(dead_code)] // Methods used in Issue #14
 impl BufferManager {
     /// Creates a new BufferManager with the specified memory size.
     ///
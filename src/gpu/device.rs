@@ -3,8 +3,6 @@
 //! This module provides functions to enumerate available GPU adapters
 //! and select one for memory testing.
 
-#![allow(dead_code)] // Foundation module - used in Issues #10, #15
-
 use crate::error::GpuError;
 use wgpu::{Adapter, Backend, Backends, DeviceType, Instance, InstanceDescriptor};
 
@@ -136,6 +134,39 @@ fn auto_select_gpu(adapters: &[Adapter]) -> Option<&Adapter> {
     adapters.first()
 }
 
+/// Returns the index of the GPU that auto-selection picks from an enumerated list.
+///
+/// Uses the same preference order as [`select_gpu`] with no index
+/// (discrete > integrated > virtual), falling back to the first GPU.
+pub fn default_gpu_index(gpus: &[GpuInfo]) -> usize {
+    // Prefer discrete GPU
+    if let Some(gpu) = gpus
+        .iter()
+        .find(|g| g.device_type == DeviceType::DiscreteGpu)
+    {
+        return gpu.index;
+    }
+
+    // Then integrated GPU
+    if let Some(gpu) = gpus
+        .iter()
+        .find(|g| g.device_type == DeviceType::IntegratedGpu)
+    {
+        return gpu.index;
+    }
+
+    // Then virtual GPU
+    if let Some(gpu) = gpus
+        .iter()
+        .find(|g| g.device_type == DeviceType::VirtualGpu)
+    {
+        return gpu.index;
+    }
+
+    // Fall back to first
+    0
+}
+
 /// Convert vendor ID to human-readable name.
 fn vendor_name(vendor_id: u32) -> String {
     match vendor_id {
@@ -195,6 +226,27 @@ mod tests {
         assert!(debug.contains("Metal"));
     }
 
+    #[test]
+    fn test_default_gpu_index() {
+        let gpu = |index, device_type| GpuInfo {
+            index,
+            name: format!("GPU {}", index),
+            vendor: "Test".to_string(),
+            backend: Backend::Vulkan,
+            device_type,
+            driver: "1.0".to_string(),
+        };
+
+        let gpus = vec![
+            gpu(0, DeviceType::Cpu),
+            gpu(1, DeviceType::IntegratedGpu),
+            gpu(2, DeviceType::DiscreteGpu),
+        ];
+        assert_eq!(default_gpu_index(&gpus), 2);
+        assert_eq!(default_gpu_index(&gpus[..2]), 1);
+        assert_eq!(default_gpu_index(&gpus[..1]), 0);
+    }
+
     #[test]
     fn test_select_invalid_index() {
         let result = select_gpu(Some(999));
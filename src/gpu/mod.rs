@@ -13,8 +13,11 @@
 //!
 //! # Usage
 //!
-//! ```rust,ignore
-//! use ferritest::gpu::{enumerate_gpus, select_gpu, GpuTester};
+//! ```rust,no_run
+//! use ferritest::gpu::{default_gpu_index, enumerate_gpus, select_gpu, GpuTester};
+//! use ferritest::{MemoryTester, TestConfig, TestStats};
+//! use std::sync::atomic::AtomicBool;
+//! use std::sync::Arc;
 //!
 //! // List available GPUs
 //! let gpus = enumerate_gpus();
@@ -23,12 +26,16 @@
 //! }
 //!
 //! // Select and create tester
-//! let adapter = select_gpu(None)?; // Auto-select best GPU
-//! let gpu_info = gpus[0].clone();
-//! let mut tester = GpuTester::new(adapter, gpu_info, 1024, 30, false)?;
+//! let index = default_gpu_index(&gpus);
+//! let adapter = select_gpu(Some(index))?;
+//! let mut tester = GpuTester::new(adapter, gpus[index].clone(), 1024, 30, false)?;
 //!
 //! // Run tests
+//! let config = TestConfig::default();
+//! let stats = Arc::new(TestStats::new());
+//! let should_stop = Arc::new(AtomicBool::new(false));
 //! let results = tester.run_tests(&config, stats, should_stop)?;
+//! # Ok::<(), ferritest::FerritestError>(())
 //! ```
 //!
 //! # Platform Support
@@ -44,11 +51,7 @@ pub mod device;
 pub mod shaders;
 pub mod tester;
 
-#[allow(unused_imports)] // Re-exports for Issue #15
 pub use buffers::{BufferManager, ErrorInfo, ShaderParams};
-#[allow(unused_imports)] // Re-exports for Issue #15
-pub use device::{enumerate_gpus, select_gpu, GpuInfo};
-#[allow(unused_imports)] // Re-exports for Issue #15
+pub use device::{default_gpu_index, enumerate_gpus, select_gpu, GpuInfo};
 pub use shaders::{ShaderManager, WORKGROUP_SIZE};
-#[allow(unused_imports)] // Re-exports for Issue #15
 pub use tester::GpuTester;
@@ -3,8 +3,6 @@
 //! This module provides shader loading and validation for the compute
 //! shaders used in GPU memory testing.
 
-#![allow(dead_code)] // Used in Issue #14
-
 use crate::error::GpuError;
 use wgpu::{BindGroupLayout, ComputePipeline, Device};
 
@@ -3,11 +3,11 @@
 //! This module provides the core `GpuTester` struct that orchestrates
 //! GPU VRAM memory testing using compute shaders.
 
-use crate::error::{FerritestError, GpuError};
+use crate::error::{FerritestError, GpuError, MemoryErrorInfo};
 use crate::gpu::buffers::{BufferManager, ErrorInfo, ShaderParams};
 use crate::gpu::device::GpuInfo;
 use crate::gpu::shaders::{ShaderManager, WORKGROUP_SIZE};
-use crate::patterns::TestPattern;
+use crate::patterns::{derive_seed, TestPattern};
 use crate::stats::TestStats;
 use crate::traits::{MemoryTester, TestConfig, TestResult};
 use pollster::block_on;
@@ -26,7 +26,6 @@ pub struct GpuTester {
     /// The command queue for submitting work.
     queue: Queue,
     /// Information about the GPU being tested.
-    #[allow(dead_code)] // Used in MemoryTester::device_info()
     gpu_info: GpuInfo,
     /// Shader manager with compute pipelines.
     shaders: ShaderManager,
@@ -34,9 +33,10 @@ pub struct GpuTester {
     buffers: BufferManager,
     /// Timeout for GPU operations.
     timeout: Duration,
-    /// Enable verbose output (used via config.verbose in run_tests).
-    #[allow(dead_code)]
+    /// Enable verbose output (in addition to `TestConfig::verbose`).
     verbose: bool,
+    /// First error of each failing pass from the last `run_tests` call.
+    errors: Vec<MemoryErrorInfo>,
 }
 
 impl GpuTester {
@@ -85,21 +85,28 @@ impl GpuTester {
             buffers,
             timeout: Duration::from_secs(timeout_secs),
             verbose,
+            errors: Vec::new(),
         })
     }
 
     /// Returns information about the GPU being tested.
-    #[allow(dead_code)] // Used in tests
     pub fn gpu_info(&self) -> &GpuInfo {
         &self.gpu_info
     }
 
     /// Returns the amount of memory being tested in bytes.
-    #[allow(dead_code)] // Used in tests
     pub fn buffer_size(&self) -> u64 {
         self.buffers.buffer_size()
     }
 
+    /// Returns the errors found by the last `run_tests` call.
+    ///
+    /// The verify shader only reports the first mismatch of each pass, so
+    /// there is at most one entry per failing pass.
+    pub fn errors(&self) -> &[MemoryErrorInfo] {
+        &self.errors
+    }
+
     /// Runs a single pattern test (write + verify).
     ///
     /// # Arguments
@@ -109,11 +116,16 @@ impl GpuTester {
     ///
     /// # Returns
     ///
-    /// Returns `ErrorInfo` containing error count and first error details.
+    /// Returns `ErrorInfo` containing error count and first error details, or
+    /// `GpuError::UnsupportedPattern` for CPU-only patterns.
     pub fn run_pattern(&self, pattern: TestPattern, seed: u32) -> Result<ErrorInfo, GpuError> {
+        let pattern_id = pattern
+            .pattern_id()
+            .ok_or_else(|| GpuError::UnsupportedPattern(pattern.name().to_string()))?;
+
         // Update params
         let params = ShaderParams {
-            pattern_id: pattern.pattern_id(),
+            pattern_id,
             seed,
             total_elements: self.buffers.element_count(),
             _padding: 0,
@@ -262,6 +274,7 @@ impl MemoryTester for GpuTester {
         let mut results = Vec::new();
         let mut pass = 0u64;
         let start_time = Instant::now();
+        self.errors.clear();
 
         loop {
             for pattern in &config.patterns {
@@ -269,6 +282,11 @@ impl MemoryTester for GpuTester {
                     return Ok(results);
                 }
 
+                // CPU-only patterns have no shader
+                let Some(pattern_id) = pattern.pattern_id() else {
+                    continue;
+                };
+
                 // Check timeout
                 if let Some(timeout) = config.timeout {
                     if start_time.elapsed() >= timeout {
@@ -277,7 +295,7 @@ impl MemoryTester for GpuTester {
                 }
 
                 let pattern_start = Instant::now();
-                let seed = (pass * 1000 + pattern.pattern_id() as u64) as u32;
+                let seed = derive_seed(config.seed, &[pass, pattern_id as u64]) as u32;
 
                 let errors = self.run_pattern(*pattern, seed)?;
 
@@ -289,16 +307,27 @@ impl MemoryTester for GpuTester {
                 stats.add_test();
                 if errors.error_count > 0 {
                     stats.add_error();
+                    self.errors.push(
+                        MemoryErrorInfo::with_values(
+                            *pattern,
+                            errors.first_error_index as usize * 4,
+                            self.gpu_info.index,
+                            errors.first_error_expected as u64,
+                            errors.first_error_actual as u64,
+                        )
+                        .with_pass(pass),
+                    );
                 }
 
                 results.push(TestResult {
                     bytes_tested: bytes,
                     errors_found: errors.error_count as u64,
                     pattern: *pattern,
+                    pass,
                     duration_ms,
                 });
 
-                if config.verbose && errors.error_count > 0 {
+                if (self.verbose || config.verbose) && errors.error_count > 0 {
                     eprintln!(
                         "GPU Error: {} at offset {:#x} - expected {:#x}, got {:#x}",
                         pattern.name(),
@@ -319,6 +348,10 @@ impl MemoryTester for GpuTester {
 
         Ok(results)
     }
+
+    fn recorded_errors(&self) -> Vec<MemoryErrorInfo> {
+        self.errors.clone()
+    }
 }
 
 #[cfg(test)]
@@ -410,6 +443,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cpu_only_pattern_rejected() {
+        let Some(tester) = setup_tester(16) else {
+            println!("No GPU available, skipping CPU-only pattern test");
+            return;
+        };
+
+        assert!(matches!(
+            tester.run_pattern(TestPattern::MovingInversions64, 0),
+            Err(GpuError::UnsupportedPattern(_))
+        ));
+    }
+
     #[test]
     fn test_different_seeds() {
         let Some(tester) = setup_tester(16) else {
@@ -476,6 +522,7 @@ mod tests {
             timeout: None,
             threads: None,
             verbose: false,
+            seed: 0,
         };
         let stats = Arc::new(TestStats::new());
         let should_stop = Arc::new(AtomicBool::new(false));
@@ -0,0 +1,255 @@
+//! Moving-inversions test with shifting bit patterns.
+//!
+//! The fill-then-verify patterns write each word once and read it back, so
+//! they never exercise the transition a cell makes when it is read and
+//! immediately rewritten with the opposite value. Moving inversions
+//! (memtest86's tests 7 and 8) do: the block is filled with a pattern `P`,
+//! walked upward reading `P` and writing `~P`, then walked downward reading
+//! `~P` and writing `P` back.
+//!
+//! `P` is a single set bit that shifts by one position per word, treating
+//! memory as either 32-bit or 64-bit words. The test repeats for every
+//! rotation of that bit, so each bit of each word is the one set at some
+//! point.
+
+use crate::cache;
+use crate::error::InversionStep;
+use std::fmt;
+
+/// Reads and writes applied to each word per rotation: the fill and the
+/// read and write of both walks.
+pub const OPS_PER_WORD: usize = 5;
+
+/// Word size the shifting pattern is laid out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordWidth {
+    /// Two 32-bit words per `u64`, the lower address in the low half.
+    Bits32,
+    /// One 64-bit word per `u64`.
+    Bits64,
+}
+
+impl WordWidth {
+    /// Returns the word size in bits, which is also the number of rotations.
+    pub fn bits(&self) -> u32 {
+        match self {
+            Self::Bits32 => 32,
+            Self::Bits64 => 64,
+        }
+    }
+}
+
+/// Returns the pattern `P` for `rotation` at `index`, as the `u64` stored
+/// there.
+pub fn value(width: WordWidth, rotation: u32, index: usize) -> u64 {
+    match width {
+        WordWidth::Bits64 => 1u64 << ((rotation as usize + index) % 64),
+        WordWidth::Bits32 => {
+            let word = |i: usize| 1u64 << ((rotation as usize + i) % 32);
+            word(2 * index) | word(2 * index + 1) << 32
+        }
+    }
+}
+
+/// A read during a moving-inversions walk that returned the wrong value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InversionFailure {
+    /// Word index within the block.
+    pub index: usize,
+    /// Value the read expected.
+    pub expected: u64,
+    /// Value actually read.
+    pub actual: u64,
+    /// Rotation and walk the read belonged to.
+    pub step: InversionStep,
+}
+
+impl fmt::Display for InversionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at word 0x{:X}: expected 0x{:016X}, got 0x{:016X}",
+            self.step, self.index, self.expected, self.actual
+        )
+    }
+}
+
+/// Word-addressable memory the moving-inversions test can run against.
+trait InversionCells {
+    fn len(&self) -> usize;
+    fn read(&self, index: usize) -> u64;
+    fn write(&mut self, index: usize, value: u64);
+
+    /// Pushes the cells out of the CPU caches between walks.
+    fn flush(&self) {}
+}
+
+impl InversionCells for [u64] {
+    fn len(&self) -> usize {
+        <[u64]>::len(self)
+    }
+
+    fn read(&self, index: usize) -> u64 {
+        cache::read_word(&self[index])
+    }
+
+    fn write(&mut self, index: usize, value: u64) {
+        cache::write_word(&mut self[index], value);
+    }
+
+    fn flush(&self) {
+        cache::flush(self);
+    }
+}
+
+/// Runs moving inversions over a memory block for every rotation of
+/// `width`, starting at `first_rotation` and wrapping around.
+///
+/// The block is flushed from the CPU caches between walks, so each walk
+/// reads what the previous one left in DRAM. The block is left holding `P`
+/// of the last rotation.
+///
+/// # Returns
+/// * `Ok(())` if every read returned its expected value
+/// * `Err(InversionFailure)` describing the first failing read
+pub fn run(
+    block: &mut [u64],
+    width: WordWidth,
+    first_rotation: u32,
+) -> Result<(), InversionFailure> {
+    run_cells(block, width, first_rotation)
+}
+
+fn run_cells<C: InversionCells + ?Sized>(
+    cells: &mut C,
+    width: WordWidth,
+    first_rotation: u32,
+) -> Result<(), InversionFailure> {
+    let len = cells.len();
+    for step in 0..width.bits() {
+        let rotation = (first_rotation + step) % width.bits();
+        for index in 0..len {
+            cells.write(index, value(width, rotation, index));
+        }
+
+        // ⇑(rP, w~P) then ⇓(r~P, wP)
+        for inverted in [false, true] {
+            cells.flush();
+            for i in 0..len {
+                let index = if inverted { len - 1 - i } else { i };
+                let pattern = value(width, rotation, index);
+                let (expected, next) = if inverted {
+                    (!pattern, pattern)
+                } else {
+                    (pattern, !pattern)
+                };
+                let actual = cells.read(index);
+                if actual != expected {
+                    return Err(InversionFailure {
+                        index,
+                        expected,
+                        actual,
+                        step: InversionStep { rotation, inverted },
+                    });
+                }
+                cells.write(index, next);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_BLOCK_SIZE: usize = 1024;
+
+    /// Memory where one bit of one word cannot rise from 0 to 1 (an
+    /// up-transition fault).
+    struct TransitionFaultCells {
+        cells: Vec<u64>,
+        index: usize,
+        bit: u32,
+    }
+
+    impl InversionCells for TransitionFaultCells {
+        fn len(&self) -> usize {
+            self.cells.len()
+        }
+
+        fn read(&self, index: usize) -> u64 {
+            self.cells[index]
+        }
+
+        fn write(&mut self, index: usize, value: u64) {
+            let mut value = value;
+            let mask = 1 << self.bit;
+            if index == self.index && self.cells[index] & mask == 0 {
+                value &= !mask;
+            }
+            self.cells[index] = value;
+        }
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(value(WordWidth::Bits64, 0, 0), 1);
+        assert_eq!(value(WordWidth::Bits64, 0, 65), 2);
+        assert_eq!(value(WordWidth::Bits64, 63, 1), 1);
+        assert_eq!(value(WordWidth::Bits32, 0, 0), 1 | 2 << 32);
+        assert_eq!(value(WordWidth::Bits32, 3, 1), 1 << 5 | 1 << (6 + 32));
+        assert_eq!(
+            value(WordWidth::Bits32, 0, 16),
+            value(WordWidth::Bits32, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_passes_on_good_memory() {
+        for width in [WordWidth::Bits32, WordWidth::Bits64] {
+            let mut block = vec![0u64; TEST_BLOCK_SIZE];
+            assert!(run(&mut block, width, 7).is_ok());
+            // The last rotation is the one before the first
+            assert_eq!(block[10], value(width, 6, 10));
+        }
+    }
+
+    #[test]
+    fn test_catches_transition_fault() {
+        let mut cells = TransitionFaultCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            index: 100,
+            bit: 40,
+        };
+
+        let failure = run_cells(&mut cells, WordWidth::Bits64, 0).unwrap_err();
+        assert_eq!(failure.index, 100);
+        // The upward walk cannot write ~P over the 0 in bit 40
+        assert_eq!(
+            failure.step,
+            InversionStep {
+                rotation: 0,
+                inverted: true
+            }
+        );
+        assert_eq!(failure.expected, !value(WordWidth::Bits64, 0, 100));
+        assert_eq!(failure.expected ^ failure.actual, 1 << 40);
+        assert!(failure
+            .to_string()
+            .starts_with("rotation 0, ⇓(r~P,wP) at word 0x64"));
+    }
+
+    #[test]
+    fn test_catches_fault_in_32_bit_mode() {
+        let mut cells = TransitionFaultCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            index: 3,
+            bit: 0,
+        };
+
+        let failure = run_cells(&mut cells, WordWidth::Bits32, 0).unwrap_err();
+        assert_eq!(failure.index, 3);
+        assert!(failure.step.inverted);
+    }
+}
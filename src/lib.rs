@@ -0,0 +1,90 @@
+//! Multi-threaded memory stress testing for system RAM and GPU VRAM.
+//!
+//! ferritest writes test patterns to memory, reads them back and reports
+//! every word that changed. The `ferritest` binary is a thin CLI over this
+//! library; launchers and other tools can embed the same testers.
+//!
+//! # Overview
+//!
+//! - [`CpuTester`] tests system RAM with one worker thread per core.
+//! - [`GpuTester`] tests VRAM with compute shaders (requires the `gpu` feature).
+//! - Both implement [`MemoryTester`], driven by a [`TestConfig`] and reporting
+//!   live counters through a shared [`TestStats`].
+//! - [`Orchestrator`] runs several testers at once, e.g. RAM and VRAM together.
+//! - [`TestPattern`] and [`march::MarchTest`] define what is written and read,
+//!   with [`inversions`] and [`modulo`] running the moving-inversions and
+//!   modulo-N patterns;
+//!   [`address`] probes for aliasing address lines, [`block_move`] for
+//!   faults in bulk copies, [`retention`] for cells that fade while idle
+//!   and [`rowhammer`] for disturbance errors.
+//! - [`observer::TestObserver`] receives progress and error events while a
+//!   test runs; [`observer::TerminalObserver`] is the CLI's progress display.
+//! - [`report::Report`] turns results into JSON, JUnit XML or TAP.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ferritest::{CpuTester, CpuTesterConfig, MemoryTester, TestConfig, TestPattern, TestStats};
+//! use std::sync::atomic::AtomicBool;
+//! use std::sync::Arc;
+//!
+//! let mut tester = CpuTester::new(CpuTesterConfig {
+//!     memory_mb: 512,
+//!     ..Default::default()
+//! });
+//! let config = TestConfig {
+//!     memory_mb: 512,
+//!     patterns: vec![TestPattern::WalkingOnes, TestPattern::RandomPattern],
+//!     seed: 42,
+//!     ..Default::default()
+//! };
+//! let stats = Arc::new(TestStats::new());
+//! let should_stop = Arc::new(AtomicBool::new(false));
+//!
+//! let results = tester.run_tests(&config, Arc::clone(&stats), should_stop)?;
+//! for result in &results {
+//!     println!("{}: {} errors", result.pattern, result.errors_found);
+//! }
+//! println!("{} bytes tested", stats.get_bytes());
+//! # Ok::<(), ferritest::FerritestError>(())
+//! ```
+//!
+//! # Feature Flags
+//!
+//! - `gpu` (default): GPU VRAM testing through wgpu.
+
+pub mod address;
+pub mod affinity;
+pub mod analysis;
+pub mod badram;
+pub mod block_move;
+pub mod buffer;
+pub mod cache;
+pub mod cpu;
+pub mod error;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod inversions;
+pub mod march;
+pub mod memlock;
+pub mod modulo;
+pub mod numa;
+pub mod observer;
+pub mod orchestrator;
+pub mod pagemap;
+pub mod patterns;
+pub mod report;
+pub mod retention;
+pub mod rowhammer;
+pub mod simd;
+pub mod stats;
+pub mod traits;
+
+pub use cpu::{CpuMemoryError, CpuTestRun, CpuTester, CpuTesterConfig};
+pub use error::{FerritestError, GpuError, MemoryErrorInfo, TestKind};
+#[cfg(feature = "gpu")]
+pub use gpu::GpuTester;
+pub use orchestrator::Orchestrator;
+pub use patterns::TestPattern;
+pub use stats::TestStats;
+pub use traits::{MemoryTester, ProgressUpdate, TestConfig, TestResult};
@@ -1,30 +1,53 @@
-mod cpu;
-mod error;
-#[cfg(feature = "gpu")]
-mod gpu;
-mod patterns;
-mod stats;
-mod traits;
-
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use clap::Parser;
-use cpu::{CpuTester, CpuTesterConfig};
-use stats::TestStats;
+use clap::{Parser, Subcommand, ValueEnum};
+use ferritest::affinity;
+use ferritest::analysis::ErrorAnalysis;
+use ferritest::badram::{self, BadRamReport};
+use ferritest::buffer::HugePages;
+use ferritest::cache::WriteMode;
+use ferritest::march::MarchTest;
+use ferritest::modulo;
+use ferritest::numa;
+use ferritest::observer::TerminalObserver;
+use ferritest::report::{DeviceKind, DeviceReport, ErrorRecord, Report, ReportConfig, Totals};
+use ferritest::retention::Retention;
+use ferritest::rowhammer::{self, Rowhammer};
+use ferritest::{
+    CpuMemoryError, CpuTestRun, CpuTester, CpuTesterConfig, MemoryErrorInfo, TestKind, TestPattern,
+    TestStats,
+};
 
 #[cfg(feature = "gpu")]
-use gpu::{enumerate_gpus, select_gpu, GpuTester};
+use ferritest::gpu::{default_gpu_index, enumerate_gpus, select_gpu, GpuInfo};
+#[cfg(feature = "gpu")]
+use ferritest::{FerritestError, GpuError, GpuTester, TestResult};
 
 #[cfg(feature = "gpu")]
 use indicatif::{ProgressBar, ProgressStyle};
 
-#[cfg(feature = "gpu")]
-use traits::{MemoryTester, TestConfig};
+use ferritest::{MemoryTester, Orchestrator, TestConfig};
 
 const DEFAULT_TOTAL_MB: usize = 1024; // 1 GB default
 
+/// Set by the Ctrl+C handler, so reports can tell an interrupted run from one
+/// that stopped on its own (duration reached or error limit hit).
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Machine-readable report formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    /// Versioned JSON document
+    Json,
+    /// JUnit XML, one test suite per device and one test case per pattern pass
+    Junit,
+    /// TAP version 13, one test point per pattern pass
+    Tap,
+}
+
 /// GPU selection mode for multi-GPU systems.
 #[cfg(feature = "gpu")]
 #[derive(Debug, Clone, PartialEq)]
@@ -52,9 +75,22 @@ impl GpuSelection {
     }
 }
 
+#[derive(Subcommand, Debug, PartialEq)]
+enum Command {
+    /// Test RAM and print memmap=/GRUB_BADRAM exclusions for the faulty pages found
+    Badram {
+        /// Size the excluded regions are aligned to (power of two, e.g. 4K, 2M, 1G)
+        #[arg(long, default_value = "4K", value_parser = badram::parse_granularity)]
+        granularity: u64,
+    },
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Comprehensive memory stress tester", long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short, long, default_value_t = DEFAULT_TOTAL_MB)]
     memory_mb: usize,
 
@@ -74,10 +110,102 @@ struct Args {
     )]
     continuous: bool,
 
+    /// Only run these patterns, comma-separated (e.g. walking-ones,random)
+    #[arg(long, value_name = "PATTERNS", value_delimiter = ',')]
+    patterns: Vec<TestPattern>,
+
+    /// Skip these patterns, comma-separated
+    #[arg(long, value_name = "PATTERNS", value_delimiter = ',')]
+    exclude_patterns: Vec<TestPattern>,
+
+    /// Check every Nth word in the modulo-n pattern
+    #[arg(long, value_name = "N", default_value_t = modulo::DEFAULT_N, value_parser = parse_modulo_n)]
+    modulo_n: usize,
+
+    /// Keep testing after a memory error instead of stopping
+    #[arg(long, default_value_t = false)]
+    keep_going: bool,
+
+    /// Keep testing until this many errors are found, then stop
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// Master seed for reproducible runs (random if omitted; always printed)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Number the first pass N, replaying pass N of an earlier run with the same --seed (system RAM only)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    start_pass: u64,
+
+    /// Run the address-line test after the patterns to find aliasing offsets and bad address bits
+    #[arg(long, default_value_t = false)]
+    address_lines: bool,
+
+    /// Run the block-move test after the address-line test, moving data between block halves with each copy method
+    #[arg(long, default_value_t = false)]
+    block_move: bool,
+
+    /// March tests to run after the patterns (mats+, march-c-, march-b, march-ss or all)
+    #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+    march: Vec<String>,
+
+    /// Run the retention test: fill with zeros and ones, idle this long (e.g. 90s), then verify
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    retention: Option<Duration>,
+
+    /// Lengthen the retention idle period by this much on each pass (e.g. 30s)
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration, requires = "retention")]
+    retention_ramp: Option<Duration>,
+
+    /// Run the rowhammer test after the retention test, hammering aggressor pairs for this long per pass (e.g. 60s; Linux x86_64 only)
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    rowhammer: Option<Duration>,
+
+    /// Aggressor pairs the rowhammer test splits its time between
+    #[arg(long, value_name = "N", default_value_t = rowhammer::DEFAULT_PAIRS, value_parser = parse_rowhammer_pairs, requires = "rowhammer")]
+    rowhammer_pairs: usize,
+
+    /// Pin each worker thread to one CPU core (Linux only)
+    #[arg(long, default_value_t = false)]
+    pin_threads: bool,
+
+    /// Cores to pin workers to, e.g. 0-7,16 (implies --pin-threads)
+    #[arg(long, value_name = "LIST")]
+    cpus: Option<String>,
+
+    /// Bind CPU test memory to these NUMA nodes, e.g. 0,1 or all (Linux only)
+    #[arg(long, value_name = "NODES|all")]
+    numa_node: Option<String>,
+
+    /// Verify each node's memory from the next node's CPUs (implies --numa-node all)
+    #[arg(long, default_value_t = false)]
+    cross_node: bool,
+
+    /// Fail instead of warning when test memory cannot be locked into RAM
+    #[arg(long, default_value_t = false)]
+    require_locked: bool,
+
+    /// Back CPU test memory with huge pages: off, thp, 2m or 1g (falls back when unavailable)
+    #[arg(long, value_name = "SIZE", default_value = "off")]
+    huge_pages: HugePages,
+
+    /// How CPU pattern fills write memory: cached, or streaming with non-temporal stores
+    #[arg(long, value_name = "MODE", default_value = "cached")]
+    write_mode: WriteMode,
+
     /// Test GPU VRAM instead of CPU RAM
     #[arg(long, default_value_t = false)]
     gpu: bool,
 
+    /// Test CPU RAM; with --gpu, test RAM and VRAM at the same time
+    #[arg(long, default_value_t = false)]
+    cpu: bool,
+
+    /// Test CPU RAM and every GPU at the same time
+    #[arg(long, default_value_t = false, conflicts_with = "gpu_index")]
+    all_devices: bool,
+
     /// Select GPU by index or 'all' for all GPUs (use --list-gpus to see available)
     #[arg(long, value_name = "INDEX|all")]
     gpu_index: Option<String>,
@@ -89,6 +217,83 @@ struct Args {
     /// Timeout per GPU operation in seconds
     #[arg(long, default_value_t = 30)]
     gpu_timeout: u64,
+
+    /// Emit a machine-readable report (to stdout unless --report-file is given)
+    #[arg(long, value_enum)]
+    format: Option<ReportFormat>,
+
+    /// Write the report to this file and keep the text summary on stdout
+    #[arg(long, value_name = "PATH", requires = "format")]
+    report_file: Option<PathBuf>,
+}
+
+impl Args {
+    /// Returns whether several devices are tested concurrently.
+    fn concurrent(&self) -> bool {
+        self.all_devices || (self.cpu && self.gpu)
+    }
+
+    /// Returns whether a test other than the patterns runs on the CPU, so
+    /// excluding every pattern still leaves work to do.
+    fn runs_other_tests(&self) -> bool {
+        let cpu_tested = self.concurrent() || !self.gpu;
+        cpu_tested
+            && (self.address_lines
+                || self.block_move
+                || !self.march.is_empty()
+                || self.retention.is_some()
+                || self.rowhammer.is_some())
+    }
+
+    /// Returns whether the human-readable summary goes to stdout.
+    ///
+    /// It is suppressed only when the machine-readable report takes its place.
+    fn prints_text(&self) -> bool {
+        self.format.is_none() || self.report_file.is_some()
+    }
+
+    /// Returns the options recorded in the report.
+    fn report_config(&self, patterns: &[TestPattern], march_tests: &[MarchTest]) -> ReportConfig {
+        ReportConfig {
+            memory_mb: self.memory_mb,
+            threads: self.threads,
+            continuous: self.continuous,
+            duration_ms: self
+                .duration
+                .as_ref()
+                .and_then(|s| parse_duration(s))
+                .map(|d| d.as_millis() as u64),
+            patterns: patterns.to_vec(),
+            modulo_n: patterns
+                .contains(&TestPattern::ModuloN)
+                .then_some(self.modulo_n),
+            address_lines: self.address_lines,
+            block_move: self.block_move,
+            march_tests: march_tests.iter().map(|m| m.name().to_string()).collect(),
+            retention_ms: self.retention.map(|d| d.as_millis() as u64),
+            retention_ramp_ms: self.retention_ramp.map(|d| d.as_millis() as u64),
+            rowhammer_ms: self.rowhammer.map(|d| d.as_millis() as u64),
+            rowhammer_pairs: self.rowhammer.map(|_| self.rowhammer_pairs),
+            keep_going: self.keep_going,
+            max_errors: self.max_errors,
+        }
+    }
+}
+
+fn parse_modulo_n(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= 2 => Ok(n),
+        Ok(_) => Err("must be at least 2".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_rowhammer_pairs(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(n) => Ok(n),
+        Err(e) => Err(e.to_string()),
+    }
 }
 
 fn parse_duration(s: &str) -> Option<Duration> {
@@ -98,37 +303,100 @@ fn parse_duration(s: &str) -> Option<Duration> {
     humantime::parse_duration(s).ok()
 }
 
-/// Find the default GPU index using auto-select logic (discrete > integrated > virtual).
-#[cfg(feature = "gpu")]
-fn find_default_gpu_index(gpus: &[gpu::GpuInfo]) -> usize {
-    use wgpu::DeviceType;
-
-    // Prefer discrete GPU
-    if let Some(gpu) = gpus
-        .iter()
-        .find(|g| g.device_type == DeviceType::DiscreteGpu)
-    {
-        return gpu.index;
+/// Resolve `--march` names into built-in March tests.
+fn parse_march_tests(names: &[String]) -> Result<Vec<MarchTest>, String> {
+    let mut tests = Vec::new();
+    for name in names {
+        if name.eq_ignore_ascii_case("all") {
+            tests.extend(MarchTest::all_builtin());
+            continue;
+        }
+        let test = MarchTest::from_name(name).ok_or_else(|| {
+            format!(
+                "Unknown March test: '{}'. Use mats+, march-c-, march-b, march-ss or all.",
+                name
+            )
+        })?;
+        tests.push(test);
     }
+    Ok(tests)
+}
 
-    // Then integrated GPU
-    if let Some(gpu) = gpus
-        .iter()
-        .find(|g| g.device_type == DeviceType::IntegratedGpu)
-    {
-        return gpu.index;
-    }
+/// Where CPU workers run and where their memory lives.
+#[derive(Debug, Default, PartialEq)]
+struct Placement {
+    /// Cores to pin workers to; `None` leaves them unpinned, empty pins to every core.
+    cpus: Option<Vec<usize>>,
+    /// NUMA nodes to bind worker memory to; empty leaves placement to the kernel.
+    numa_nodes: Vec<usize>,
+}
 
-    // Then virtual GPU
-    if let Some(gpu) = gpus
+fn parse_placement(args: &Args) -> Result<Placement, String> {
+    Ok(Placement {
+        cpus: parse_cpus(args)?,
+        numa_nodes: parse_numa_nodes(args)?,
+    })
+}
+
+/// Returns the cores to pin workers to, or `None` when threads are not pinned.
+///
+/// An empty list pins workers to every core this process may run on.
+fn parse_cpus(args: &Args) -> Result<Option<Vec<usize>>, String> {
+    if !args.pin_threads && args.cpus.is_none() {
+        return Ok(None);
+    }
+    if !affinity::supported() {
+        eprintln!("Warning: thread pinning is only supported on Linux; workers will not be pinned");
+        return Ok(None);
+    }
+    let Some(list) = &args.cpus else {
+        return Ok(Some(Vec::new()));
+    };
+    let cpus = affinity::parse_cpu_list(list)?;
+    let available = affinity::available_cpus();
+    let missing: Vec<usize> = cpus
         .iter()
-        .find(|g| g.device_type == DeviceType::VirtualGpu)
-    {
-        return gpu.index;
+        .copied()
+        .filter(|cpu| !available.contains(cpu))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "CPU {} not available; this process may run on {}",
+            affinity::format_cpu_list(&missing),
+            affinity::format_cpu_list(&available)
+        ));
     }
+    Ok(Some(cpus))
+}
 
-    // Fall back to first
-    0
+/// Returns the NUMA nodes to bind worker memory to, empty when memory is not bound.
+fn parse_numa_nodes(args: &Args) -> Result<Vec<usize>, String> {
+    if args.numa_node.is_none() && !args.cross_node {
+        return Ok(Vec::new());
+    }
+    if !numa::supported() {
+        eprintln!("Warning: NUMA placement is only supported on Linux; memory will not be bound");
+        return Ok(Vec::new());
+    }
+    let nodes = numa::nodes();
+    if nodes.is_empty() {
+        return Err(format!(
+            "No NUMA topology found in {}",
+            numa::SYSFS_NODE_PATH
+        ));
+    }
+    let selected = numa::parse_node_list(args.numa_node.as_deref().unwrap_or("all"), &nodes)?;
+    if selected.is_empty() {
+        return Err("No NUMA node with memory found".to_string());
+    }
+    let with_cpus = nodes.iter().filter(|node| !node.cpus.is_empty()).count();
+    if args.cross_node && with_cpus < 2 {
+        return Err(format!(
+            "--cross-node needs at least two NUMA nodes with CPUs; found {}",
+            with_cpus
+        ));
+    }
+    Ok(selected)
 }
 
 fn main() {
@@ -140,6 +408,7 @@ fn main() {
     ctrlc::set_handler(move || {
         println!();
         println!("Received Ctrl+C, stopping gracefully...");
+        INTERRUPTED.store(true, Ordering::Relaxed);
         should_stop_handler.store(true, Ordering::Relaxed);
     })
     .expect("Error setting Ctrl+C handler");
@@ -153,7 +422,7 @@ fn main() {
                 println!("No GPUs found.");
             } else {
                 // Find the default GPU index (auto-select logic)
-                let default_index = find_default_gpu_index(&gpus);
+                let default_index = default_gpu_index(&gpus);
 
                 println!("Available GPUs:");
                 for gpu_info in &gpus {
@@ -198,41 +467,264 @@ fn main() {
         }
     }
 
+    if (args.gpu || args.all_devices) && matches!(args.command, Some(Command::Badram { .. })) {
+        eprintln!(
+            "Error: badram exclusions apply to system RAM only; drop --gpu and --all-devices"
+        );
+        std::process::exit(1);
+    }
+
     // Warn if --gpu-index used without --gpu
     if args.gpu_index.is_some() && !args.gpu {
         eprintln!("Warning: --gpu-index has no effect without --gpu flag");
     }
 
-    // Run appropriate tester
-    if args.gpu {
-        #[cfg(feature = "gpu")]
-        {
-            run_gpu_test(&args, Arc::clone(&should_stop));
+    if args.start_pass > 0 && (args.gpu || args.all_devices) {
+        eprintln!("Warning: --start-pass applies to system RAM only; GPUs start at pass 0");
+    }
+
+    let patterns = match TestPattern::select(
+        &args.patterns,
+        &args.exclude_patterns,
+        args.runs_other_tests(),
+    ) {
+        Ok(patterns) => patterns,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    #[cfg(feature = "gpu")]
+    if args.gpu || args.all_devices {
+        let cpu_only: Vec<&str> = patterns
+            .iter()
+            .filter(|p| p.pattern_id().is_none())
+            .map(|p| p.slug())
+            .collect();
+        if !cpu_only.is_empty() {
+            eprintln!(
+                "Warning: {} run on the CPU only; GPUs skip them",
+                cpu_only.join(", ")
+            );
+        }
+    }
+
+    let march_tests = match parse_march_tests(&args.march) {
+        Ok(tests) => tests,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let placement = match parse_placement(&args) {
+        Ok(placement) => placement,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
+    };
+
+    if !args.prints_text() && matches!(args.command, Some(Command::Badram { .. })) {
+        eprintln!("Error: badram prints its exclusions as text; add --report-file to write the report elsewhere");
+        std::process::exit(1);
+    }
+
+    if args.rowhammer.is_some() && !rowhammer::supported() {
+        eprintln!("Error: --rowhammer needs Linux on x86_64");
+        std::process::exit(1);
+    }
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let start_time = Instant::now();
+
+    // Run appropriate tester
+    #[cfg(feature = "gpu")]
+    let devices = if args.concurrent() {
+        run_concurrent_test(
+            &args,
+            &patterns,
+            &march_tests,
+            &placement,
+            seed,
+            Arc::clone(&should_stop),
+        )
+    } else if args.gpu {
+        run_gpu_test(&args, &patterns, seed, Arc::clone(&should_stop))
+    } else {
+        vec![run_cpu_test(
+            &args,
+            &patterns,
+            &march_tests,
+            &placement,
+            seed,
+            Arc::clone(&should_stop),
+        )]
+    };
+    #[cfg(not(feature = "gpu"))]
+    let devices = if args.concurrent() {
+        run_concurrent_test(
+            &args,
+            &patterns,
+            &march_tests,
+            &placement,
+            seed,
+            Arc::clone(&should_stop),
+        )
     } else {
-        run_cpu_test(&args, Arc::clone(&should_stop));
+        vec![run_cpu_test(
+            &args,
+            &patterns,
+            &march_tests,
+            &placement,
+            seed,
+            Arc::clone(&should_stop),
+        )]
+    };
+
+    let report = Report::new(
+        args.report_config(&patterns, &march_tests),
+        seed,
+        devices,
+        INTERRUPTED.load(Ordering::Relaxed),
+        start_time.elapsed(),
+    );
+    if let Some(format) = args.format {
+        write_report(&report, format, args.report_file.as_deref());
+    }
+    std::process::exit(report.exit_code());
+}
+
+/// Writes the report to `path`, or to stdout if no path is given.
+fn write_report(report: &Report, format: ReportFormat, path: Option<&Path>) {
+    let output = match format {
+        ReportFormat::Json => report.to_json(),
+        ReportFormat::Junit => report.to_junit_xml(),
+        ReportFormat::Tap => report.to_tap(),
+    };
+    match path {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, output) {
+                eprintln!("Error: failed to write report to {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", output),
     }
 }
 
-fn run_cpu_test(args: &Args, should_stop: Arc<AtomicBool>) {
-    // Create CPU tester configuration
+/// Creates the CPU tester, with the terminal progress display attached.
+fn cpu_tester(
+    args: &Args,
+    patterns: &[TestPattern],
+    march_tests: &[MarchTest],
+    placement: &Placement,
+    seed: u64,
+) -> CpuTester {
     let config = CpuTesterConfig {
         memory_mb: args.memory_mb,
         threads: args.threads,
         continuous: args.continuous,
         timeout: args.duration.as_ref().and_then(|s| parse_duration(s)),
         verbose: args.verbose,
+        patterns: patterns.to_vec(),
+        modulo_n: args.modulo_n,
+        address_lines: args.address_lines,
+        block_move: args.block_move,
+        march_tests: march_tests.to_vec(),
+        retention: args
+            .retention
+            .map(|idle| Retention::new(idle).with_ramp(args.retention_ramp.unwrap_or_default())),
+        rowhammer: args
+            .rowhammer
+            .map(|budget| Rowhammer::new(budget).with_pairs(args.rowhammer_pairs)),
+        seed,
+        start_pass: args.start_pass,
+        keep_going: args.keep_going,
+        max_errors: args.max_errors,
+        pin_threads: placement.cpus.is_some(),
+        cpus: placement.cpus.clone().unwrap_or_default(),
+        numa_nodes: placement.numa_nodes.clone(),
+        cross_node: args.cross_node && !placement.numa_nodes.is_empty(),
+        lock_memory: true,
+        require_locked: args.require_locked,
+        huge_pages: args.huge_pages,
+        write_mode: args.write_mode,
     };
 
-    let tester = CpuTester::new(config);
+    let observer = if args.prints_text() {
+        TerminalObserver::new()
+    } else {
+        TerminalObserver::new().without_header()
+    };
+    CpuTester::new(config).with_observer(Arc::new(observer))
+}
+
+fn run_cpu_test(
+    args: &Args,
+    patterns: &[TestPattern],
+    march_tests: &[MarchTest],
+    placement: &Placement,
+    seed: u64,
+    should_stop: Arc<AtomicBool>,
+) -> DeviceReport {
+    let tester = cpu_tester(args, patterns, march_tests, placement, seed);
     let stats = Arc::new(TestStats::new());
 
     let start_time = Instant::now();
 
     // Run the test
-    let errors = tester.run(Arc::clone(&stats), Arc::clone(&should_stop));
+    let run = tester.run(Arc::clone(&stats), Arc::clone(&should_stop));
+    let duration = start_time.elapsed();
 
-    let was_interrupted = should_stop.load(Ordering::Relaxed);
+    if args.prints_text() {
+        print_cpu_summary(args, &tester, &stats, &run, duration);
+    }
+
+    let device = DeviceReport::new(
+        DeviceKind::Cpu,
+        tester.name(),
+        tester.device_info(),
+        duration,
+        Totals::from(stats.as_ref()),
+        &run.results,
+        run.errors.iter().map(ErrorRecord::from).collect(),
+    );
+    with_cpu_run(device, &tester, &run)
+}
+
+/// Attaches the per-core, per-node, residency, page, cache and per-test
+/// results of a CPU run to its report, failing it if its memory could not be
+/// allocated or locked as required.
+fn with_cpu_run(device: DeviceReport, tester: &CpuTester, run: &CpuTestRun) -> DeviceReport {
+    let device = device
+        .with_cores(&run.cores)
+        .with_nodes(&run.nodes)
+        .with_residency(&run.residency)
+        .with_pages(&run.pages)
+        .with_cache(&run.cache, run.write_mode)
+        .with_test_errors(&run.test_errors);
+    if let Err(e) = run.ensure_allocated() {
+        return device.with_error(e.to_string());
+    }
+    match run.residency.ensure_locked() {
+        Err(e) if tester.config().require_locked => device.with_error(e.to_string()),
+        _ => device,
+    }
+}
+
+/// Prints the human-readable summary of a CPU run.
+fn print_cpu_summary(
+    args: &Args,
+    tester: &CpuTester,
+    stats: &TestStats,
+    run: &CpuTestRun,
+    duration: Duration,
+) {
+    let errors = &run.errors;
+    let seed = tester.config().seed;
+    let was_interrupted = INTERRUPTED.load(Ordering::Relaxed);
 
     // Print results
     println!();
@@ -247,32 +739,501 @@ fn run_cpu_test(args: &Args, should_stop: Arc<AtomicBool>) {
         stats.get_bytes() / (1024 * 1024)
     );
     println!("Total tests completed: {}", stats.get_tests());
-    println!("Errors found: {}", errors.len());
-    println!("Duration: {:.2}s", start_time.elapsed().as_secs_f64());
+    if stats.get_errors() > errors.len() as u64 {
+        println!(
+            "Errors found: {} (first {} recorded)",
+            stats.get_errors(),
+            errors.len()
+        );
+    } else {
+        println!("Errors found: {}", errors.len());
+    }
+    println!("Duration: {:.2}s", duration.as_secs_f64());
+    println!("Seed: {}", seed);
+
+    let residency = &run.residency;
+    println!(
+        "Memory locked: {} of {} MB",
+        residency.locked_bytes / (1024 * 1024),
+        residency.total_bytes / (1024 * 1024)
+    );
+    let lock_failed = tester.config().require_locked && !residency.fully_locked();
+    if let Some(percent) = residency.resident_percent().filter(|_| !lock_failed) {
+        println!("Resident in RAM: {:.1}%", percent);
+    }
+    let cache = &run.cache;
+    println!(
+        "Cache bypass: {} (reads after flush {:.1} ns, cached {:.1} ns)",
+        cache.bypass, cache.flushed_ns, cache.cached_ns
+    );
+    if run.write_mode != WriteMode::Cached {
+        println!(
+            "Write mode: {} ({})",
+            run.write_mode,
+            run.write_mode.describe()
+        );
+    }
+    let pages = &run.pages;
+    match (pages.fell_back(), pages.huge_percent()) {
+        (true, _) => println!(
+            "Pages: {} ({} unavailable)",
+            pages.backing,
+            pages.requested.describe()
+        ),
+        (false, Some(percent)) if pages.requested != HugePages::Off => {
+            println!("Pages: {} ({:.1}% huge)", pages.backing, percent)
+        }
+        _ => println!("Pages: {}", pages.backing),
+    }
+    if let Err(e) = residency.ensure_locked() {
+        println!();
+        if lock_failed {
+            println!("ERROR: {}", e);
+        } else {
+            println!("WARNING: {}", e);
+            println!("Unlocked pages can be swapped out; use --require-locked to fail instead.");
+        }
+    }
+
+    if !run.cores.is_empty() {
+        println!();
+        println!("PER-CORE RESULTS:");
+        println!("=================");
+        for core in &run.cores {
+            println!(
+                "Core {}: {} MB tested, {} errors",
+                core.core,
+                core.bytes_tested / (1024 * 1024),
+                core.errors_found
+            );
+        }
+    }
+
+    if !run.nodes.is_empty() {
+        println!();
+        println!("PER-NODE RESULTS:");
+        println!("=================");
+        for node in &run.nodes {
+            let unbound = if node.unbound_bytes > 0 {
+                format!(
+                    " ({} MB could not be bound to the node)",
+                    node.unbound_bytes / (1024 * 1024)
+                )
+            } else {
+                String::new()
+            };
+            println!(
+                "Node {}: {} MB tested, {} errors{}",
+                node.node,
+                node.bytes_tested / (1024 * 1024),
+                node.errors_found,
+                unbound
+            );
+        }
+    }
 
     if !errors.is_empty() {
         println!();
         println!("MEMORY ERRORS DETECTED:");
         println!("=======================");
         for (i, error) in errors.iter().enumerate() {
-            println!(
-                "Error {}: Thread {} - Pattern {} - Offset: 0x{:X}",
-                i + 1,
-                error.thread_id,
-                error.pattern.name(),
-                error.offset
-            );
+            let line = match &error.test {
+                TestKind::March(failure) => format!(
+                    "Error {}: Thread {} - March {} - Offset: 0x{:X} - Physical: {}",
+                    i + 1,
+                    error.thread_id,
+                    failure,
+                    error.offset,
+                    error.physical_address
+                ),
+                test => {
+                    let label = match test {
+                        TestKind::MovingInversions(pattern, step) => {
+                            format!("Pattern {} ({})", pattern.name(), step)
+                        }
+                        TestKind::ModuloN(phase) => {
+                            format!("Pattern {} ({})", TestPattern::ModuloN.name(), phase)
+                        }
+                        TestKind::AddressLines(line) => format!("Address lines {}", line),
+                        TestKind::BlockMove(method) => format!("Block move with {}", method),
+                        TestKind::Retention(idle) => {
+                            format!("Retention after {} idle", humantime::format_duration(*idle))
+                        }
+                        TestKind::Rowhammer(hammering) => format!("Rowhammer, {}", hammering),
+                        test => format!("Pattern {}", test.name()),
+                    };
+                    format!(
+                        "Error {}: Thread {} - {} - Offset: 0x{:X} - Physical: {} - Expected: 0x{:016X}, Actual: 0x{:016X}",
+                        i + 1,
+                        error.thread_id,
+                        label,
+                        error.offset,
+                        error.physical_address,
+                        error.expected,
+                        error.actual
+                    )
+                }
+            };
+            println!("{}{}", line, core_suffix(error));
         }
-        std::process::exit(1);
+        print_cpu_error_analysis(args, tester, errors);
+    } else if let Some(caveat) = cpu_run_caveat(tester, run) {
+        println!();
+        println!("{}", caveat);
     } else {
         println!();
         println!("SUCCESS: No memory errors detected!");
-        std::process::exit(0);
     }
 }
 
+/// Prints the analysis of the errors a CPU run recorded, the badram
+/// exclusions when asked for, and the command that replays the first failing
+/// pass.
+fn print_cpu_error_analysis(args: &Args, tester: &CpuTester, errors: &[CpuMemoryError]) {
+    let infos: Vec<MemoryErrorInfo> = errors.iter().map(MemoryErrorInfo::from).collect();
+    println!();
+    println!("ERROR ANALYSIS:");
+    println!("===============");
+    print!("{}", ErrorAnalysis::from_errors(&infos));
+
+    if let Some(Command::Badram { granularity }) = args.command {
+        println!();
+        println!("BADRAM EXCLUSIONS:");
+        println!("==================");
+        print!("{}", BadRamReport::from_errors(&infos, granularity));
+    }
+
+    // Replay the first failing pass with the patterns that failed in it
+    let first_pass = errors
+        .iter()
+        .filter(|e| e.test.pattern().is_some())
+        .map(|e| e.pass)
+        .min();
+    if let Some(first_pass) = first_pass {
+        let config = tester.config();
+        let mut failed_patterns: Vec<TestPattern> = Vec::new();
+        for pattern in errors
+            .iter()
+            .filter(|e| e.pass == first_pass)
+            .filter_map(|e| e.test.pattern())
+        {
+            if !failed_patterns.contains(&pattern) {
+                failed_patterns.push(pattern);
+            }
+        }
+        let slugs: Vec<&str> = failed_patterns.iter().map(|p| p.slug()).collect();
+        // Options that change what the failed patterns write
+        let mut options = String::new();
+        if failed_patterns.contains(&TestPattern::ModuloN) {
+            options.push_str(&format!(" --modulo-n {}", config.modulo_n));
+        }
+        if config.write_mode != WriteMode::Cached {
+            options.push_str(&format!(" --write-mode {}", config.write_mode));
+        }
+        if config.huge_pages != HugePages::Off {
+            options.push_str(&format!(" --huge-pages {}", config.huge_pages));
+        }
+        println!();
+        println!(
+            "Replay pass {} with: ferritest -m {} -t {} --seed {} --start-pass {} --patterns {}{}",
+            first_pass,
+            config.memory_mb,
+            tester.num_threads(),
+            config.seed,
+            first_pass,
+            slugs.join(","),
+            options
+        );
+    }
+}
+
+/// Returns the verdict that replaces SUCCESS for a CPU run without memory
+/// errors whose memory was not fully allocated, locked, resident, uncached
+/// or bound to its NUMA node.
+fn cpu_run_caveat(tester: &CpuTester, run: &CpuTestRun) -> Option<String> {
+    let residency = &run.residency;
+    if let Err(e) = run.ensure_allocated() {
+        Some(format!("FAILURE: {}; the run stopped early.", e))
+    } else if tester.config().require_locked && !residency.fully_locked() {
+        Some("FAILURE: Test memory could not be locked into RAM; nothing was tested.".to_string())
+    } else if let Some(percent) = residency.resident_percent().filter(|&p| p < 100.0) {
+        Some(format!(
+            "WARNING: No memory errors detected, but only {:.1}% of the tested memory was resident in RAM; the rest may have been tested on swap.",
+            percent
+        ))
+    } else if !run.cache.reaches_dram() {
+        Some("WARNING: No memory errors detected, but reads after a cache flush were not clearly slower than cached reads; verification may have been served from the CPU caches.".to_string())
+    } else if run.nodes.iter().any(|node| node.unbound_bytes > 0) {
+        Some("WARNING: No memory errors detected, but some test memory could not be bound to its NUMA node; those DIMMs may not have been tested.".to_string())
+    } else {
+        None
+    }
+}
+
+/// Returns the core that saw `error` and its cross-check, for appending to an error line.
+fn core_suffix(error: &CpuMemoryError) -> String {
+    let mut suffix = String::new();
+    if let Some(core) = error.core {
+        suffix.push_str(&format!(" - Core: {}", core));
+    }
+    if let Some(check) = &error.cross_check {
+        suffix.push_str(&format!(" - {}", check));
+    }
+    suffix
+}
+
+/// Tests CPU RAM and the selected GPUs at the same time.
+///
+/// With `--all-devices` every GPU is tested; with `--cpu --gpu` the GPUs
+/// chosen by `--gpu-index`. `--memory-mb` applies to each device.
+fn run_concurrent_test(
+    args: &Args,
+    patterns: &[TestPattern],
+    march_tests: &[MarchTest],
+    placement: &Placement,
+    seed: u64,
+    should_stop: Arc<AtomicBool>,
+) -> Vec<DeviceReport> {
+    let cpu = cpu_tester(args, patterns, march_tests, placement, seed);
+
+    // Kind, display name and GPU index of each tester, in tester order
+    let mut devices = vec![(DeviceKind::Cpu, cpu.name().to_string(), None)];
+    let mut testers: Vec<Box<dyn MemoryTester>> = vec![Box::new(cpu)];
+    let (gpu_testers, failures) = open_gpu_testers(args);
+    for (name, index, tester) in gpu_testers {
+        devices.push((DeviceKind::Gpu, name, Some(index)));
+        testers.push(tester);
+    }
+
+    if args.prints_text() {
+        let names: Vec<String> = devices
+            .iter()
+            .map(|(_, name, index)| device_label(name, *index))
+            .collect();
+        println!("Concurrent Memory Stress Test");
+        println!("=============================");
+        println!("Devices: {}", names.join(" + "));
+        println!();
+    }
+
+    let config = TestConfig {
+        memory_mb: args.memory_mb,
+        patterns: patterns.to_vec(),
+        continuous: args.continuous,
+        timeout: args.duration.as_ref().and_then(|s| parse_duration(s)),
+        threads: args.threads,
+        verbose: args.verbose,
+        seed,
+    };
+    let mut orchestrator = Orchestrator::new(testers)
+        .with_stop_on_error(!args.keep_going && args.max_errors.is_none());
+    let run = orchestrator.run(&config, should_stop);
+    // The CPU tester comes first; its run carries more than the trait reports
+    let cpu = orchestrator.testers()[0]
+        .as_any()
+        .and_then(|tester| tester.downcast_ref::<CpuTester>())
+        .and_then(|tester| Some((tester, tester.last_run()?)));
+
+    let tested = run
+        .outcomes
+        .iter()
+        .zip(devices)
+        .map(|(outcome, (kind, name, index))| {
+            let device = DeviceReport::new(
+                kind,
+                &name,
+                outcome.device_info.clone(),
+                outcome.duration,
+                Totals::from(outcome.stats.as_ref()),
+                outcome.results.as_deref().unwrap_or_default(),
+                outcome.errors.iter().map(ErrorRecord::from).collect(),
+            );
+            let device = match (index, cpu) {
+                (Some(index), _) => device.with_index(index),
+                (None, Some((tester, run))) => with_cpu_run(device, tester, run),
+                (None, None) => device,
+            };
+            match &outcome.results {
+                Ok(_) => device,
+                Err(e) => device.with_error(e.to_string()),
+            }
+        });
+    let reports: Vec<DeviceReport> = tested.chain(failures).collect();
+
+    if args.prints_text() {
+        print_concurrent_summary(args, &reports, cpu, run.duration, seed);
+    }
+    reports
+}
+
+/// Returns the name a device is shown under in text output.
+fn device_label(name: &str, gpu_index: Option<usize>) -> String {
+    match gpu_index {
+        Some(index) => format!("GPU {}: {}", index, name),
+        None => name.to_string(),
+    }
+}
+
+/// Prints the human-readable summary of a concurrent run.
+///
+/// `cpu` is the CPU tester and its run, whose errors are analysed and whose
+/// memory is checked the same way as in a CPU-only run.
+fn print_concurrent_summary(
+    args: &Args,
+    devices: &[DeviceReport],
+    cpu: Option<(&CpuTester, &CpuTestRun)>,
+    duration: Duration,
+    seed: u64,
+) {
+    println!();
+    if INTERRUPTED.load(Ordering::Relaxed) {
+        println!("Test Interrupted");
+    } else {
+        println!("Test Complete");
+    }
+    println!("=============");
+    for device in devices {
+        let label = device_label(&device.name, device.index);
+        match &device.error {
+            Some(e) => println!("  {} - ERROR: {}", label, e),
+            None => println!(
+                "  {} - {}: {} MB tested, {} errors, {:.1}s",
+                label,
+                if device.passed { "PASSED" } else { "FAILED" },
+                device.totals.bytes_tested / (1024 * 1024),
+                device.totals.errors_found,
+                device.duration_ms as f64 / 1000.0
+            ),
+        }
+    }
+    println!("Duration: {:.2}s", duration.as_secs_f64());
+    println!("Seed: {}", seed);
+
+    let failing: Vec<&DeviceReport> = devices.iter().filter(|d| !d.errors.is_empty()).collect();
+    if !failing.is_empty() {
+        println!();
+        println!("MEMORY ERRORS DETECTED:");
+        println!("=======================");
+        for device in failing {
+            let label = device_label(&device.name, device.index);
+            for error in &device.errors {
+                println!("{}: {}", label, error);
+            }
+        }
+    }
+    if let Some((tester, run)) = cpu.filter(|(_, run)| !run.errors.is_empty()) {
+        print_cpu_error_analysis(args, tester, &run.errors);
+    }
+
+    println!();
+    if !devices.iter().all(|d| d.passed) {
+        println!("FAILURE: Memory errors or device failures detected.");
+    } else if let Some(caveat) = cpu.and_then(|(tester, run)| cpu_run_caveat(tester, run)) {
+        println!("{}", caveat);
+    } else {
+        println!("SUCCESS: No memory errors detected on any device!");
+    }
+}
+
+/// A GPU tester ready to run, with the GPU's name and index.
+type OpenedGpu = (String, usize, Box<dyn MemoryTester>);
+
+/// Opens a tester for each GPU chosen by `--gpu-index`, or for every GPU with
+/// `--all-devices`.
+///
+/// GPUs that cannot be opened are returned as failed device reports.
 #[cfg(feature = "gpu")]
-fn run_gpu_test(args: &Args, should_stop: Arc<AtomicBool>) {
+fn open_gpu_testers(args: &Args) -> (Vec<OpenedGpu>, Vec<DeviceReport>) {
+    let gpus = enumerate_gpus();
+    let mut failures = Vec::new();
+
+    let selected: Vec<&GpuInfo> = if args.all_devices {
+        if gpus.is_empty() {
+            eprintln!("Warning: no GPUs found; testing CPU RAM only");
+        }
+        gpus.iter().collect()
+    } else {
+        match GpuSelection::parse(args.gpu_index.as_deref()) {
+            Ok(GpuSelection::All) => gpus.iter().collect(),
+            Ok(GpuSelection::Auto) if gpus.is_empty() => {
+                eprintln!("Error selecting GPU: {}", GpuError::NoAdapter);
+                failures.push(DeviceReport::failed(
+                    DeviceKind::Gpu,
+                    "GPU/VRAM",
+                    String::new(),
+                    GpuError::NoAdapter.to_string(),
+                ));
+                Vec::new()
+            }
+            Ok(GpuSelection::Auto) => vec![&gpus[default_gpu_index(&gpus)]],
+            Ok(GpuSelection::Index(index)) => match gpus.get(index) {
+                Some(gpu_info) => vec![gpu_info],
+                None => {
+                    eprintln!("GPU index {} not found", index);
+                    failures.push(
+                        DeviceReport::failed(
+                            DeviceKind::Gpu,
+                            "GPU/VRAM",
+                            String::new(),
+                            format!("GPU index {} not found", index),
+                        )
+                        .with_index(index),
+                    );
+                    Vec::new()
+                }
+            },
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let mut testers = Vec::new();
+    for gpu_info in selected {
+        let tester = select_gpu(Some(gpu_info.index)).and_then(|adapter| {
+            GpuTester::new(
+                adapter,
+                gpu_info.clone(),
+                args.memory_mb,
+                args.gpu_timeout,
+                args.verbose,
+            )
+        });
+        match tester {
+            Ok(tester) => {
+                testers.push((gpu_info.name.clone(), gpu_info.index, Box::new(tester) as _))
+            }
+            Err(e) => {
+                eprintln!("Error opening GPU {}: {}", gpu_info.index, e);
+                failures.push(
+                    DeviceReport::failed(
+                        DeviceKind::Gpu,
+                        &gpu_info.name,
+                        gpu_info.to_string(),
+                        e.to_string(),
+                    )
+                    .with_index(gpu_info.index),
+                );
+            }
+        }
+    }
+    (testers, failures)
+}
+
+#[cfg(not(feature = "gpu"))]
+fn open_gpu_testers(_args: &Args) -> (Vec<OpenedGpu>, Vec<DeviceReport>) {
+    eprintln!("Warning: GPU support not compiled; testing CPU RAM only");
+    (Vec::new(), Vec::new())
+}
+
+#[cfg(feature = "gpu")]
+fn run_gpu_test(
+    args: &Args,
+    patterns: &[TestPattern],
+    seed: u64,
+    should_stop: Arc<AtomicBool>,
+) -> Vec<DeviceReport> {
     // Parse GPU selection
     let selection = match GpuSelection::parse(args.gpu_index.as_deref()) {
         Ok(s) => s,
@@ -291,8 +1252,7 @@ fn run_gpu_test(args: &Args, should_stop: Arc<AtomicBool>) {
 
     // Handle "all" GPUs mode
     if selection == GpuSelection::All {
-        run_all_gpus_test(args, Arc::clone(&should_stop));
-        return;
+        return run_all_gpus_test(args, patterns, seed, Arc::clone(&should_stop));
     }
 
     // Convert selection to index for select_gpu
@@ -307,14 +1267,19 @@ fn run_gpu_test(args: &Args, should_stop: Arc<AtomicBool>) {
         Ok(adapter) => adapter,
         Err(e) => {
             eprintln!("Error selecting GPU: {}", e);
-            std::process::exit(1);
+            return vec![DeviceReport::failed(
+                DeviceKind::Gpu,
+                "GPU/VRAM",
+                String::new(),
+                e.to_string(),
+            )];
         }
     };
 
     // Get GPU info
     let gpus = enumerate_gpus();
     let gpu_index = match &selection {
-        GpuSelection::Auto => find_default_gpu_index(&gpus),
+        GpuSelection::Auto => default_gpu_index(&gpus),
         GpuSelection::Index(i) => *i,
         GpuSelection::All => unreachable!(),
     };
@@ -322,7 +1287,13 @@ fn run_gpu_test(args: &Args, should_stop: Arc<AtomicBool>) {
         gpus[gpu_index].clone()
     } else {
         eprintln!("GPU index {} not found", gpu_index);
-        std::process::exit(1);
+        return vec![DeviceReport::failed(
+            DeviceKind::Gpu,
+            "GPU/VRAM",
+            String::new(),
+            format!("GPU index {} not found", gpu_index),
+        )
+        .with_index(gpu_index)];
     };
 
     // Create GPU tester
@@ -336,43 +1307,58 @@ fn run_gpu_test(args: &Args, should_stop: Arc<AtomicBool>) {
         Ok(tester) => tester,
         Err(e) => {
             eprintln!("Error creating GPU tester: {}", e);
-            std::process::exit(1);
+            return vec![DeviceReport::failed(
+                DeviceKind::Gpu,
+                &gpu_info.name,
+                gpu_info.to_string(),
+                e.to_string(),
+            )
+            .with_index(gpu_info.index)];
         }
     };
 
     // Print header
-    println!("GPU Memory Stress Test");
-    println!("======================");
-    println!("GPU: {} ({:?})", gpu_info.name, gpu_info.backend);
-    println!("VRAM to test: {} MB", args.memory_mb);
-    println!(
-        "Mode: {}",
-        if args.continuous {
-            "Continuous"
-        } else {
-            "Single pass"
+    if args.prints_text() {
+        println!("GPU Memory Stress Test");
+        println!("======================");
+        println!("GPU: {} ({:?})", gpu_info.name, gpu_info.backend);
+        println!("VRAM to test: {} MB", args.memory_mb);
+        println!("Seed: {}", seed);
+        println!(
+            "Mode: {}",
+            if args.continuous {
+                "Continuous"
+            } else {
+                "Single pass"
+            }
+        );
+        if let Some(ref duration_str) = args.duration {
+            println!("Duration: {}", duration_str);
         }
-    );
-    if let Some(ref duration_str) = args.duration {
-        println!("Duration: {}", duration_str);
+        println!();
     }
-    println!();
 
     // Create test config
     let config = TestConfig {
         memory_mb: args.memory_mb,
-        patterns: patterns::TestPattern::all_patterns(),
+        patterns: patterns.to_vec(),
         continuous: args.continuous,
         timeout: args.duration.as_ref().and_then(|s| parse_duration(s)),
         threads: None,
         verbose: args.verbose,
+        seed,
     };
 
     let stats = Arc::new(TestStats::new());
     let start_time = Instant::now();
 
-    // Create progress bar
-    let pb = ProgressBar::new(config.patterns.len() as u64);
+    // Create progress bar, counting only the patterns with a GPU shader
+    let gpu_patterns = config
+        .patterns
+        .iter()
+        .filter(|p| p.pattern_id().is_some())
+        .count() as u64;
+    let pb = ProgressBar::new(gpu_patterns);
     pb.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} patterns | {msg}")
@@ -403,65 +1389,96 @@ fn run_gpu_test(args: &Args, should_stop: Arc<AtomicBool>) {
 
     // Run GPU tests
     let results = tester.run_tests(&config, Arc::clone(&stats), Arc::clone(&should_stop));
+    let duration = start_time.elapsed();
 
-    // Check if interrupted before signaling stop
-    let was_interrupted = should_stop.load(Ordering::Relaxed);
+    let was_interrupted = INTERRUPTED.load(Ordering::Relaxed);
 
     // Signal progress thread to stop
     should_stop.store(true, Ordering::Relaxed);
     progress_handle.join().expect("Progress thread panicked");
 
     // Update progress bar with final count
-    pb.set_position(config.patterns.len() as u64);
+    pb.set_position(gpu_patterns);
     pb.finish_with_message(if was_interrupted {
         "Interrupted"
     } else {
         "Complete"
     });
 
-    // Print results
-    let total_errors: u64 = match &results {
-        Ok(r) => r.iter().map(|r| r.errors_found).sum(),
-        Err(_) => 0,
-    };
+    let device = gpu_device_report(&tester, &gpu_info, &stats, &results, duration);
 
-    println!();
-    if was_interrupted {
-        println!("Test Interrupted");
-    } else {
-        println!("Test Complete");
+    if args.prints_text() {
+        println!();
+        if was_interrupted {
+            println!("Test Interrupted");
+        } else {
+            println!("Test Complete");
+        }
+        println!("=============");
+        println!(
+            "Total bytes tested: {} MB",
+            stats.get_bytes() / (1024 * 1024)
+        );
+        println!("Total tests completed: {}", stats.get_tests());
+        println!(
+            "Errors found: {}",
+            device.results.iter().map(|r| r.errors_found).sum::<u64>()
+        );
+        println!("Duration: {:.2}s", duration.as_secs_f64());
+        println!("Seed: {}", seed);
+
+        match &device.error {
+            None if device.passed => {
+                println!();
+                println!("SUCCESS: No GPU memory errors detected!");
+            }
+            None => {
+                println!();
+                println!("GPU MEMORY ERRORS DETECTED!");
+            }
+            Some(e) => {
+                eprintln!();
+                eprintln!("GPU test error: {}", e);
+            }
+        }
     }
-    println!("=============");
-    println!(
-        "Total bytes tested: {} MB",
-        stats.get_bytes() / (1024 * 1024)
-    );
-    println!("Total tests completed: {}", stats.get_tests());
-    println!("Errors found: {}", total_errors);
-    println!("Duration: {:.2}s", start_time.elapsed().as_secs_f64());
 
+    vec![device]
+}
+
+/// Builds the report for a GPU that ran its tests.
+#[cfg(feature = "gpu")]
+fn gpu_device_report(
+    tester: &GpuTester,
+    gpu_info: &GpuInfo,
+    stats: &TestStats,
+    results: &Result<Vec<TestResult>, FerritestError>,
+    duration: Duration,
+) -> DeviceReport {
+    let device = DeviceReport::new(
+        DeviceKind::Gpu,
+        &gpu_info.name,
+        tester.device_info(),
+        duration,
+        Totals::from(stats),
+        results.as_deref().unwrap_or_default(),
+        tester.errors().iter().map(ErrorRecord::from).collect(),
+    )
+    .with_index(gpu_info.index);
     match results {
-        Ok(_) if total_errors == 0 => {
-            println!();
-            println!("SUCCESS: No GPU memory errors detected!");
-            std::process::exit(0);
-        }
-        Ok(_) => {
-            println!();
-            println!("GPU MEMORY ERRORS DETECTED!");
-            std::process::exit(1);
-        }
-        Err(e) => {
-            eprintln!();
-            eprintln!("GPU test error: {}", e);
-            std::process::exit(1);
-        }
+        Ok(_) => device,
+        Err(e) => device.with_error(e.to_string()),
     }
 }
 
 /// Run tests on all available GPUs sequentially.
 #[cfg(feature = "gpu")]
-fn run_all_gpus_test(args: &Args, should_stop: Arc<AtomicBool>) {
+fn run_all_gpus_test(
+    args: &Args,
+    patterns: &[TestPattern],
+    seed: u64,
+    should_stop: Arc<AtomicBool>,
+) -> Vec<DeviceReport> {
     let gpus = enumerate_gpus();
 
     if gpus.is_empty() {
@@ -469,23 +1486,37 @@ fn run_all_gpus_test(args: &Args, should_stop: Arc<AtomicBool>) {
         std::process::exit(1);
     }
 
-    println!("Testing {} GPU(s) sequentially...", gpus.len());
-    println!();
+    let text = args.prints_text();
+    if text {
+        println!("Testing {} GPU(s) sequentially...", gpus.len());
+        println!("Seed: {}", seed);
+        println!();
+    }
 
-    let mut all_passed = true;
-    let mut gpu_results: Vec<(String, bool, u64)> = Vec::new();
+    let mut devices: Vec<DeviceReport> = Vec::new();
     let overall_start = Instant::now();
 
     for gpu_info in &gpus {
-        println!("=== GPU {}: {} ===", gpu_info.index, gpu_info.name);
+        if text {
+            println!("=== GPU {}: {} ===", gpu_info.index, gpu_info.name);
+        }
 
         let adapter = match select_gpu(Some(gpu_info.index)) {
             Ok(a) => a,
             Err(e) => {
                 eprintln!("Error selecting GPU {}: {}", gpu_info.index, e);
-                gpu_results.push((gpu_info.name.clone(), false, 0));
-                all_passed = false;
-                println!();
+                devices.push(
+                    DeviceReport::failed(
+                        DeviceKind::Gpu,
+                        &gpu_info.name,
+                        gpu_info.to_string(),
+                        e.to_string(),
+                    )
+                    .with_index(gpu_info.index),
+                );
+                if text {
+                    println!();
+                }
                 continue;
             }
         };
@@ -500,20 +1531,30 @@ fn run_all_gpus_test(args: &Args, should_stop: Arc<AtomicBool>) {
             Ok(t) => t,
             Err(e) => {
                 eprintln!("Error creating tester for GPU {}: {}", gpu_info.index, e);
-                gpu_results.push((gpu_info.name.clone(), false, 0));
-                all_passed = false;
-                println!();
+                devices.push(
+                    DeviceReport::failed(
+                        DeviceKind::Gpu,
+                        &gpu_info.name,
+                        gpu_info.to_string(),
+                        e.to_string(),
+                    )
+                    .with_index(gpu_info.index),
+                );
+                if text {
+                    println!();
+                }
                 continue;
             }
         };
 
         let config = TestConfig {
             memory_mb: args.memory_mb,
-            patterns: patterns::TestPattern::all_patterns(),
+            patterns: patterns.to_vec(),
             continuous: false, // Single pass per GPU in all mode
             timeout: args.duration.as_ref().and_then(|s| parse_duration(s)),
             threads: None,
             verbose: args.verbose,
+            seed,
         };
 
         let stats = Arc::new(TestStats::new());
@@ -521,76 +1562,78 @@ fn run_all_gpus_test(args: &Args, should_stop: Arc<AtomicBool>) {
 
         // Check if we should stop before testing this GPU
         if should_stop.load(Ordering::Relaxed) {
-            println!("Interrupted, skipping remaining GPUs...");
+            if text {
+                println!("Interrupted, skipping remaining GPUs...");
+            }
             break;
         }
 
         let results = tester.run_tests(&config, Arc::clone(&stats), Arc::clone(&should_stop));
+        let duration = gpu_start.elapsed();
+        let device = gpu_device_report(&tester, gpu_info, &stats, &results, duration);
 
-        let total_errors: u64 = match &results {
-            Ok(r) => r.iter().map(|r| r.errors_found).sum(),
-            Err(_) => 0,
-        };
-
-        let duration = gpu_start.elapsed().as_secs_f64();
-
-        match results {
-            Ok(_) if total_errors == 0 => {
-                println!(
-                    "PASSED: {} MB tested, {:.1}s",
-                    stats.get_bytes() / (1024 * 1024),
-                    duration
-                );
-                gpu_results.push((gpu_info.name.clone(), true, 0));
+        let total_errors: u64 = device.results.iter().map(|r| r.errors_found).sum();
+        match &device.error {
+            None if total_errors == 0 => {
+                if text {
+                    println!(
+                        "PASSED: {} MB tested, {:.1}s",
+                        stats.get_bytes() / (1024 * 1024),
+                        duration.as_secs_f64()
+                    );
+                }
             }
-            Ok(_) => {
-                println!(
-                    "FAILED: {} errors, {} MB tested, {:.1}s",
-                    total_errors,
-                    stats.get_bytes() / (1024 * 1024),
-                    duration
-                );
-                gpu_results.push((gpu_info.name.clone(), false, total_errors));
-                all_passed = false;
+            None => {
+                if text {
+                    println!(
+                        "FAILED: {} errors, {} MB tested, {:.1}s",
+                        total_errors,
+                        stats.get_bytes() / (1024 * 1024),
+                        duration.as_secs_f64()
+                    );
+                }
             }
-            Err(e) => {
+            Some(e) => {
                 eprintln!("ERROR: {}", e);
-                gpu_results.push((gpu_info.name.clone(), false, 0));
-                all_passed = false;
             }
         }
-        println!();
-    }
-
-    // Print summary
-    println!("====================");
-    println!("Multi-GPU Test Summary");
-    println!("====================");
-    for (name, passed, errors) in &gpu_results {
-        let status = if *passed {
-            "PASSED"
-        } else if *errors > 0 {
-            "FAILED"
-        } else {
-            "ERROR"
-        };
-        println!("  {} - {}", name, status);
+        devices.push(device);
+        if text {
+            println!();
+        }
     }
-    println!();
-    println!(
-        "Total duration: {:.1}s",
-        overall_start.elapsed().as_secs_f64()
-    );
 
-    if all_passed {
+    if text {
+        // Print summary
+        println!("====================");
+        println!("Multi-GPU Test Summary");
+        println!("====================");
+        for device in &devices {
+            let status = if device.passed {
+                "PASSED"
+            } else if device.error.is_none() {
+                "FAILED"
+            } else {
+                "ERROR"
+            };
+            println!("  {} - {}", device.name, status);
+        }
         println!();
-        println!("SUCCESS: All GPUs passed!");
-        std::process::exit(0);
-    } else {
+        println!(
+            "Total duration: {:.1}s",
+            overall_start.elapsed().as_secs_f64()
+        );
+        println!("Seed: {}", seed);
+
         println!();
-        println!("FAILURE: Some GPUs failed!");
-        std::process::exit(1);
+        if devices.iter().all(|d| d.passed) {
+            println!("SUCCESS: All GPUs passed!");
+        } else {
+            println!("FAILURE: Some GPUs failed!");
+        }
     }
+
+    devices
 }
 
 #[cfg(test)]
@@ -631,6 +1674,230 @@ mod tests {
         assert!(parse_duration("").is_none());
     }
 
+    #[test]
+    fn test_parse_patterns() {
+        let args = Args::parse_from(["ferritest", "--patterns", "walking-ones,random"]);
+        assert_eq!(
+            args.patterns,
+            vec![TestPattern::WalkingOnes, TestPattern::RandomPattern]
+        );
+        assert!(args.exclude_patterns.is_empty());
+    }
+
+    #[test]
+    fn test_parse_moving_inversions() {
+        let args = Args::parse_from([
+            "ferritest",
+            "--patterns",
+            "moving-inversions-32,moving-inversions-64",
+        ]);
+        assert_eq!(
+            args.patterns,
+            vec![
+                TestPattern::MovingInversions32,
+                TestPattern::MovingInversions64
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_modulo_n() {
+        let args = Args::parse_from(["ferritest", "--patterns", "modulo-n"]);
+        assert_eq!(args.modulo_n, modulo::DEFAULT_N);
+        let config = args.report_config(&args.patterns, &[]);
+        assert_eq!(config.modulo_n, Some(20));
+        assert_eq!(args.report_config(&[], &[]).modulo_n, None);
+
+        let args = Args::parse_from(["ferritest", "--patterns", "modulo-n", "--modulo-n", "7"]);
+        assert_eq!(args.modulo_n, 7);
+        assert!(Args::try_parse_from(["ferritest", "--modulo-n", "1"]).is_err());
+        assert!(Args::try_parse_from(["ferritest", "--modulo-n", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_exclude_patterns() {
+        let args = Args::parse_from(["ferritest", "--exclude-patterns", "sequential,all-ones"]);
+        assert_eq!(
+            args.exclude_patterns,
+            vec![TestPattern::Sequential, TestPattern::AllOnes]
+        );
+        let selected = TestPattern::select(&args.patterns, &args.exclude_patterns, false).unwrap();
+        assert_eq!(selected.len(), 6);
+    }
+
+    #[test]
+    fn test_runs_other_tests() {
+        assert!(!Args::parse_from(["ferritest"]).runs_other_tests());
+        assert!(Args::parse_from(["ferritest", "--march", "mats+"]).runs_other_tests());
+        assert!(Args::parse_from(["ferritest", "--address-lines"]).runs_other_tests());
+        assert!(Args::parse_from(["ferritest", "--retention", "1s"]).runs_other_tests());
+        // GPUs run patterns only
+        assert!(!Args::parse_from(["ferritest", "--gpu", "--block-move"]).runs_other_tests());
+        assert!(
+            Args::parse_from(["ferritest", "--all-devices", "--block-move"]).runs_other_tests()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_pattern() {
+        assert!(Args::try_parse_from(["ferritest", "--patterns", "diagonal"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_error_limits() {
+        let args = Args::parse_from(["ferritest"]);
+        assert!(!args.keep_going);
+        assert!(args.max_errors.is_none());
+
+        let args = Args::parse_from(["ferritest", "--keep-going", "--max-errors", "50"]);
+        assert!(args.keep_going);
+        assert_eq!(args.max_errors, Some(50));
+    }
+
+    #[test]
+    fn test_parse_start_pass() {
+        assert_eq!(Args::parse_from(["ferritest"]).start_pass, 0);
+        let args = Args::parse_from(["ferritest", "--seed", "7", "--start-pass", "4"]);
+        assert_eq!(args.start_pass, 4);
+        assert!(Args::try_parse_from(["ferritest", "--start-pass", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        let args = Args::parse_from(["ferritest", "--seed", "12345"]);
+        assert_eq!(args.seed, Some(12345));
+
+        let args = Args::parse_from(["ferritest"]);
+        assert!(args.seed.is_none());
+    }
+
+    #[test]
+    fn test_parse_march_tests() {
+        let args = Args::parse_from(["ferritest", "--march", "mats+,march-c-"]);
+        let tests = parse_march_tests(&args.march).unwrap();
+        assert_eq!(
+            tests,
+            vec![MarchTest::mats_plus(), MarchTest::march_c_minus()]
+        );
+
+        let tests = parse_march_tests(&["all".to_string()]).unwrap();
+        assert_eq!(tests.len(), 4);
+
+        assert!(parse_march_tests(&[]).unwrap().is_empty());
+        assert!(parse_march_tests(&["march-z".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_parse_badram_command() {
+        let args = Args::parse_from(["ferritest", "-m", "4096", "badram"]);
+        assert_eq!(args.memory_mb, 4096);
+        assert_eq!(
+            args.command,
+            Some(Command::Badram {
+                granularity: badram::DEFAULT_GRANULARITY
+            })
+        );
+
+        let args = Args::parse_from(["ferritest", "badram", "--granularity", "2M"]);
+        assert_eq!(
+            args.command,
+            Some(Command::Badram {
+                granularity: 2 << 20
+            })
+        );
+
+        assert!(Args::try_parse_from(["ferritest", "badram", "--granularity", "3K"]).is_err());
+        assert!(Args::parse_from(["ferritest"]).command.is_none());
+    }
+
+    #[test]
+    fn test_parse_report_format() {
+        let args = Args::parse_from(["ferritest"]);
+        assert!(args.format.is_none());
+        assert!(args.prints_text());
+
+        let args = Args::parse_from(["ferritest", "--format", "json"]);
+        assert_eq!(args.format, Some(ReportFormat::Json));
+        assert!(!args.prints_text());
+
+        let args = Args::parse_from([
+            "ferritest",
+            "--format",
+            "json",
+            "--report-file",
+            "report.json",
+        ]);
+        assert_eq!(args.report_file, Some(PathBuf::from("report.json")));
+        assert!(args.prints_text());
+
+        assert!(Args::try_parse_from(["ferritest", "--report-file", "report.json"]).is_err());
+        let args = Args::parse_from(["ferritest", "--format", "junit"]);
+        assert_eq!(args.format, Some(ReportFormat::Junit));
+        let args = Args::parse_from(["ferritest", "--format", "tap"]);
+        assert_eq!(args.format, Some(ReportFormat::Tap));
+
+        assert!(Args::try_parse_from(["ferritest", "--format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn test_report_config() {
+        let args = Args::parse_from(["ferritest", "-m", "512", "-d", "2m", "--max-errors", "5"]);
+        let config = args.report_config(&[TestPattern::AllOnes], &[MarchTest::mats_plus()]);
+        assert_eq!(config.memory_mb, 512);
+        assert_eq!(config.duration_ms, Some(120_000));
+        assert_eq!(config.patterns, vec![TestPattern::AllOnes]);
+        assert_eq!(config.march_tests, vec!["MATS+".to_string()]);
+        assert_eq!(config.max_errors, Some(5));
+        assert!(!config.address_lines);
+        assert_eq!(config.retention_ms, None);
+        assert!(!config.block_move);
+
+        let args = Args::parse_from(["ferritest", "--address-lines"]);
+        assert!(args.report_config(&[], &[]).address_lines);
+
+        let args = Args::parse_from(["ferritest", "--block-move"]);
+        assert!(args.report_config(&[], &[]).block_move);
+    }
+
+    #[test]
+    fn test_parse_retention() {
+        let args = Args::parse_from(["ferritest", "--retention", "90s", "--retention-ramp", "30s"]);
+        assert_eq!(args.retention, Some(Duration::from_secs(90)));
+        assert_eq!(args.retention_ramp, Some(Duration::from_secs(30)));
+        let config = args.report_config(&[], &[]);
+        assert_eq!(config.retention_ms, Some(90_000));
+        assert_eq!(config.retention_ramp_ms, Some(30_000));
+
+        assert!(Args::try_parse_from(["ferritest", "--retention", "soon"]).is_err());
+        assert!(Args::try_parse_from(["ferritest", "--retention-ramp", "30s"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rowhammer() {
+        let args = Args::parse_from(["ferritest", "--rowhammer", "60s"]);
+        assert_eq!(args.rowhammer, Some(Duration::from_secs(60)));
+        assert_eq!(args.rowhammer_pairs, rowhammer::DEFAULT_PAIRS);
+        let config = args.report_config(&[], &[]);
+        assert_eq!(config.rowhammer_ms, Some(60_000));
+        assert_eq!(config.rowhammer_pairs, Some(rowhammer::DEFAULT_PAIRS));
+
+        let args = Args::parse_from(["ferritest", "--rowhammer", "5m", "--rowhammer-pairs", "8"]);
+        assert_eq!(args.rowhammer_pairs, 8);
+        assert_eq!(args.report_config(&[], &[]).rowhammer_pairs, Some(8));
+
+        let args = Args::parse_from(["ferritest"]);
+        assert_eq!(args.report_config(&[], &[]).rowhammer_ms, None);
+        assert!(Args::try_parse_from(["ferritest", "--rowhammer-pairs", "8"]).is_err());
+        assert!(Args::try_parse_from([
+            "ferritest",
+            "--rowhammer",
+            "60s",
+            "--rowhammer-pairs",
+            "0"
+        ])
+        .is_err());
+    }
+
     #[test]
     fn test_parse_gpu_flag() {
         let args = Args::parse_from(["ferritest", "--gpu"]);
@@ -651,6 +1918,73 @@ mod tests {
         assert_eq!(args.gpu_index, Some("all".to_string()));
     }
 
+    #[test]
+    fn test_parse_concurrent_devices() {
+        let args = Args::parse_from(["ferritest"]);
+        assert!(!args.concurrent());
+
+        let args = Args::parse_from(["ferritest", "--cpu"]);
+        assert!(!args.concurrent());
+
+        let args = Args::parse_from(["ferritest", "--cpu", "--gpu", "--gpu-index", "1"]);
+        assert!(args.concurrent());
+
+        let args = Args::parse_from(["ferritest", "--all-devices"]);
+        assert!(args.all_devices);
+        assert!(args.concurrent());
+
+        assert!(Args::try_parse_from(["ferritest", "--all-devices", "--gpu-index", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_cpus() {
+        let args = Args::parse_from(["ferritest"]);
+        assert_eq!(parse_cpus(&args), Ok(None));
+
+        if !affinity::supported() {
+            return;
+        }
+        let args = Args::parse_from(["ferritest", "--pin-threads"]);
+        assert_eq!(parse_cpus(&args), Ok(Some(Vec::new())));
+
+        let first = affinity::available_cpus()[0];
+        let list = first.to_string();
+        let args = Args::parse_from(["ferritest", "--cpus", list.as_str()]);
+        assert_eq!(parse_cpus(&args), Ok(Some(vec![first])));
+
+        let args = Args::parse_from(["ferritest", "--cpus", "100000"]);
+        assert!(parse_cpus(&args).is_err());
+        let args = Args::parse_from(["ferritest", "--cpus", "3-1"]);
+        assert!(parse_cpus(&args).is_err());
+    }
+
+    #[test]
+    fn test_parse_numa_nodes() {
+        let args = Args::parse_from(["ferritest"]);
+        assert_eq!(parse_numa_nodes(&args), Ok(Vec::new()));
+
+        let nodes = numa::nodes();
+        if !numa::supported() || nodes.is_empty() {
+            return;
+        }
+        let first = nodes[0].id.to_string();
+        let args = Args::parse_from(["ferritest", "--numa-node", first.as_str()]);
+        assert_eq!(parse_numa_nodes(&args), Ok(vec![nodes[0].id]));
+
+        let args = Args::parse_from(["ferritest", "--numa-node", "100000"]);
+        assert!(parse_numa_nodes(&args).is_err());
+
+        let with_cpus = nodes.iter().filter(|n| !n.cpus.is_empty()).count();
+        let args = Args::parse_from(["ferritest", "--cross-node"]);
+        assert_eq!(parse_numa_nodes(&args).is_ok(), with_cpus >= 2);
+    }
+
+    #[test]
+    fn test_device_label() {
+        assert_eq!(device_label("CPU/RAM", None), "CPU/RAM");
+        assert_eq!(device_label("Test GPU", Some(1)), "GPU 1: Test GPU");
+    }
+
     #[test]
     fn test_parse_list_gpus() {
         let args = Args::parse_from(["ferritest", "--list-gpus"]);
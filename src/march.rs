@@ -0,0 +1,565 @@
+//! March test algorithms for memory testing.
+//!
+//! March tests walk through memory in a fixed address order, applying a
+//! short sequence of reads and writes to every word before moving on to the
+//! next one. Unlike the fill-then-verify patterns in the patterns module,
+//! this interleaving catches coupling faults, transition faults and address
+//! decoder faults.
+//!
+//! A March test is written as a list of elements separated by `;`, where each
+//! element is an address order followed by its operations:
+//!
+//! ```text
+//! ⇕(w0); ⇑(r0,w1); ⇓(r1,w0)
+//! ```
+//!
+//! `⇑` (or `up`) walks ascending addresses, `⇓` (or `down`) walks descending
+//! addresses and `⇕` (or `any`) may use either order. `r0`/`r1` read and
+//! expect all-zeros/all-ones words, `w0`/`w1` write them.
+
+use crate::cache;
+use std::fmt;
+
+/// Address order of a March element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarchDirection {
+    /// Ascending addresses (`⇑`).
+    Up,
+    /// Descending addresses (`⇓`).
+    Down,
+    /// Either order (`⇕`); executed ascending.
+    Any,
+}
+
+impl MarchDirection {
+    /// Returns the arrow symbol used in March notation.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Up => "⇑",
+            Self::Down => "⇓",
+            Self::Any => "⇕",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "⇑" | "up" => Some(Self::Up),
+            "⇓" | "down" => Some(Self::Down),
+            "⇕" | "any" => Some(Self::Any),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MarchDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// A single read or write operation within a March element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarchOp {
+    /// Read and expect all zeros (`false`) or all ones (`true`).
+    Read(bool),
+    /// Write all zeros (`false`) or all ones (`true`).
+    Write(bool),
+}
+
+impl MarchOp {
+    /// Returns the word value this operation reads or writes.
+    pub fn value(&self) -> u64 {
+        match self {
+            Self::Read(one) | Self::Write(one) => {
+                if *one {
+                    u64::MAX
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "r0" => Some(Self::Read(false)),
+            "r1" => Some(Self::Read(true)),
+            "w0" => Some(Self::Write(false)),
+            "w1" => Some(Self::Write(true)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MarchOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(one) => write!(f, "r{}", u8::from(*one)),
+            Self::Write(one) => write!(f, "w{}", u8::from(*one)),
+        }
+    }
+}
+
+/// One March element: an address order and the operations applied to each word.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MarchElement {
+    /// Order in which addresses are visited.
+    pub direction: MarchDirection,
+    /// Operations applied to every word, in sequence.
+    pub ops: Vec<MarchOp>,
+}
+
+impl fmt::Display for MarchElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ops: Vec<String> = self.ops.iter().map(|op| op.to_string()).collect();
+        write!(f, "{}({})", self.direction, ops.join(","))
+    }
+}
+
+/// Details of the first read that returned an unexpected value during a March test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarchFailure {
+    /// Name of the March test that failed.
+    pub test_name: String,
+    /// Index of the failing element within the test (M0, M1, ...).
+    pub element_index: usize,
+    /// The failing element.
+    pub element: MarchElement,
+    /// Index of the failing read within the element's operations.
+    pub op_index: usize,
+    /// Word index within the block where the failure was detected.
+    pub index: usize,
+    /// Value the read expected.
+    pub expected: u64,
+    /// Value actually read.
+    pub actual: u64,
+}
+
+impl MarchFailure {
+    /// Returns the direction of the element that caught the fault.
+    pub fn direction(&self) -> MarchDirection {
+        self.element.direction
+    }
+}
+
+impl fmt::Display for MarchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} M{} {} op {} ({}) at word 0x{:X}: expected 0x{:016X}, got 0x{:016X}",
+            self.test_name,
+            self.element_index,
+            self.element,
+            self.op_index,
+            self.element.ops[self.op_index],
+            self.index,
+            self.expected,
+            self.actual
+        )
+    }
+}
+
+/// Word-addressable memory a March test can run against.
+trait MarchCells {
+    fn len(&self) -> usize;
+    fn read(&self, index: usize) -> u64;
+    fn write(&mut self, index: usize, value: u64);
+
+    /// Pushes the cells out of the CPU caches between elements.
+    fn flush(&self) {}
+}
+
+impl MarchCells for [u64] {
+    fn len(&self) -> usize {
+        <[u64]>::len(self)
+    }
+
+    fn read(&self, index: usize) -> u64 {
+        cache::read_word(&self[index])
+    }
+
+    fn write(&mut self, index: usize, value: u64) {
+        cache::write_word(&mut self[index], value);
+    }
+
+    fn flush(&self) {
+        cache::flush(self);
+    }
+}
+
+/// A complete March test: a named sequence of March elements.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MarchTest {
+    name: String,
+    elements: Vec<MarchElement>,
+}
+
+impl MarchTest {
+    /// Parses a March test from its textual description.
+    ///
+    /// # Arguments
+    /// * `name` - Name reported when the test fails
+    /// * `description` - Elements such as `⇕(w0); ⇑(r0,w1); ⇓(r1,w0)`, optionally
+    ///   wrapped in `{}`
+    pub fn parse(name: &str, description: &str) -> Result<Self, String> {
+        let body = description.trim();
+        let body = body
+            .strip_prefix('{')
+            .and_then(|b| b.strip_suffix('}'))
+            .unwrap_or(body);
+
+        let mut elements = Vec::new();
+        for element in body.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let (direction, rest) = element
+                .split_once('(')
+                .ok_or_else(|| format!("Missing '(' in March element '{}'", element))?;
+            let direction = MarchDirection::parse(direction).ok_or_else(|| {
+                format!(
+                    "Invalid direction '{}' in March element '{}'. Use ⇑/up, ⇓/down or ⇕/any.",
+                    direction.trim(),
+                    element
+                )
+            })?;
+            let ops = rest
+                .strip_suffix(')')
+                .ok_or_else(|| format!("Missing ')' in March element '{}'", element))?;
+            let ops = ops
+                .split(',')
+                .map(|op| {
+                    MarchOp::parse(op).ok_or_else(|| {
+                        format!(
+                            "Invalid operation '{}' in March element '{}'. Use r0, r1, w0 or w1.",
+                            op.trim(),
+                            element
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            elements.push(MarchElement { direction, ops });
+        }
+
+        if elements.is_empty() {
+            return Err(format!("March test '{}' has no elements", name));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            elements,
+        })
+    }
+
+    /// MATS+: `⇕(w0); ⇑(r0,w1); ⇓(r1,w0)` (5n).
+    pub fn mats_plus() -> Self {
+        Self::builtin("MATS+", "⇕(w0); ⇑(r0,w1); ⇓(r1,w0)")
+    }
+
+    /// March C-: `⇕(w0); ⇑(r0,w1); ⇑(r1,w0); ⇓(r0,w1); ⇓(r1,w0); ⇕(r0)` (10n).
+    pub fn march_c_minus() -> Self {
+        Self::builtin(
+            "March C-",
+            "⇕(w0); ⇑(r0,w1); ⇑(r1,w0); ⇓(r0,w1); ⇓(r1,w0); ⇕(r0)",
+        )
+    }
+
+    /// March B: `⇕(w0); ⇑(r0,w1,r1,w0,r0,w1); ⇑(r1,w0,w1); ⇓(r1,w0,w1,w0); ⇓(r0,w1,w0)` (17n).
+    pub fn march_b() -> Self {
+        Self::builtin(
+            "March B",
+            "⇕(w0); ⇑(r0,w1,r1,w0,r0,w1); ⇑(r1,w0,w1); ⇓(r1,w0,w1,w0); ⇓(r0,w1,w0)",
+        )
+    }
+
+    /// March SS: `⇕(w0); ⇑(r0,r0,w0,r0,w1); ⇑(r1,r1,w1,r1,w0); ⇓(r0,r0,w0,r0,w1);
+    /// ⇓(r1,r1,w1,r1,w0); ⇕(r0)` (22n).
+    pub fn march_ss() -> Self {
+        Self::builtin(
+            "March SS",
+            "⇕(w0); ⇑(r0,r0,w0,r0,w1); ⇑(r1,r1,w1,r1,w0); ⇓(r0,r0,w0,r0,w1); ⇓(r1,r1,w1,r1,w0); ⇕(r0)",
+        )
+    }
+
+    /// Returns all built-in March tests.
+    pub fn all_builtin() -> Vec<Self> {
+        vec![
+            Self::mats_plus(),
+            Self::march_c_minus(),
+            Self::march_b(),
+            Self::march_ss(),
+        ]
+    }
+
+    /// Looks up a built-in March test by name (e.g. `mats+`, `march-c-`, `March B`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        let key = name.trim().to_lowercase().replace([' ', '_'], "-");
+        match key.as_str() {
+            "mats+" | "mats-plus" => Some(Self::mats_plus()),
+            "march-c-" | "march-c-minus" => Some(Self::march_c_minus()),
+            "march-b" => Some(Self::march_b()),
+            "march-ss" => Some(Self::march_ss()),
+            _ => None,
+        }
+    }
+
+    fn builtin(name: &str, description: &str) -> Self {
+        Self::parse(name, description).expect("built-in March test description is valid")
+    }
+
+    /// Returns the name of this March test.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the elements of this March test.
+    pub fn elements(&self) -> &[MarchElement] {
+        &self.elements
+    }
+
+    /// Returns the number of operations applied to each word (the `k` in `kn`).
+    pub fn ops_per_word(&self) -> usize {
+        self.elements.iter().map(|e| e.ops.len()).sum()
+    }
+
+    /// Runs this March test over a memory block.
+    ///
+    /// The block is flushed from the CPU caches between elements, so each
+    /// element reads what the previous one left in DRAM.
+    ///
+    /// # Returns
+    /// * `Ok(())` if every read returned its expected value
+    /// * `Err(MarchFailure)` describing the first failing read
+    pub fn run(&self, block: &mut [u64]) -> Result<(), MarchFailure> {
+        self.run_cells(block)
+    }
+
+    fn run_cells<C: MarchCells + ?Sized>(&self, cells: &mut C) -> Result<(), MarchFailure> {
+        let len = cells.len();
+        for (element_index, element) in self.elements.iter().enumerate() {
+            // Each element reads what the previous one wrote, from DRAM
+            if element_index > 0 {
+                cells.flush();
+            }
+            let ascending = element.direction != MarchDirection::Down;
+            for step in 0..len {
+                let index = if ascending { step } else { len - 1 - step };
+                for (op_index, op) in element.ops.iter().enumerate() {
+                    match op {
+                        MarchOp::Read(_) => {
+                            let actual = cells.read(index);
+                            if actual != op.value() {
+                                return Err(MarchFailure {
+                                    test_name: self.name.clone(),
+                                    element_index,
+                                    element: element.clone(),
+                                    op_index,
+                                    index,
+                                    expected: op.value(),
+                                    actual,
+                                });
+                            }
+                        }
+                        MarchOp::Write(_) => cells.write(index, op.value()),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for MarchTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements: Vec<String> = self.elements.iter().map(|e| e.to_string()).collect();
+        write!(f, "{} {{{}}}", self.name, elements.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_BLOCK_SIZE: usize = 1024;
+
+    /// Memory with one bit stuck at a fixed value in one word.
+    struct StuckAtCells {
+        cells: Vec<u64>,
+        index: usize,
+        bit: u32,
+        value: bool,
+    }
+
+    impl MarchCells for StuckAtCells {
+        fn len(&self) -> usize {
+            self.cells.len()
+        }
+
+        fn read(&self, index: usize) -> u64 {
+            self.cells[index]
+        }
+
+        fn write(&mut self, index: usize, value: u64) {
+            let mut value = value;
+            if index == self.index {
+                if self.value {
+                    value |= 1 << self.bit;
+                } else {
+                    value &= !(1 << self.bit);
+                }
+            }
+            self.cells[index] = value;
+        }
+    }
+
+    /// Memory where writing one word also overwrites another (an idempotent coupling fault).
+    struct CoupledCells {
+        cells: Vec<u64>,
+        aggressor: usize,
+        victim: usize,
+    }
+
+    impl MarchCells for CoupledCells {
+        fn len(&self) -> usize {
+            self.cells.len()
+        }
+
+        fn read(&self, index: usize) -> u64 {
+            self.cells[index]
+        }
+
+        fn write(&mut self, index: usize, value: u64) {
+            if index == self.aggressor && self.cells[index] != value {
+                self.cells[self.victim] = value;
+            }
+            self.cells[index] = value;
+        }
+    }
+
+    #[test]
+    fn test_builtin_ops_per_word() {
+        assert_eq!(MarchTest::mats_plus().ops_per_word(), 5);
+        assert_eq!(MarchTest::march_c_minus().ops_per_word(), 10);
+        assert_eq!(MarchTest::march_b().ops_per_word(), 17);
+        assert_eq!(MarchTest::march_ss().ops_per_word(), 22);
+    }
+
+    #[test]
+    fn test_parse_ascii_notation() {
+        let test = MarchTest::parse("ascii", "{any(w0); up(r0,w1); down(r1,w0)}").unwrap();
+        assert_eq!(
+            test,
+            MarchTest::parse("ascii", "⇕(w0); ⇑(r0,w1); ⇓(r1,w0)").unwrap()
+        );
+        assert_eq!(test.elements()[2].direction, MarchDirection::Down);
+        assert_eq!(
+            test.elements()[1].ops,
+            vec![MarchOp::Read(false), MarchOp::Write(true)]
+        );
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for test in MarchTest::all_builtin() {
+            let text = test.to_string();
+            let description = text.strip_prefix(test.name()).unwrap();
+            assert_eq!(MarchTest::parse(test.name(), description).unwrap(), test);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(MarchTest::parse("empty", "").is_err());
+        assert!(MarchTest::parse("dir", "sideways(w0)").is_err());
+        assert!(MarchTest::parse("op", "⇑(w2)").is_err());
+        assert!(MarchTest::parse("paren", "⇑(w0").is_err());
+        assert!(MarchTest::parse("open", "⇑ w0").is_err());
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(MarchTest::from_name("mats+"), Some(MarchTest::mats_plus()));
+        assert_eq!(
+            MarchTest::from_name("March C-"),
+            Some(MarchTest::march_c_minus())
+        );
+        assert_eq!(MarchTest::from_name("march_b"), Some(MarchTest::march_b()));
+        assert_eq!(
+            MarchTest::from_name("MARCH-SS"),
+            Some(MarchTest::march_ss())
+        );
+        assert!(MarchTest::from_name("march-z").is_none());
+    }
+
+    #[test]
+    fn test_builtin_pass_on_good_memory() {
+        for test in MarchTest::all_builtin() {
+            let mut block = vec![0x1234u64; TEST_BLOCK_SIZE];
+            assert!(test.run(&mut block).is_ok(), "{} failed", test.name());
+        }
+    }
+
+    #[test]
+    fn test_stuck_at_one_detected() {
+        let mut cells = StuckAtCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            index: 100,
+            bit: 17,
+            value: true,
+        };
+
+        let failure = MarchTest::mats_plus().run_cells(&mut cells).unwrap_err();
+        assert_eq!(failure.element_index, 1);
+        assert_eq!(failure.direction(), MarchDirection::Up);
+        assert_eq!(failure.index, 100);
+        assert_eq!(failure.expected, 0);
+        assert_eq!(failure.actual, 1 << 17);
+    }
+
+    #[test]
+    fn test_stuck_at_zero_detected() {
+        let mut cells = StuckAtCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            index: 5,
+            bit: 0,
+            value: false,
+        };
+
+        let failure = MarchTest::march_c_minus()
+            .run_cells(&mut cells)
+            .unwrap_err();
+        assert_eq!(failure.element_index, 2);
+        assert_eq!(failure.expected, u64::MAX);
+        assert_eq!(failure.actual, u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_coupling_fault_caught_by_ascending_element() {
+        // A coupling fault is caught by whichever walk visits the aggressor
+        // before the victim, so the failing direction depends on their order.
+        let mut cells = CoupledCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            aggressor: 10,
+            victim: 20,
+        };
+
+        let failure = MarchTest::march_c_minus()
+            .run_cells(&mut cells)
+            .unwrap_err();
+        assert_eq!(failure.index, 20);
+        assert_eq!(failure.direction(), MarchDirection::Up);
+    }
+
+    #[test]
+    fn test_coupling_fault_caught_by_descending_element() {
+        let mut cells = CoupledCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            aggressor: 20,
+            victim: 10,
+        };
+
+        let failure = MarchTest::march_c_minus()
+            .run_cells(&mut cells)
+            .unwrap_err();
+        assert_eq!(failure.index, 10);
+        assert_eq!(failure.direction(), MarchDirection::Down);
+        assert!(failure.to_string().contains("March C- M3 ⇓(r0,w1)"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Locking test memory into RAM and checking that it stayed there.
+//!
+//! On a loaded machine the kernel may swap out parts of the test blocks, and
+//! a pattern read back from swap says nothing about the RAM. Blocks are locked
+//! with `mlock`, which is capped by `RLIMIT_MEMLOCK` for unprivileged users,
+//! and `mincore` reports how much of them was actually resident.
+
+/// Returns whether memory can be locked and its residency queried on this platform.
+pub fn supported() -> bool {
+    cfg!(unix)
+}
+
+/// Locks the pages spanning `memory` into RAM, faulting them in.
+#[cfg(unix)]
+pub fn lock(memory: &[u64]) -> Result<(), String> {
+    // SAFETY: the range is exactly `memory`, which is borrowed for the call;
+    // mlock only changes whether its pages may be swapped out.
+    let ret = unsafe {
+        libc::mlock(
+            memory.as_ptr() as *const libc::c_void,
+            std::mem::size_of_val(memory),
+        )
+    };
+    if ret != 0 {
+        return Err(format!(
+            "Failed to lock {} bytes: {}",
+            std::mem::size_of_val(memory),
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// Locks the pages spanning `memory` into RAM, faulting them in.
+#[cfg(not(unix))]
+pub fn lock(_memory: &[u64]) -> Result<(), String> {
+    Err("Locking memory is not supported on this platform".to_string())
+}
+
+/// Unlocks the pages spanning `memory`, letting them be swapped out again.
+#[cfg(unix)]
+pub fn unlock(memory: &[u64]) {
+    // SAFETY: the range is exactly `memory`, which is borrowed for the call.
+    unsafe {
+        libc::munlock(
+            memory.as_ptr() as *const libc::c_void,
+            std::mem::size_of_val(memory),
+        );
+    }
+}
+
+/// Unlocks the pages spanning `memory`, letting them be swapped out again.
+#[cfg(not(unix))]
+pub fn unlock(_memory: &[u64]) {}
+
+/// Returns the `RLIMIT_MEMLOCK` soft limit in bytes, or `None` if unlimited or unknown.
+#[cfg(unix)]
+pub fn limit() -> Option<u64> {
+    let mut rlimit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `rlimit` is a valid, writable rlimit struct.
+    let ret = unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut rlimit) };
+    if ret != 0 || rlimit.rlim_cur == libc::RLIM_INFINITY {
+        return None;
+    }
+    // rlim_t is not u64 on every platform
+    #[allow(clippy::unnecessary_cast)]
+    Some(rlimit.rlim_cur as u64)
+}
+
+/// Returns the `RLIMIT_MEMLOCK` soft limit in bytes, or `None` if unlimited or unknown.
+#[cfg(not(unix))]
+pub fn limit() -> Option<u64> {
+    None
+}
+
+/// Describes the lock limit for messages (e.g., "8 MB" or "unlimited").
+pub fn describe_limit() -> String {
+    match limit() {
+        Some(bytes) if bytes >= 1024 * 1024 => format!("{} MB", bytes / (1024 * 1024)),
+        Some(bytes) => format!("{} KB", bytes / 1024),
+        None if supported() => "unlimited".to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Returns how many bytes of `memory` are resident in RAM.
+#[cfg(unix)]
+pub fn resident_bytes(memory: &[u64]) -> Option<u64> {
+    if memory.is_empty() {
+        return Some(0);
+    }
+    // mincore needs a page-aligned start; widen the range to whole pages
+    let page_size = crate::pagemap::page_size();
+    let start = memory.as_ptr() as usize;
+    let end = start + std::mem::size_of_val(memory);
+    let aligned = start & !(page_size - 1);
+    let pages = (end - aligned).div_ceil(page_size);
+    let mut residency = vec![0u8; pages];
+
+    // SAFETY: the range covers only pages holding part of `memory`, and
+    // `residency` has one byte for each of them.
+    let ret = unsafe {
+        libc::mincore(
+            aligned as *mut libc::c_void,
+            end - aligned,
+            residency.as_mut_ptr() as _,
+        )
+    };
+    if ret != 0 {
+        return None;
+    }
+    let resident = residency
+        .iter()
+        .enumerate()
+        .filter(|(_, &flags)| flags & 1 != 0)
+        .map(|(page, _)| {
+            let page_start = aligned + page * page_size;
+            let overlap = (page_start + page_size).min(end) - page_start.max(start);
+            overlap as u64
+        })
+        .sum();
+    Some(resident)
+}
+
+/// Returns how many bytes of `memory` are resident in RAM.
+#[cfg(not(unix))]
+pub fn resident_bytes(_memory: &[u64]) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resident_bytes() {
+        let mut memory = vec![0u64; 1 << 16];
+        memory.fill(1);
+        assert_eq!(
+            resident_bytes(&memory),
+            Some(std::mem::size_of_val(memory.as_slice()) as u64)
+        );
+        assert_eq!(resident_bytes(&memory[3..5]), Some(16));
+        assert_eq!(resident_bytes(&[]), Some(0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lock() {
+        let memory = vec![0u64; 512];
+        // Small enough to fit under the default RLIMIT_MEMLOCK
+        assert!(lock(&memory).is_ok());
+        assert_eq!(resident_bytes(&memory), Some(4096));
+        unlock(&memory);
+    }
+
+    #[test]
+    fn test_describe_limit() {
+        let limit = describe_limit();
+        assert!(
+            limit.ends_with("MB")
+                || limit.ends_with("KB")
+                || limit == "unlimited"
+                || limit == "unknown",
+            "{}",
+            limit
+        );
+    }
+}
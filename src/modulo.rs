@@ -0,0 +1,198 @@
+//! Modulo-N test for faults hidden by caching and write buffering.
+//!
+//! A whole-block fill writes each word's neighbors right before or after
+//! the word itself, so the caches and write buffers merge the stores and a
+//! neighbor that disturbs the word has little chance to. The modulo-N test
+//! (memtest86's modulo-20 test) writes a pattern `P` to every Nth word
+//! only, overwrites every other word with `~P` several times, and then
+//! checks just the Nth words. It repeats for each offset phase `0..N` and
+//! with `P` and `~P` swapped, so every word is the one checked at some
+//! point.
+
+use crate::cache;
+use crate::error::ModuloPhase;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+/// Spacing used when none is configured, as in memtest86.
+pub const DEFAULT_N: usize = 20;
+
+/// Times the other words are overwritten with `~P` in each phase.
+const FILL_REPEATS: usize = 2;
+
+/// Returns the pattern `P` derived from `seed`.
+pub fn pattern(seed: u64) -> u64 {
+    rand::rngs::StdRng::seed_from_u64(seed).gen()
+}
+
+/// Returns the word at `index` of the first phase with the default
+/// spacing: `p` on every Nth word and `~p` on the others.
+pub fn first_phase(p: u64, index: usize) -> u64 {
+    if index.is_multiple_of(DEFAULT_N) {
+        p
+    } else {
+        !p
+    }
+}
+
+/// A checked word that did not hold `P` after its neighbors were rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuloFailure {
+    /// Word index within the block.
+    pub index: usize,
+    /// Value the word should have held.
+    pub expected: u64,
+    /// Value actually read.
+    pub actual: u64,
+    /// Offset phase that checked the word.
+    pub phase: ModuloPhase,
+}
+
+impl fmt::Display for ModuloFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at word 0x{:X}: expected 0x{:016X}, got 0x{:016X}",
+            self.phase, self.index, self.expected, self.actual
+        )
+    }
+}
+
+/// Word-addressable memory the modulo-N test can run against.
+trait ModuloCells {
+    fn len(&self) -> usize;
+    fn read(&self, index: usize) -> u64;
+    fn write(&mut self, index: usize, value: u64);
+
+    /// Pushes the cells out of the CPU caches before they are checked.
+    fn flush(&self) {}
+}
+
+impl ModuloCells for [u64] {
+    fn len(&self) -> usize {
+        <[u64]>::len(self)
+    }
+
+    fn read(&self, index: usize) -> u64 {
+        cache::read_word(&self[index])
+    }
+
+    fn write(&mut self, index: usize, value: u64) {
+        cache::write_word(&mut self[index], value);
+    }
+
+    fn flush(&self) {
+        cache::flush(self);
+    }
+}
+
+/// Runs the modulo-N test over a memory block with spacing `n` (at least
+/// 2) and pattern `p`.
+///
+/// The block is flushed from the CPU caches before each check, so the
+/// checked words are read from DRAM.
+///
+/// # Returns
+/// * `Ok(())` if every checked word still held its pattern
+/// * `Err(ModuloFailure)` describing the first one that did not
+pub fn run(block: &mut [u64], n: usize, p: u64) -> Result<(), ModuloFailure> {
+    run_cells(block, n, p)
+}
+
+fn run_cells<C: ModuloCells + ?Sized>(
+    cells: &mut C,
+    n: usize,
+    p: u64,
+) -> Result<(), ModuloFailure> {
+    let len = cells.len();
+    for value in [p, !p] {
+        for offset in 0..n {
+            for index in (offset..len).step_by(n) {
+                cells.write(index, value);
+            }
+            for _ in 0..FILL_REPEATS {
+                for index in (0..len).filter(|i| i % n != offset) {
+                    cells.write(index, !value);
+                }
+            }
+
+            cells.flush();
+            for index in (offset..len).step_by(n) {
+                let actual = cells.read(index);
+                if actual != value {
+                    return Err(ModuloFailure {
+                        index,
+                        expected: value,
+                        actual,
+                        phase: ModuloPhase { offset, n },
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_BLOCK_SIZE: usize = 1024;
+
+    /// Memory where writing one word also sets bits of the word after it.
+    struct DisturbedCells {
+        cells: Vec<u64>,
+        aggressor: usize,
+        mask: u64,
+    }
+
+    impl ModuloCells for DisturbedCells {
+        fn len(&self) -> usize {
+            self.cells.len()
+        }
+
+        fn read(&self, index: usize) -> u64 {
+            self.cells[index]
+        }
+
+        fn write(&mut self, index: usize, value: u64) {
+            self.cells[index] = value;
+            if index == self.aggressor {
+                self.cells[index + 1] |= self.mask;
+            }
+        }
+    }
+
+    #[test]
+    fn test_pattern_follows_seed() {
+        assert_eq!(pattern(7), pattern(7));
+        assert_ne!(pattern(7), pattern(8));
+    }
+
+    #[test]
+    fn test_passes_on_good_memory() {
+        let mut block = vec![0u64; TEST_BLOCK_SIZE];
+        assert!(run(&mut block, DEFAULT_N, 0x1234_5678_9ABC_DEF0).is_ok());
+        // The last phase checked offset 19 with ~P
+        assert_eq!(block[19], !0x1234_5678_9ABC_DEF0);
+        assert_eq!(block[20], 0x1234_5678_9ABC_DEF0);
+    }
+
+    #[test]
+    fn test_reports_offset_phase() {
+        let mut cells = DisturbedCells {
+            cells: vec![0; TEST_BLOCK_SIZE],
+            aggressor: 46,
+            mask: 1 << 9,
+        };
+
+        let failure = run_cells(&mut cells, DEFAULT_N, 0).unwrap_err();
+        // Word 47 is checked in phase 7, while word 46 is rewritten
+        assert_eq!(failure.index, 47);
+        assert_eq!(failure.phase, ModuloPhase { offset: 7, n: 20 });
+        assert_eq!(failure.expected ^ failure.actual, 1 << 9);
+        assert!(failure
+            .to_string()
+            .starts_with("offset phase 7 of 20 at word 0x2F"));
+    }
+}
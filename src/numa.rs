@@ -0,0 +1,269 @@
+//! NUMA topology discovery and memory placement.
+//!
+//! On multi-socket machines each node has its own memory controller, and
+//! memory on another node is reached over the socket interconnect. Binding
+//! test blocks to a node tests that node's DIMMs; reading them from another
+//! node's cores also exercises the interconnect. Topology is read from
+//! `/sys/devices/system/node` and memory is bound with `mbind`, so placement
+//! is only available on Linux.
+
+use crate::affinity;
+use std::path::Path;
+
+/// Where the kernel publishes the NUMA topology.
+pub const SYSFS_NODE_PATH: &str = "/sys/devices/system/node";
+
+/// One NUMA node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumaNode {
+    /// Node ID.
+    pub id: usize,
+    /// CPU cores local to this node.
+    pub cpus: Vec<usize>,
+    /// Memory attached to this node in megabytes (0 if unknown).
+    pub memory_mb: u64,
+}
+
+/// Returns whether memory can be bound to NUMA nodes on this platform.
+pub fn supported() -> bool {
+    cfg!(target_os = "linux")
+}
+
+/// Returns the NUMA nodes of this machine, ordered by ID.
+///
+/// Empty when the platform does not expose a topology.
+pub fn nodes() -> Vec<NumaNode> {
+    if supported() {
+        read_nodes(Path::new(SYSFS_NODE_PATH))
+    } else {
+        Vec::new()
+    }
+}
+
+/// Reads the `nodeN` directories under `root`, ordered by ID.
+pub fn read_nodes(root: &Path) -> Vec<NumaNode> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<NumaNode> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let id: usize = name.to_str()?.strip_prefix("node")?.parse().ok()?;
+            let path = entry.path();
+            let cpus = std::fs::read_to_string(path.join("cpulist"))
+                .ok()
+                .and_then(|list| match list.trim() {
+                    "" => Some(Vec::new()),
+                    list => affinity::parse_cpu_list(list).ok(),
+                })
+                .unwrap_or_default();
+            let memory_mb = std::fs::read_to_string(path.join("meminfo"))
+                .ok()
+                .and_then(|meminfo| parse_mem_total_kb(&meminfo))
+                .map_or(0, |kb| kb / 1024);
+            Some(NumaNode {
+                id,
+                cpus,
+                memory_mb,
+            })
+        })
+        .collect();
+    nodes.sort_by_key(|node| node.id);
+    nodes
+}
+
+/// Extracts `MemTotal` from a node's `meminfo` (`Node 0 MemTotal: 6158152 kB`).
+fn parse_mem_total_kb(meminfo: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        let (_, value) = line.split_once("MemTotal:")?;
+        value.trim().strip_suffix("kB")?.trim().parse().ok()
+    })
+}
+
+/// Parses a node list such as `0,1`, `0-3` or `all` against `nodes`.
+///
+/// `all` selects every node with memory attached.
+pub fn parse_node_list(s: &str, nodes: &[NumaNode]) -> Result<Vec<usize>, String> {
+    if s.eq_ignore_ascii_case("all") {
+        return Ok(nodes
+            .iter()
+            .filter(|node| node.memory_mb > 0)
+            .map(|node| node.id)
+            .collect());
+    }
+    let ids = affinity::parse_cpu_list(s)
+        .map_err(|_| format!("Invalid NUMA node list: '{}'. Use e.g. 0,1 or all.", s))?;
+    if let Some(id) = ids
+        .iter()
+        .find(|&&id| !nodes.iter().any(|node| node.id == id))
+    {
+        let known: Vec<usize> = nodes.iter().map(|node| node.id).collect();
+        return Err(format!(
+            "NUMA node {} not found; this machine has node {}",
+            id,
+            affinity::format_cpu_list(&known)
+        ));
+    }
+    Ok(ids)
+}
+
+/// Returns the next node after `node` that has CPUs, wrapping around.
+///
+/// `None` when no other node has CPUs to run on.
+pub fn next_node(nodes: &[NumaNode], node: usize) -> Option<usize> {
+    let with_cpus = || nodes.iter().filter(|n| !n.cpus.is_empty() && n.id != node);
+    with_cpus()
+        .find(|n| n.id > node)
+        .or_else(|| with_cpus().next())
+        .map(|n| n.id)
+}
+
+/// Highest node ID that fits in the node mask passed to `mbind`.
+#[cfg(target_os = "linux")]
+const MAX_NODES: usize = 1024;
+
+/// `mbind` flag that migrates pages already faulted in to the new node.
+#[cfg(target_os = "linux")]
+const MPOL_MF_MOVE: libc::c_uint = 1 << 1;
+
+/// Binds the pages spanning `memory` to `node`.
+///
+/// Pages not yet touched are allocated on `node`; pages already faulted in
+/// are moved there.
+#[cfg(target_os = "linux")]
+pub fn bind_memory(memory: &mut [u64], node: usize) -> Result<(), String> {
+    const BITS: usize = libc::c_ulong::BITS as usize;
+    if node >= MAX_NODES {
+        return Err(format!("NUMA node {} is out of range", node));
+    }
+    if memory.is_empty() {
+        return Ok(());
+    }
+    let mut mask = [0 as libc::c_ulong; MAX_NODES / BITS];
+    mask[node / BITS] |= 1 << (node % BITS);
+
+    // mbind needs a page-aligned start; widen the range to whole pages
+    let page_size = crate::pagemap::page_size();
+    let start = memory.as_ptr() as usize;
+    let aligned = start & !(page_size - 1);
+    let len = std::mem::size_of_val(memory) + (start - aligned);
+
+    // SAFETY: the range covers only pages that hold part of `memory`, which
+    // we borrow mutably; changing their placement does not change their
+    // contents. `mask` outlives the call and holds MAX_NODES bits; the kernel
+    // reads one bit less than `maxnode`.
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            aligned as *mut libc::c_void,
+            len as libc::c_ulong,
+            libc::MPOL_BIND,
+            mask.as_ptr(),
+            (MAX_NODES + 1) as libc::c_ulong,
+            MPOL_MF_MOVE,
+        )
+    };
+    if ret != 0 {
+        return Err(format!(
+            "Failed to bind memory to NUMA node {}: {}",
+            node,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// Binds the pages spanning `memory` to `node`.
+#[cfg(not(target_os = "linux"))]
+pub fn bind_memory(_memory: &mut [u64], node: usize) -> Result<(), String> {
+    Err(format!(
+        "Cannot bind memory to NUMA node {}: NUMA placement is only supported on Linux",
+        node
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: usize, cpus: Vec<usize>, memory_mb: u64) -> NumaNode {
+        NumaNode {
+            id,
+            cpus,
+            memory_mb,
+        }
+    }
+
+    #[test]
+    fn test_read_nodes() {
+        let root = std::env::temp_dir().join(format!("ferritest-numa-{}", std::process::id()));
+        for (name, cpulist, meminfo) in [
+            ("node1", "4-7\n", "Node 1 MemTotal:        2097152 kB\n"),
+            ("node0", "0-3\n", "Node 0 MemTotal:        4194304 kB\n"),
+            ("node2", "\n", "Node 2 MemTotal:        1048576 kB\n"),
+        ] {
+            let dir = root.join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("cpulist"), cpulist).unwrap();
+            std::fs::write(dir.join("meminfo"), meminfo).unwrap();
+        }
+        std::fs::create_dir_all(root.join("power")).unwrap();
+
+        let nodes = read_nodes(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            nodes,
+            vec![
+                node(0, vec![0, 1, 2, 3], 4096),
+                node(1, vec![4, 5, 6, 7], 2048),
+                node(2, vec![], 1024),
+            ]
+        );
+        assert!(read_nodes(Path::new("/nonexistent")).is_empty());
+    }
+
+    #[test]
+    fn test_parse_node_list() {
+        let nodes = vec![
+            node(0, vec![0], 1024),
+            node(1, vec![1], 1024),
+            node(2, vec![], 0),
+        ];
+
+        assert_eq!(parse_node_list("all", &nodes), Ok(vec![0, 1]));
+        assert_eq!(parse_node_list("1", &nodes), Ok(vec![1]));
+        assert_eq!(parse_node_list("0-2", &nodes), Ok(vec![0, 1, 2]));
+        assert!(parse_node_list("3", &nodes).is_err());
+        assert!(parse_node_list("x", &nodes).is_err());
+    }
+
+    #[test]
+    fn test_next_node() {
+        let nodes = vec![
+            node(0, vec![0], 1024),
+            node(1, vec![], 1024),
+            node(2, vec![2], 1024),
+        ];
+
+        assert_eq!(next_node(&nodes, 0), Some(2));
+        assert_eq!(next_node(&nodes, 1), Some(2));
+        assert_eq!(next_node(&nodes, 2), Some(0));
+        assert_eq!(next_node(&nodes[..1], 0), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_bind_memory() {
+        let Some(first) = nodes().into_iter().find(|n| n.memory_mb > 0) else {
+            return;
+        };
+        let mut memory = vec![0u64; 4096];
+        bind_memory(&mut memory, first.id).unwrap();
+        memory.fill(u64::MAX);
+        assert!(memory.iter().all(|&word| word == u64::MAX));
+
+        assert!(bind_memory(&mut memory, MAX_NODES).is_err());
+    }
+}
@@ -0,0 +1,466 @@
+//! Events emitted while a test runs.
+//!
+//! Testers report what they are doing through a [`TestObserver`] instead of
+//! printing, so the same run can drive a terminal UI, a GUI or nothing at all.
+//! [`TerminalObserver`] is the progress display the CLI uses; an
+//! [`mpsc::Sender<TestEvent>`](std::sync::mpsc::Sender) forwards every event
+//! to another thread.
+
+use crate::affinity;
+use crate::block_move;
+use crate::buffer::HugePages;
+use crate::cache::{CacheBypass, WriteMode};
+use crate::error::MemoryErrorInfo;
+use crate::pagemap;
+use crate::patterns::TestPattern;
+use crate::retention::{self, Retention};
+use crate::rowhammer::Rowhammer;
+use crate::simd::SimdLevel;
+use crate::traits::{ProgressUpdate, TestResult};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Parameters of a run, sent once before testing starts.
+#[derive(Debug, Clone)]
+pub struct RunInfo {
+    /// Name of the tester (e.g., "CPU/RAM").
+    pub device: String,
+    /// Memory actually tested in megabytes, after rounding to whole blocks.
+    pub memory_mb: usize,
+    /// Memory requested in megabytes.
+    pub requested_mb: usize,
+    /// Size of each test block in bytes.
+    pub block_size: usize,
+    /// Number of worker threads.
+    pub threads: usize,
+    /// Blocks tested by each worker.
+    pub blocks_per_thread: usize,
+    /// Patterns run on every block, in order.
+    pub patterns: Vec<TestPattern>,
+    /// Spacing of the modulo-N pattern, if it is among the patterns.
+    pub modulo_n: Option<usize>,
+    /// Whether the address-line test runs after the patterns.
+    pub address_lines: bool,
+    /// Whether the block-move test runs after the address-line test.
+    pub block_move: bool,
+    /// Names of the March tests run after the patterns.
+    pub march_tests: Vec<String>,
+    /// Idle periods of the retention test run after the March tests, if any.
+    pub retention: Option<Retention>,
+    /// Budget of the rowhammer test run after the retention test, if any.
+    pub rowhammer: Option<Rowhammer>,
+    /// Master seed of the run.
+    pub seed: u64,
+    /// Whether the run repeats until stopped.
+    pub continuous: bool,
+    /// Time limit of the run, if any.
+    pub timeout: Option<Duration>,
+    /// Cores the workers are pinned to, empty when unpinned.
+    pub cores: Vec<usize>,
+    /// NUMA nodes worker memory is bound to, empty when unbound.
+    pub numa_nodes: Vec<usize>,
+    /// Whether blocks are verified from a different node than wrote them.
+    pub cross_node: bool,
+    /// Huge pages requested for test memory.
+    pub huge_pages: HugePages,
+    /// How blocks are flushed from the CPU caches before verification.
+    pub cache_bypass: CacheBypass,
+    /// Vector instructions used to fill and verify blocks.
+    pub simd: SimdLevel,
+    /// Stores used to fill blocks with patterns.
+    pub write_mode: WriteMode,
+}
+
+/// Something that happened during a test run.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum TestEvent {
+    /// The run is about to start.
+    RunStarted(RunInfo),
+    /// A worker started writing a pattern to its blocks.
+    PatternStarted {
+        /// Worker thread index.
+        worker: usize,
+        /// Pattern being tested.
+        pattern: TestPattern,
+        /// Zero-based pass.
+        pass: u64,
+    },
+    /// A worker finished a pattern, or stopped partway through it.
+    PatternFinished {
+        /// Worker thread index.
+        worker: usize,
+        /// This worker's share of the pattern pass.
+        result: TestResult,
+    },
+    /// A worker started the address-line test on its blocks.
+    AddressLinesStarted {
+        /// Worker thread index.
+        worker: usize,
+        /// Zero-based pass.
+        pass: u64,
+    },
+    /// A worker started the block-move test on its blocks.
+    BlockMoveStarted {
+        /// Worker thread index.
+        worker: usize,
+        /// Zero-based pass.
+        pass: u64,
+    },
+    /// A worker started a March test on its blocks.
+    MarchStarted {
+        /// Worker thread index.
+        worker: usize,
+        /// Name of the March test.
+        name: String,
+        /// Zero-based pass.
+        pass: u64,
+    },
+    /// A worker filled its blocks for the retention test and is leaving
+    /// them idle.
+    RetentionStarted {
+        /// Worker thread index.
+        worker: usize,
+        /// Pattern left in the blocks.
+        pattern: TestPattern,
+        /// Idle period before the blocks are verified.
+        idle: Duration,
+        /// Zero-based pass.
+        pass: u64,
+    },
+    /// A worker picked its aggressor pairs and started hammering them.
+    RowhammerStarted {
+        /// Worker thread index.
+        worker: usize,
+        /// Aggressor pairs to hammer.
+        pairs: usize,
+        /// How many of them are double-sided.
+        double_sided: usize,
+        /// Zero-based pass.
+        pass: u64,
+    },
+    /// A worker finished testing one block.
+    BlockProgress(ProgressUpdate),
+    /// A memory error was recorded.
+    ErrorFound(MemoryErrorInfo),
+    /// Periodic snapshot of the run's counters.
+    Stats {
+        /// Time since the run started.
+        elapsed: Duration,
+        /// Bytes written and verified so far.
+        bytes_tested: u64,
+        /// Blocks that passed a test.
+        tests_completed: u64,
+        /// Mismatching words found.
+        errors_found: u64,
+    },
+    /// The run ended, either completed or stopped.
+    RunFinished {
+        /// Wall-clock duration of the run.
+        duration: Duration,
+        /// Merged per-pass results, as returned by the tester.
+        results: Vec<TestResult>,
+    },
+}
+
+/// Receives events from a running tester.
+///
+/// Events arrive from several worker threads at once, so implementations
+/// must be cheap and thread-safe. Testing blocks while `on_event` runs.
+pub trait TestObserver: Send + Sync {
+    /// Handles one event.
+    fn on_event(&self, event: &TestEvent);
+}
+
+/// Forwards every event over a channel; events are dropped once the receiver is gone.
+impl TestObserver for mpsc::Sender<TestEvent> {
+    fn on_event(&self, event: &TestEvent) {
+        let _ = self.send(event.clone());
+    }
+}
+
+/// Terminal progress display: a banner, one progress bar per worker and a
+/// statistics spinner.
+///
+/// The banner goes to stdout; the bars are drawn on stderr.
+pub struct TerminalObserver {
+    header: bool,
+    multi_progress: MultiProgress,
+    bars: Mutex<Vec<ProgressBar>>,
+    stats: Mutex<Option<ProgressBar>>,
+}
+
+impl TerminalObserver {
+    /// Creates a display that prints the banner and draws progress.
+    pub fn new() -> Self {
+        Self {
+            header: true,
+            multi_progress: MultiProgress::new(),
+            bars: Mutex::new(Vec::new()),
+            stats: Mutex::new(None),
+        }
+    }
+
+    /// Skips the banner, leaving stdout free for machine-readable output.
+    pub fn without_header(mut self) -> Self {
+        self.header = false;
+        self
+    }
+
+    fn start(&self, info: &RunInfo) {
+        if self.header {
+            print_header(info);
+        }
+
+        let spinner = ProgressBar::new_spinner();
+        spinner.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} {msg}")
+                .unwrap(),
+        );
+        *self.stats.lock().unwrap() = Some(spinner);
+
+        let tests_per_block = info.patterns.len()
+            + usize::from(info.address_lines)
+            + usize::from(info.block_move)
+            + info.march_tests.len()
+            + info.retention.map_or(0, |_| retention::PATTERNS.len())
+            + usize::from(info.rowhammer.is_some());
+        let mut bars = self.bars.lock().unwrap();
+        for worker in 0..info.threads {
+            let bar = self.multi_progress.add(ProgressBar::new(
+                (info.blocks_per_thread * tests_per_block * info.block_size) as u64,
+            ));
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("{msg}\n{bar:40.cyan/blue} {bytes}/{total_bytes}")
+                    .unwrap()
+                    .progress_chars("=>-"),
+            );
+            bar.set_message(format!("Thread {} initializing", worker));
+            bars.push(bar);
+        }
+    }
+
+    fn with_bar(&self, worker: usize, f: impl FnOnce(&ProgressBar)) {
+        if let Some(bar) = self.bars.lock().unwrap().get(worker) {
+            f(bar);
+        }
+    }
+
+    fn finish(&self) {
+        for (worker, bar) in self.bars.lock().unwrap().drain(..).enumerate() {
+            bar.finish_with_message(format!("Thread {} complete", worker));
+        }
+        if let Some(spinner) = self.stats.lock().unwrap().take() {
+            spinner.finish_with_message("Statistics reporting complete");
+        }
+    }
+}
+
+impl Default for TerminalObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestObserver for TerminalObserver {
+    fn on_event(&self, event: &TestEvent) {
+        match event {
+            TestEvent::RunStarted(info) => self.start(info),
+            TestEvent::PatternStarted {
+                worker,
+                pattern,
+                pass,
+            } => self.with_bar(*worker, |bar| {
+                bar.set_message(format!(
+                    "Thread {} - {} (iter {})",
+                    worker,
+                    pattern.name(),
+                    pass
+                ))
+            }),
+            TestEvent::AddressLinesStarted { worker, pass } => self.with_bar(*worker, |bar| {
+                bar.set_message(format!("Thread {} - Address Lines (iter {})", worker, pass))
+            }),
+            TestEvent::BlockMoveStarted { worker, pass } => self.with_bar(*worker, |bar| {
+                bar.set_message(format!("Thread {} - Block Move (iter {})", worker, pass))
+            }),
+            TestEvent::MarchStarted { worker, name, pass } => self.with_bar(*worker, |bar| {
+                bar.set_message(format!("Thread {} - {} (iter {})", worker, name, pass))
+            }),
+            TestEvent::RetentionStarted {
+                worker,
+                pattern,
+                idle,
+                pass,
+            } => self.with_bar(*worker, |bar| {
+                bar.set_message(format!(
+                    "Thread {} - Retention {}, idle {} (iter {})",
+                    worker,
+                    pattern.name(),
+                    humantime::format_duration(*idle),
+                    pass
+                ))
+            }),
+            TestEvent::RowhammerStarted {
+                worker,
+                pairs,
+                double_sided,
+                pass,
+            } => self.with_bar(*worker, |bar| {
+                bar.set_message(format!(
+                    "Thread {} - Rowhammer, {} pairs ({} double-sided) (iter {})",
+                    worker, pairs, double_sided, pass
+                ))
+            }),
+            TestEvent::BlockProgress(update) => self.with_bar(update.worker, |bar| {
+                bar.set_length(update.total_bytes);
+                bar.set_position(update.bytes_processed);
+            }),
+            TestEvent::Stats {
+                elapsed,
+                bytes_tested,
+                tests_completed,
+                errors_found,
+            } => {
+                if let Some(spinner) = self.stats.lock().unwrap().as_ref() {
+                    let mb_per_sec = if elapsed.as_secs_f64() > 0.0 {
+                        (*bytes_tested as f64 / (1024.0 * 1024.0)) / elapsed.as_secs_f64()
+                    } else {
+                        0.0
+                    };
+                    spinner.set_message(format!(
+                        "Elapsed: {:.1}s | Tested: {} MB | Speed: {:.2} MB/s | Tests: {} | Errors: {}",
+                        elapsed.as_secs_f64(),
+                        bytes_tested / (1024 * 1024),
+                        mb_per_sec,
+                        tests_completed,
+                        errors_found
+                    ));
+                }
+            }
+            TestEvent::RunFinished { .. } => self.finish(),
+            TestEvent::PatternFinished { .. } | TestEvent::ErrorFound(_) => {}
+        }
+    }
+}
+
+/// Prints the test parameters before the run starts.
+fn print_header(info: &RunInfo) {
+    println!("Memory Stress Test");
+    println!("==================");
+    println!(
+        "Memory to test: {} MB (requested: {} MB)",
+        info.memory_mb, info.requested_mb
+    );
+    println!("Block size: {} MB", info.block_size / (1024 * 1024));
+    println!("Threads: {}", info.threads);
+    if !info.cores.is_empty() {
+        println!(
+            "Pinned to cores: {}",
+            affinity::format_cpu_list(&info.cores)
+        );
+    }
+    if !info.numa_nodes.is_empty() {
+        println!(
+            "NUMA nodes: {}{}",
+            affinity::format_cpu_list(&info.numa_nodes),
+            if info.cross_node {
+                " (verified from the next node)"
+            } else {
+                ""
+            }
+        );
+    }
+    if info.huge_pages != HugePages::Off {
+        println!("Pages: {} requested", info.huge_pages.describe());
+    }
+    println!(
+        "Cache bypass: {} ({})",
+        info.cache_bypass,
+        info.cache_bypass.describe()
+    );
+    println!("Fill/verify instructions: {}", info.simd);
+    if info.write_mode != WriteMode::Cached {
+        println!(
+            "Write mode: {} ({})",
+            info.write_mode,
+            info.write_mode.describe()
+        );
+    }
+    println!("Seed: {}", info.seed);
+    println!("Physical addresses: {}", pagemap::probe().describe());
+    println!("Blocks per thread: {}", info.blocks_per_thread);
+    let pattern_names: Vec<&str> = info.patterns.iter().map(|p| p.name()).collect();
+    println!("Patterns: {}", pattern_names.join(", "));
+    if let Some(n) = info.modulo_n {
+        println!("Modulo-N spacing: {}", n);
+    }
+    println!(
+        "Mode: {}",
+        if info.continuous {
+            "Continuous"
+        } else {
+            "Single pass"
+        }
+    );
+
+    if let Some(timeout) = info.timeout {
+        println!("Duration: {:?}", timeout);
+    }
+    if info.address_lines {
+        println!("Address-line test: on");
+    }
+    if info.block_move {
+        let methods: Vec<&str> = block_move::methods().iter().map(|m| m.name()).collect();
+        println!("Block-move test: {}", methods.join(", "));
+    }
+    if !info.march_tests.is_empty() {
+        println!("March tests: {}", info.march_tests.join(", "));
+    }
+    if let Some(retention) = &info.retention {
+        println!("Retention test: {}", retention);
+    }
+    if let Some(rowhammer) = &info.rowhammer {
+        println!("Rowhammer test: {}", rowhammer);
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sender_forwards_events() {
+        let (tx, rx) = mpsc::channel();
+        tx.on_event(&TestEvent::PatternStarted {
+            worker: 1,
+            pattern: TestPattern::AllOnes,
+            pass: 2,
+        });
+
+        match rx.try_recv() {
+            Ok(TestEvent::PatternStarted {
+                worker,
+                pattern,
+                pass,
+            }) => {
+                assert_eq!(worker, 1);
+                assert_eq!(pattern, TestPattern::AllOnes);
+                assert_eq!(pass, 2);
+            }
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        drop(rx);
+        tx.on_event(&TestEvent::ErrorFound(MemoryErrorInfo::new(
+            TestPattern::AllOnes,
+            0,
+            0,
+        )));
+    }
+}
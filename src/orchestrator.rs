@@ -0,0 +1,290 @@
+//! Concurrent testing of several devices.
+//!
+//! The [`Orchestrator`] runs any mix of [`MemoryTester`]s in parallel, so RAM
+//! and VRAM are loaded at the same time, and combines their results into a
+//! single verdict.
+
+use crate::error::{FerritestError, MemoryErrorInfo};
+use crate::stats::TestStats;
+use crate::traits::{MemoryTester, TestConfig, TestResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the orchestrator checks the stop signal and timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Outcome of one tester in a concurrent run.
+#[derive(Debug)]
+pub struct TesterOutcome {
+    /// Name of the tester (e.g., "CPU/RAM").
+    pub name: &'static str,
+    /// Device description from the tester.
+    pub device_info: String,
+    /// Statistics collected by this tester alone.
+    pub stats: Arc<TestStats>,
+    /// Per-pass results, or the fatal error that ended testing.
+    pub results: Result<Vec<TestResult>, FerritestError>,
+    /// Errors the tester recorded.
+    pub errors: Vec<MemoryErrorInfo>,
+    /// Wall time this tester ran for.
+    pub duration: Duration,
+}
+
+impl TesterOutcome {
+    /// Returns whether the tester finished without memory errors or a fatal error.
+    pub fn passed(&self) -> bool {
+        match &self.results {
+            Ok(results) => {
+                self.stats.get_errors() == 0 && results.iter().all(|r| r.errors_found == 0)
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Combined outcome of a concurrent run.
+#[derive(Debug)]
+pub struct OrchestratorRun {
+    /// One outcome per tester, in the order the testers were given.
+    pub outcomes: Vec<TesterOutcome>,
+    /// Wall time of the whole run.
+    pub duration: Duration,
+}
+
+impl OrchestratorRun {
+    /// Returns whether every tester passed.
+    pub fn passed(&self) -> bool {
+        self.outcomes.iter().all(TesterOutcome::passed)
+    }
+}
+
+/// Runs several memory testers at the same time.
+///
+/// Each tester runs on its own thread with its own [`TestStats`] and stop
+/// flag, so one device finishing its passes does not cut the others short.
+/// Setting the shared stop signal or reaching the configured timeout stops
+/// all of them.
+pub struct Orchestrator {
+    testers: Vec<Box<dyn MemoryTester>>,
+    stop_on_error: bool,
+}
+
+impl Orchestrator {
+    /// Creates an orchestrator for the given testers.
+    pub fn new(testers: Vec<Box<dyn MemoryTester>>) -> Self {
+        Self {
+            testers,
+            stop_on_error: false,
+        }
+    }
+
+    /// Stops every tester as soon as any of them finds a memory error.
+    pub fn with_stop_on_error(mut self, stop_on_error: bool) -> Self {
+        self.stop_on_error = stop_on_error;
+        self
+    }
+
+    /// Returns the testers, in the order they were given.
+    pub fn testers(&self) -> &[Box<dyn MemoryTester>] {
+        &self.testers
+    }
+
+    /// Runs all testers concurrently with the same configuration.
+    ///
+    /// `config.timeout` applies to the run as a whole. Returns once every
+    /// tester has finished or stopped.
+    pub fn run(&mut self, config: &TestConfig, should_stop: Arc<AtomicBool>) -> OrchestratorRun {
+        let start_time = Instant::now();
+        let stats: Vec<Arc<TestStats>> = self
+            .testers
+            .iter()
+            .map(|_| Arc::new(TestStats::new()))
+            .collect();
+        let stops: Vec<Arc<AtomicBool>> = self
+            .testers
+            .iter()
+            .map(|_| Arc::new(AtomicBool::new(false)))
+            .collect();
+        let stop_on_error = self.stop_on_error;
+
+        let outcomes = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .testers
+                .iter_mut()
+                .zip(stats.iter().zip(&stops))
+                .map(|(tester, (stats, stop))| {
+                    let stats = Arc::clone(stats);
+                    let stop = Arc::clone(stop);
+                    scope.spawn(move || {
+                        let tester_start = Instant::now();
+                        let results = tester.run_tests(config, Arc::clone(&stats), stop);
+                        TesterOutcome {
+                            name: tester.name(),
+                            device_info: tester.device_info(),
+                            stats,
+                            results,
+                            errors: tester.recorded_errors(),
+                            duration: tester_start.elapsed(),
+                        }
+                    })
+                })
+                .collect();
+
+            // Propagate the shared stop signal, timeout and first error to every tester
+            while !handles.iter().all(|h| h.is_finished()) {
+                let timed_out = config
+                    .timeout
+                    .is_some_and(|timeout| start_time.elapsed() >= timeout);
+                let failed = stop_on_error && stats.iter().any(|s| s.get_errors() > 0);
+                if should_stop.load(Ordering::Relaxed) || timed_out || failed {
+                    for stop in &stops {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("Tester thread panicked"))
+                .collect()
+        });
+
+        OrchestratorRun {
+            outcomes,
+            duration: start_time.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::TestPattern;
+
+    /// Tester that completes one pass per tick until stopped or out of passes.
+    struct FakeTester {
+        passes: Option<u64>,
+        errors: u64,
+    }
+
+    impl MemoryTester for FakeTester {
+        fn name(&self) -> &'static str {
+            "Fake"
+        }
+
+        fn device_info(&self) -> String {
+            "fake device".to_string()
+        }
+
+        fn max_testable_memory(&self) -> u64 {
+            0
+        }
+
+        fn run_tests(
+            &mut self,
+            _config: &TestConfig,
+            stats: Arc<TestStats>,
+            should_stop: Arc<AtomicBool>,
+        ) -> Result<Vec<TestResult>, FerritestError> {
+            let mut results = Vec::new();
+            let mut pass = 0;
+            while !should_stop.load(Ordering::Relaxed) && self.passes.is_none_or(|p| pass < p) {
+                std::thread::sleep(Duration::from_millis(5));
+                stats.add_bytes(1024);
+                for _ in 0..self.errors {
+                    stats.add_error();
+                }
+                results.push(TestResult {
+                    bytes_tested: 1024,
+                    errors_found: self.errors,
+                    pattern: TestPattern::AllOnes,
+                    pass,
+                    duration_ms: 5,
+                });
+                pass += 1;
+            }
+            Ok(results)
+        }
+
+        fn recorded_errors(&self) -> Vec<MemoryErrorInfo> {
+            (0..self.errors)
+                .map(|i| MemoryErrorInfo::new(TestPattern::AllOnes, i as usize * 8, 0))
+                .collect()
+        }
+    }
+
+    fn fake(passes: Option<u64>, errors: u64) -> Box<dyn MemoryTester> {
+        Box::new(FakeTester { passes, errors })
+    }
+
+    fn config(timeout: Option<Duration>) -> TestConfig {
+        TestConfig {
+            timeout,
+            seed: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_runs_every_tester() {
+        let mut orchestrator = Orchestrator::new(vec![fake(Some(2), 0), fake(Some(5), 0)]);
+        let run = orchestrator.run(&config(None), Arc::new(AtomicBool::new(false)));
+
+        assert!(run.passed());
+        let passes: Vec<usize> = run
+            .outcomes
+            .iter()
+            .map(|o| o.results.as_ref().unwrap().len())
+            .collect();
+        assert_eq!(passes, vec![2, 5]);
+        assert_eq!(run.outcomes[1].stats.get_bytes(), 5 * 1024);
+        assert_eq!(orchestrator.testers().len(), 2);
+    }
+
+    #[test]
+    fn test_timeout_stops_all_testers() {
+        let mut orchestrator = Orchestrator::new(vec![fake(None, 0), fake(None, 0)]);
+        let run = orchestrator.run(
+            &config(Some(Duration::from_millis(100))),
+            Arc::new(AtomicBool::new(false)),
+        );
+
+        assert!(run.passed());
+        assert!(run.duration < Duration::from_secs(5));
+        for outcome in &run.outcomes {
+            assert!(!outcome.results.as_ref().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_shared_stop_signal() {
+        let should_stop = Arc::new(AtomicBool::new(true));
+        let mut orchestrator = Orchestrator::new(vec![fake(None, 0)]);
+        let run = orchestrator.run(&config(None), should_stop);
+
+        assert!(run.outcomes[0].results.as_ref().unwrap().len() <= 1);
+    }
+
+    #[test]
+    fn test_combined_verdict() {
+        let mut orchestrator = Orchestrator::new(vec![fake(Some(1), 0), fake(Some(1), 2)]);
+        let run = orchestrator.run(&config(None), Arc::new(AtomicBool::new(false)));
+
+        assert!(!run.passed());
+        assert!(run.outcomes[0].passed());
+        assert!(!run.outcomes[1].passed());
+        assert_eq!(run.outcomes[1].errors.len(), 2);
+    }
+
+    #[test]
+    fn test_stop_on_error() {
+        let mut orchestrator =
+            Orchestrator::new(vec![fake(None, 0), fake(None, 1)]).with_stop_on_error(true);
+        let run = orchestrator.run(&config(None), Arc::new(AtomicBool::new(false)));
+
+        assert!(!run.passed());
+        assert!(run.outcomes[0].passed());
+    }
+}
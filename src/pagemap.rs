@@ -0,0 +1,258 @@
+//! Virtual-to-physical address translation via `/proc/self/pagemap`.
+//!
+//! Linux exposes one 64-bit entry per virtual page in `/proc/self/pagemap`.
+//! Bits 0-54 hold the page frame number (PFN) when the page is present, bit
+//! 62 marks a swapped page and bit 63 a present page. Since Linux 4.0 the PFN
+//! reads as zero unless the process has `CAP_SYS_ADMIN`.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Path of the calling process's page map.
+pub const PAGEMAP_PATH: &str = "/proc/self/pagemap";
+
+/// Size of one pagemap entry in bytes.
+const ENTRY_SIZE: u64 = 8;
+
+const PFN_MASK: u64 = (1 << 55) - 1;
+const SWAPPED_BIT: u64 = 1 << 62;
+const PRESENT_BIT: u64 = 1 << 63;
+
+/// Returns the system page size in bytes.
+#[cfg(unix)]
+pub fn page_size() -> usize {
+    // SAFETY: sysconf has no preconditions and only reads a system constant.
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as usize
+    } else {
+        4096
+    }
+}
+
+/// Returns the system page size in bytes.
+#[cfg(not(unix))]
+pub fn page_size() -> usize {
+    4096
+}
+
+/// A decoded pagemap entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageMapEntry {
+    /// The page is resident in RAM.
+    pub present: bool,
+    /// The page is swapped out.
+    pub swapped: bool,
+    /// Page frame number, or 0 if hidden or not present.
+    pub pfn: u64,
+}
+
+impl PageMapEntry {
+    /// Decodes a raw 64-bit pagemap entry.
+    pub fn from_raw(raw: u64) -> Self {
+        let present = raw & PRESENT_BIT != 0;
+        Self {
+            present,
+            swapped: raw & SWAPPED_BIT != 0,
+            pfn: if present { raw & PFN_MASK } else { 0 },
+        }
+    }
+}
+
+/// Physical address of a tested word, or why it could not be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicalAddress {
+    /// The resolved physical address.
+    Known(u64),
+    /// The kernel hides frame numbers from unprivileged processes.
+    NeedsPrivilege,
+    /// The page was not resident (swapped out or never touched).
+    NotPresent,
+    /// The page map could not be read (not Linux, or `/proc` unavailable).
+    Unsupported,
+}
+
+impl PhysicalAddress {
+    /// Describes whether addresses can be resolved, for use in report headers.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Known(_) => format!("resolved via {}", PAGEMAP_PATH),
+            other => other.to_string(),
+        }
+    }
+
+    /// Returns the physical address, if it was resolved.
+    pub fn known(&self) -> Option<u64> {
+        match self {
+            Self::Known(addr) => Some(*addr),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PhysicalAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Known(addr) => write!(f, "0x{:X}", addr),
+            Self::NeedsPrivilege => write!(f, "unavailable (needs CAP_SYS_ADMIN)"),
+            Self::NotPresent => write!(f, "unavailable (page not resident)"),
+            Self::Unsupported => write!(f, "unavailable (pagemap not supported)"),
+        }
+    }
+}
+
+/// Reader for a pagemap file.
+pub struct PageMap<R> {
+    reader: R,
+    page_size: usize,
+}
+
+impl PageMap<File> {
+    /// Opens `/proc/self/pagemap` for the current process.
+    pub fn open_self() -> io::Result<Self> {
+        Ok(Self::new(File::open(PAGEMAP_PATH)?, page_size()))
+    }
+}
+
+impl<R: Read + Seek> PageMap<R> {
+    /// Creates a page map over any pagemap-formatted reader.
+    pub fn new(reader: R, page_size: usize) -> Self {
+        Self { reader, page_size }
+    }
+
+    /// Reads the entry for the page containing `vaddr`.
+    pub fn entry(&mut self, vaddr: usize) -> io::Result<PageMapEntry> {
+        let page = (vaddr / self.page_size) as u64;
+        self.reader.seek(SeekFrom::Start(page * ENTRY_SIZE))?;
+        let mut buf = [0u8; ENTRY_SIZE as usize];
+        self.reader.read_exact(&mut buf)?;
+        Ok(PageMapEntry::from_raw(u64::from_le_bytes(buf)))
+    }
+
+    /// Translates a virtual address into a physical address.
+    pub fn translate(&mut self, vaddr: usize) -> PhysicalAddress {
+        match self.entry(vaddr) {
+            Ok(entry) if !entry.present => PhysicalAddress::NotPresent,
+            Ok(entry) if entry.pfn == 0 => PhysicalAddress::NeedsPrivilege,
+            Ok(entry) => PhysicalAddress::Known(
+                entry.pfn * self.page_size as u64 + (vaddr % self.page_size) as u64,
+            ),
+            Err(_) => PhysicalAddress::Unsupported,
+        }
+    }
+}
+
+/// Translates `vaddr` with an optional page map, reporting why it failed if absent.
+pub fn translate(pagemap: Option<&mut PageMap<File>>, vaddr: usize) -> PhysicalAddress {
+    match pagemap {
+        Some(pagemap) => pagemap.translate(vaddr),
+        None => PhysicalAddress::Unsupported,
+    }
+}
+
+/// Checks whether this process can resolve physical addresses.
+///
+/// Translates the address of a freshly written heap word, so the result is
+/// `Known` only when the page map is readable and frame numbers are visible.
+pub fn probe() -> PhysicalAddress {
+    let word = Box::new(1u64);
+    let vaddr = &*word as *const u64 as usize;
+    match PageMap::open_self() {
+        Ok(mut pagemap) => pagemap.translate(vaddr),
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => PhysicalAddress::NeedsPrivilege,
+        Err(_) => PhysicalAddress::Unsupported,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Four pages read with CAP_SYS_ADMIN: PFN 0x1234, PFN 0xABCDE, swapped, not present.
+    const PRIVILEGED: &[u8] = include_bytes!("../tests/fixtures/pagemap_privileged.bin");
+    /// The same pages read without CAP_SYS_ADMIN (frame numbers zeroed).
+    const UNPRIVILEGED: &[u8] = include_bytes!("../tests/fixtures/pagemap_unprivileged.bin");
+
+    const PAGE: usize = 4096;
+
+    #[test]
+    fn test_entry_from_raw() {
+        let entry = PageMapEntry::from_raw(PRESENT_BIT | 0x42);
+        assert!(entry.present);
+        assert!(!entry.swapped);
+        assert_eq!(entry.pfn, 0x42);
+
+        let entry = PageMapEntry::from_raw(SWAPPED_BIT | 0x42);
+        assert!(!entry.present);
+        assert!(entry.swapped);
+        assert_eq!(entry.pfn, 0);
+    }
+
+    #[test]
+    fn test_privileged_fixture() {
+        let mut pagemap = PageMap::new(Cursor::new(PRIVILEGED), PAGE);
+
+        assert_eq!(
+            pagemap.translate(0x10),
+            PhysicalAddress::Known(0x1234 * PAGE as u64 + 0x10)
+        );
+        assert_eq!(
+            pagemap.translate(PAGE + 0xFF8),
+            PhysicalAddress::Known(0xABCDE * PAGE as u64 + 0xFF8)
+        );
+        assert!(pagemap.entry(2 * PAGE).unwrap().swapped);
+        assert_eq!(pagemap.translate(2 * PAGE), PhysicalAddress::NotPresent);
+        assert_eq!(pagemap.translate(3 * PAGE), PhysicalAddress::NotPresent);
+    }
+
+    #[test]
+    fn test_unprivileged_fixture() {
+        let mut pagemap = PageMap::new(Cursor::new(UNPRIVILEGED), PAGE);
+
+        assert!(pagemap.entry(0).unwrap().present);
+        assert_eq!(pagemap.translate(0), PhysicalAddress::NeedsPrivilege);
+        assert_eq!(pagemap.translate(PAGE), PhysicalAddress::NeedsPrivilege);
+        assert_eq!(pagemap.translate(2 * PAGE), PhysicalAddress::NotPresent);
+    }
+
+    #[test]
+    fn test_read_past_end_is_unsupported() {
+        let mut pagemap = PageMap::new(Cursor::new(PRIVILEGED), PAGE);
+        assert_eq!(pagemap.translate(100 * PAGE), PhysicalAddress::Unsupported);
+    }
+
+    #[test]
+    fn test_physical_address_display() {
+        assert_eq!(PhysicalAddress::Known(0x1F000).to_string(), "0x1F000");
+        assert_eq!(
+            PhysicalAddress::NeedsPrivilege.to_string(),
+            "unavailable (needs CAP_SYS_ADMIN)"
+        );
+        assert_eq!(
+            PhysicalAddress::Known(0x10).describe(),
+            "resolved via /proc/self/pagemap"
+        );
+        assert_eq!(PhysicalAddress::Known(0x10).known(), Some(0x10));
+        assert_eq!(PhysicalAddress::NotPresent.known(), None);
+    }
+
+    #[test]
+    fn test_page_size() {
+        let size = page_size();
+        assert!(size >= 4096);
+        assert!(size.is_power_of_two());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_probe_reads_pagemap() {
+        assert_ne!(probe(), PhysicalAddress::Unsupported);
+    }
+
+    #[test]
+    fn test_translate_without_pagemap() {
+        assert_eq!(translate(None, 0x1000), PhysicalAddress::Unsupported);
+    }
+}
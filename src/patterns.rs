@@ -3,7 +3,80 @@
 //! This module contains the `TestPattern` enum and its implementation,
 //! which defines various bit patterns used to test memory integrity.
 
+use crate::cache::{self, read_word, write_word, WriteMode};
+use crate::inversions::{self, WordWidth};
+use crate::modulo;
+use crate::simd::{self, SimdLevel};
 use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::str::FromStr;
+
+/// Derives a per-test seed from the run's master seed.
+///
+/// Each component (thread, block, pass, ...) is folded in with a SplitMix64
+/// step, so nearby component values still yield unrelated seeds and the same
+/// master seed always reproduces the same sequence.
+pub fn derive_seed(master: u64, components: &[u64]) -> u64 {
+    components.iter().fold(splitmix64(master), |acc, &c| {
+        splitmix64(acc ^ c.wrapping_mul(0x9E3779B97F4A7C15))
+    })
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// A word that did not hold the value its pattern wrote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Word index within the block.
+    pub index: usize,
+    /// Value the pattern wrote.
+    pub expected: u64,
+    /// Value read back.
+    pub actual: u64,
+}
+
+/// Iterator over every mismatching word in a block, in ascending order.
+///
+/// Created by [`TestPattern::mismatches`].
+pub struct Mismatches<'a> {
+    pattern: TestPattern,
+    seed: u64,
+    block: &'a [u64],
+    index: usize,
+    rng: rand::rngs::StdRng,
+}
+
+impl Iterator for Mismatches<'_> {
+    type Item = Mismatch;
+
+    fn next(&mut self) -> Option<Mismatch> {
+        while self.index < self.block.len() {
+            let index = self.index;
+            self.index += 1;
+
+            let expected = self.pattern.expected_at(index, self.seed, &mut self.rng);
+            let actual = read_word(&self.block[index]);
+            if actual != expected {
+                return Some(Mismatch {
+                    index,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Words copied from a precomputed chunk by the vectorized fill and verify (4 KB).
+///
+/// A multiple of 64, so the walking patterns line up with every chunk.
+const CHUNK_WORDS: usize = 512;
 
 /// Memory test patterns for detecting different types of memory faults.
 ///
@@ -12,6 +85,8 @@ use rand::{Rng, SeedableRng};
 /// - Checkerboard patterns detect address decoder faults
 /// - Random patterns provide broad coverage
 /// - Solid patterns (all zeros/ones) detect stuck bits
+/// - Moving inversions detect transition faults (CPU only)
+/// - Modulo-N detects disturbance hidden by caching and buffering (CPU only)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TestPattern {
     WalkingOnes,
@@ -22,10 +97,13 @@ pub enum TestPattern {
     AllZeros,
     AllOnes,
     Sequential,
+    MovingInversions32,
+    MovingInversions64,
+    ModuloN,
 }
 
 impl TestPattern {
-    /// Returns all available test patterns.
+    /// Returns all fill-and-verify patterns, the default selection.
     pub fn all_patterns() -> Vec<Self> {
         vec![
             Self::WalkingOnes,
@@ -39,6 +117,18 @@ impl TestPattern {
         ]
     }
 
+    /// Returns the CPU-only patterns that rewrite memory while verifying it.
+    ///
+    /// They take many times longer than a fill and verify, so they only run
+    /// when selected by name.
+    pub fn extended_patterns() -> Vec<Self> {
+        vec![
+            Self::MovingInversions32,
+            Self::MovingInversions64,
+            Self::ModuloN,
+        ]
+    }
+
     /// Returns the human-readable name of this pattern.
     pub fn name(&self) -> &'static str {
         match self {
@@ -50,79 +140,250 @@ impl TestPattern {
             Self::AllZeros => "All Zeros",
             Self::AllOnes => "All Ones",
             Self::Sequential => "Sequential",
+            Self::MovingInversions32 => "Moving Inversions 32-bit",
+            Self::MovingInversions64 => "Moving Inversions 64-bit",
+            Self::ModuloN => "Modulo-N",
         }
     }
 
-    /// Returns the numeric pattern ID for GPU shaders.
+    /// Returns the command-line name of this pattern (e.g. `walking-ones`).
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Self::WalkingOnes => "walking-ones",
+            Self::WalkingZeros => "walking-zeros",
+            Self::Checkerboard => "checkerboard",
+            Self::InverseCheckerboard => "inverse-checkerboard",
+            Self::RandomPattern => "random",
+            Self::AllZeros => "all-zeros",
+            Self::AllOnes => "all-ones",
+            Self::Sequential => "sequential",
+            Self::MovingInversions32 => "moving-inversions-32",
+            Self::MovingInversions64 => "moving-inversions-64",
+            Self::ModuloN => "modulo-n",
+        }
+    }
+
+    /// Resolves an include list and an exclude list into the patterns to run.
+    ///
+    /// An empty include list selects every pattern. Order follows the include
+    /// list, with duplicates removed. `allow_empty` lets the exclusions remove
+    /// every pattern, for runs where another test still has work to do.
+    ///
+    /// # Returns
+    /// * `Ok(patterns)`, empty only if `allow_empty` is set
+    /// * `Err(message)` if the exclusions leave nothing to run
+    pub fn select(
+        include: &[Self],
+        exclude: &[Self],
+        allow_empty: bool,
+    ) -> Result<Vec<Self>, String> {
+        let candidates = if include.is_empty() {
+            Self::all_patterns()
+        } else {
+            include.to_vec()
+        };
+
+        let mut selected = Vec::new();
+        for pattern in candidates {
+            if !exclude.contains(&pattern) && !selected.contains(&pattern) {
+                selected.push(pattern);
+            }
+        }
+
+        if selected.is_empty() && !allow_empty {
+            return Err("No test patterns selected".to_string());
+        }
+        Ok(selected)
+    }
+
+    /// Returns the word width of a moving-inversions pattern, or `None` for
+    /// the fill-and-verify patterns.
+    pub fn inversion_width(&self) -> Option<WordWidth> {
+        match self {
+            Self::MovingInversions32 => Some(WordWidth::Bits32),
+            Self::MovingInversions64 => Some(WordWidth::Bits64),
+            _ => None,
+        }
+    }
+
+    /// Returns the numeric pattern ID for GPU shaders, or `None` for
+    /// CPU-only patterns.
     ///
     /// These IDs must match the constants in the WGSL shader files.
     #[cfg(feature = "gpu")]
-    pub fn pattern_id(&self) -> u32 {
+    pub fn pattern_id(&self) -> Option<u32> {
         match self {
-            Self::WalkingOnes => 0,
-            Self::WalkingZeros => 1,
-            Self::Checkerboard => 2,
-            Self::InverseCheckerboard => 3,
-            Self::RandomPattern => 4,
-            Self::AllZeros => 5,
-            Self::AllOnes => 6,
-            Self::Sequential => 7,
+            Self::WalkingOnes => Some(0),
+            Self::WalkingZeros => Some(1),
+            Self::Checkerboard => Some(2),
+            Self::InverseCheckerboard => Some(3),
+            Self::RandomPattern => Some(4),
+            Self::AllZeros => Some(5),
+            Self::AllOnes => Some(6),
+            Self::Sequential => Some(7),
+            Self::MovingInversions32 | Self::MovingInversions64 | Self::ModuloN => None,
         }
     }
 
     /// Fills a memory block with this pattern.
     ///
+    /// Moving-inversions patterns write `P` of their first rotation and
+    /// modulo-N writes its first phase with the default spacing; the tests
+    /// themselves run in [`inversions::run`] and [`modulo::run`].
+    ///
+    /// Uses the fastest vector instructions this CPU supports and regular
+    /// stores through the caches.
+    ///
     /// # Arguments
     /// * `block` - The memory block to fill (as u64 slice)
     /// * `seed` - Seed for random pattern generation
     pub fn fill_block(&self, block: &mut [u64], seed: u64) {
+        self.fill_block_with(SimdLevel::detect(), WriteMode::Cached, block, seed);
+    }
+
+    /// Fills a memory block with this pattern using `level` and `mode`.
+    ///
+    /// Every level and mode writes the same words; [`SimdLevel::Scalar`] is
+    /// the reference implementation. The sequential and random patterns
+    /// compute each word, which costs more than storing it, so they are
+    /// always written word by word.
+    pub fn fill_block_with(&self, level: SimdLevel, mode: WriteMode, block: &mut [u64], seed: u64) {
+        match (self.repeating_chunk(), mode) {
+            (Some(words), _) if level != SimdLevel::Scalar => {
+                for chunk in block.chunks_mut(CHUNK_WORDS) {
+                    simd::store(level, mode, chunk, &words[..chunk.len()]);
+                }
+            }
+            (_, WriteMode::Cached) => self.fill_block_scalar(block, seed, write_word),
+            (_, WriteMode::Streaming) => {
+                self.fill_block_scalar(block, seed, |word, value| {
+                    // SAFETY: fenced below, before the block is accessed again.
+                    unsafe { cache::stream_word(word, value) }
+                });
+                cache::store_fence();
+            }
+        }
+    }
+
+    /// Returns the first `CHUNK_WORDS` words of a pattern that repeats
+    /// every 64 words, or `None` for the sequential, random and modulo-N
+    /// patterns.
+    fn repeating_chunk(&self) -> Option<[u64; CHUNK_WORDS]> {
+        if matches!(self, Self::Sequential | Self::RandomPattern | Self::ModuloN) {
+            return None;
+        }
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        Some(std::array::from_fn(|index| {
+            self.expected_at(index, 0, &mut rng)
+        }))
+    }
+
+    /// Reference fill: one word at a time, stored with `put`.
+    fn fill_block_scalar(&self, block: &mut [u64], seed: u64, put: impl Fn(&mut u64, u64)) {
         match self {
             Self::WalkingOnes => {
                 for (i, val) in block.iter_mut().enumerate() {
-                    *val = 1u64.wrapping_shl((i % 64) as u32);
+                    put(val, 1u64.wrapping_shl((i % 64) as u32));
                 }
             }
             Self::WalkingZeros => {
                 for (i, val) in block.iter_mut().enumerate() {
-                    *val = !1u64.wrapping_shl((i % 64) as u32);
+                    put(val, !1u64.wrapping_shl((i % 64) as u32));
                 }
             }
             Self::Checkerboard => {
                 for val in block.iter_mut() {
-                    *val = 0xAAAAAAAAAAAAAAAA;
+                    put(val, 0xAAAAAAAAAAAAAAAA);
                 }
             }
             Self::InverseCheckerboard => {
                 for val in block.iter_mut() {
-                    *val = 0x5555555555555555;
+                    put(val, 0x5555555555555555);
                 }
             }
             Self::RandomPattern => {
                 let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
                 for val in block.iter_mut() {
-                    *val = rng.gen();
+                    put(val, rng.gen());
                 }
             }
             Self::AllZeros => {
                 for val in block.iter_mut() {
-                    *val = 0;
+                    put(val, 0);
                 }
             }
             Self::AllOnes => {
                 for val in block.iter_mut() {
-                    *val = u64::MAX;
+                    put(val, u64::MAX);
                 }
             }
             Self::Sequential => {
                 for (i, val) in block.iter_mut().enumerate() {
-                    *val = i as u64;
+                    put(val, i as u64);
+                }
+            }
+            Self::MovingInversions32 => {
+                for (i, val) in block.iter_mut().enumerate() {
+                    put(val, inversions::value(WordWidth::Bits32, 0, i));
                 }
             }
+            Self::MovingInversions64 => {
+                for (i, val) in block.iter_mut().enumerate() {
+                    put(val, inversions::value(WordWidth::Bits64, 0, i));
+                }
+            }
+            Self::ModuloN => {
+                let p = modulo::pattern(seed);
+                for (i, val) in block.iter_mut().enumerate() {
+                    put(val, modulo::first_phase(p, i));
+                }
+            }
+        }
+    }
+
+    /// Returns the value this pattern writes at `index`.
+    ///
+    /// `rng` must be seeded with `seed` and is advanced once per call for
+    /// the random pattern, so indices must be visited in order.
+    fn expected_at(&self, index: usize, seed: u64, rng: &mut rand::rngs::StdRng) -> u64 {
+        match self {
+            Self::WalkingOnes => 1u64.wrapping_shl((index % 64) as u32),
+            Self::WalkingZeros => !1u64.wrapping_shl((index % 64) as u32),
+            Self::Checkerboard => 0xAAAAAAAAAAAAAAAA,
+            Self::InverseCheckerboard => 0x5555555555555555,
+            Self::RandomPattern => rng.gen(),
+            Self::AllZeros => 0,
+            Self::AllOnes => u64::MAX,
+            Self::Sequential => index as u64,
+            Self::MovingInversions32 => inversions::value(WordWidth::Bits32, 0, index),
+            Self::MovingInversions64 => inversions::value(WordWidth::Bits64, 0, index),
+            Self::ModuloN => modulo::first_phase(modulo::pattern(seed), index),
+        }
+    }
+
+    /// Returns an iterator over every word in `block` that does not match this pattern.
+    ///
+    /// Unlike [`Self::verify_block`], this keeps going past the first mismatch and
+    /// reports the expected and actual value of each one. Use `.take(n)` to cap
+    /// how many are collected.
+    ///
+    /// # Arguments
+    /// * `block` - The memory block to check (as u64 slice)
+    /// * `seed` - Seed used for random pattern generation
+    pub fn mismatches<'a>(&self, block: &'a [u64], seed: u64) -> Mismatches<'a> {
+        Mismatches {
+            pattern: *self,
+            seed,
+            block,
+            index: 0,
+            rng: rand::rngs::StdRng::seed_from_u64(seed),
         }
     }
 
     /// Verifies a memory block contains the expected pattern.
     ///
+    /// Uses the fastest vector instructions this CPU supports.
+    ///
     /// # Arguments
     /// * `block` - The memory block to verify (as u64 slice)
     /// * `seed` - Seed used for random pattern generation
@@ -131,9 +392,37 @@ impl TestPattern {
     /// * `Ok(())` if verification passes
     /// * `Err(index)` with the index of the first mismatch
     pub fn verify_block(&self, block: &[u64], seed: u64) -> Result<(), usize> {
+        self.verify_block_with(SimdLevel::detect(), block, seed)
+    }
+
+    /// Verifies a memory block contains the expected pattern using `level`.
+    ///
+    /// Every level finds the same first mismatch; [`SimdLevel::Scalar`] is
+    /// the reference implementation. As in [`Self::fill_block_with`], the
+    /// sequential and random patterns are always checked word by word.
+    pub fn verify_block_with(
+        &self,
+        level: SimdLevel,
+        block: &[u64],
+        seed: u64,
+    ) -> Result<(), usize> {
+        let words = match self.repeating_chunk() {
+            Some(words) if level != SimdLevel::Scalar => words,
+            _ => return self.verify_block_scalar(block, seed),
+        };
+        for (i, chunk) in block.chunks(CHUNK_WORDS).enumerate() {
+            if let Some(index) = simd::find_mismatch(level, chunk, &words[..chunk.len()]) {
+                return Err(i * CHUNK_WORDS + index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reference verify: one word at a time.
+    fn verify_block_scalar(&self, block: &[u64], seed: u64) -> Result<(), usize> {
         match self {
             Self::WalkingOnes => {
-                for (i, &val) in block.iter().enumerate() {
+                for (i, val) in block.iter().map(read_word).enumerate() {
                     let expected = 1u64.wrapping_shl((i % 64) as u32);
                     if val != expected {
                         return Err(i);
@@ -141,7 +430,7 @@ impl TestPattern {
                 }
             }
             Self::WalkingZeros => {
-                for (i, &val) in block.iter().enumerate() {
+                for (i, val) in block.iter().map(read_word).enumerate() {
                     let expected = !1u64.wrapping_shl((i % 64) as u32);
                     if val != expected {
                         return Err(i);
@@ -149,14 +438,14 @@ impl TestPattern {
                 }
             }
             Self::Checkerboard => {
-                for (i, &val) in block.iter().enumerate() {
+                for (i, val) in block.iter().map(read_word).enumerate() {
                     if val != 0xAAAAAAAAAAAAAAAA {
                         return Err(i);
                     }
                 }
             }
             Self::InverseCheckerboard => {
-                for (i, &val) in block.iter().enumerate() {
+                for (i, val) in block.iter().map(read_word).enumerate() {
                     if val != 0x5555555555555555 {
                         return Err(i);
                     }
@@ -164,7 +453,7 @@ impl TestPattern {
             }
             Self::RandomPattern => {
                 let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
-                for (i, &val) in block.iter().enumerate() {
+                for (i, val) in block.iter().map(read_word).enumerate() {
                     let expected: u64 = rng.gen();
                     if val != expected {
                         return Err(i);
@@ -172,31 +461,84 @@ impl TestPattern {
                 }
             }
             Self::AllZeros => {
-                for (i, &val) in block.iter().enumerate() {
+                for (i, val) in block.iter().map(read_word).enumerate() {
                     if val != 0 {
                         return Err(i);
                     }
                 }
             }
             Self::AllOnes => {
-                for (i, &val) in block.iter().enumerate() {
+                for (i, val) in block.iter().map(read_word).enumerate() {
                     if val != u64::MAX {
                         return Err(i);
                     }
                 }
             }
             Self::Sequential => {
-                for (i, &val) in block.iter().enumerate() {
+                for (i, val) in block.iter().map(read_word).enumerate() {
                     if val != i as u64 {
                         return Err(i);
                     }
                 }
             }
+            Self::MovingInversions32 => {
+                for (i, val) in block.iter().map(read_word).enumerate() {
+                    if val != inversions::value(WordWidth::Bits32, 0, i) {
+                        return Err(i);
+                    }
+                }
+            }
+            Self::MovingInversions64 => {
+                for (i, val) in block.iter().map(read_word).enumerate() {
+                    if val != inversions::value(WordWidth::Bits64, 0, i) {
+                        return Err(i);
+                    }
+                }
+            }
+            Self::ModuloN => {
+                let p = modulo::pattern(seed);
+                for (i, val) in block.iter().map(read_word).enumerate() {
+                    if val != modulo::first_phase(p, i) {
+                        return Err(i);
+                    }
+                }
+            }
         }
         Ok(())
     }
 }
 
+impl fmt::Display for TestPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.slug())
+    }
+}
+
+impl FromStr for TestPattern {
+    type Err = String;
+
+    /// Parses a pattern from its command-line name or human-readable name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = s.trim().to_lowercase().replace([' ', '_'], "-");
+        // Human-readable names that differ from the slug
+        match key.as_str() {
+            "random-pattern" => return Ok(Self::RandomPattern),
+            "moving-inversions-32-bit" => return Ok(Self::MovingInversions32),
+            "moving-inversions-64-bit" => return Ok(Self::MovingInversions64),
+            _ => {}
+        }
+        let patterns = [Self::all_patterns(), Self::extended_patterns()].concat();
+        patterns
+            .iter()
+            .copied()
+            .find(|p| p.slug() == key)
+            .ok_or_else(|| {
+                let names: Vec<&str> = patterns.iter().map(|p| p.slug()).collect();
+                format!("Unknown pattern: '{}'. Use one of: {}", s, names.join(", "))
+            })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,6 +550,57 @@ mod tests {
         assert_eq!(TestPattern::all_patterns().len(), 8);
     }
 
+    #[test]
+    fn test_extended_patterns() {
+        for pattern in TestPattern::extended_patterns() {
+            assert!(!TestPattern::all_patterns().contains(&pattern));
+            assert_eq!(pattern.to_string().parse::<TestPattern>(), Ok(pattern));
+            assert_eq!(pattern.name().parse::<TestPattern>(), Ok(pattern));
+            #[cfg(feature = "gpu")]
+            assert_eq!(pattern.pattern_id(), None);
+        }
+        assert_eq!(
+            TestPattern::MovingInversions32.name(),
+            "Moving Inversions 32-bit"
+        );
+        assert!(TestPattern::MovingInversions64.inversion_width().is_some());
+        assert_eq!(TestPattern::ModuloN.inversion_width(), None);
+        assert_eq!(TestPattern::AllOnes.inversion_width(), None);
+        assert_eq!("Modulo-N".parse(), Ok(TestPattern::ModuloN));
+        assert_eq!(
+            TestPattern::select(&[TestPattern::MovingInversions64], &[], false).unwrap(),
+            vec![TestPattern::MovingInversions64]
+        );
+    }
+
+    #[test]
+    fn test_modulo_n_fill_and_verify() {
+        let mut block = vec![0u64; TEST_BLOCK_SIZE];
+        TestPattern::ModuloN.fill_block(&mut block, 5);
+        let p = modulo::pattern(5);
+        assert_eq!(block[0], p);
+        assert_eq!(block[1], !p);
+        assert_eq!(block[40], p);
+        assert!(TestPattern::ModuloN.verify_block(&block, 5).is_ok());
+        assert_eq!(TestPattern::ModuloN.verify_block(&block, 6), Err(0));
+        assert_eq!(TestPattern::ModuloN.mismatches(&block, 5).count(), 0);
+    }
+
+    #[test]
+    fn test_moving_inversions_fill_and_verify() {
+        for (pattern, width) in [
+            (TestPattern::MovingInversions32, WordWidth::Bits32),
+            (TestPattern::MovingInversions64, WordWidth::Bits64),
+        ] {
+            let mut block = vec![0u64; TEST_BLOCK_SIZE];
+            pattern.fill_block(&mut block, 0);
+            for (i, &val) in block.iter().enumerate() {
+                assert_eq!(val, inversions::value(width, 0, i));
+            }
+            assert!(pattern.verify_block(&block, 0).is_ok());
+        }
+    }
+
     #[test]
     fn test_pattern_names() {
         assert_eq!(TestPattern::WalkingOnes.name(), "Walking Ones");
@@ -223,6 +616,61 @@ mod tests {
         assert_eq!(TestPattern::Sequential.name(), "Sequential");
     }
 
+    #[test]
+    fn test_pattern_from_str_round_trip() {
+        for pattern in TestPattern::all_patterns() {
+            assert_eq!(pattern.to_string().parse::<TestPattern>(), Ok(pattern));
+            assert_eq!(pattern.name().parse::<TestPattern>(), Ok(pattern));
+        }
+        assert_eq!("random".parse(), Ok(TestPattern::RandomPattern));
+        assert_eq!("ALL_ONES".parse(), Ok(TestPattern::AllOnes));
+        assert!("diagonal".parse::<TestPattern>().is_err());
+    }
+
+    #[test]
+    fn test_select_patterns() {
+        assert_eq!(
+            TestPattern::select(&[], &[], false).unwrap(),
+            TestPattern::all_patterns()
+        );
+
+        let selected = TestPattern::select(
+            &[
+                TestPattern::RandomPattern,
+                TestPattern::WalkingOnes,
+                TestPattern::RandomPattern,
+            ],
+            &[],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            selected,
+            vec![TestPattern::RandomPattern, TestPattern::WalkingOnes]
+        );
+
+        let selected = TestPattern::select(&[], &[TestPattern::Sequential], false).unwrap();
+        assert_eq!(selected.len(), 7);
+        assert!(!selected.contains(&TestPattern::Sequential));
+
+        assert!(
+            TestPattern::select(&[TestPattern::AllOnes], &[TestPattern::AllOnes], false).is_err()
+        );
+        assert!(
+            TestPattern::select(&[TestPattern::AllOnes], &[TestPattern::AllOnes], true)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_derive_seed_deterministic() {
+        assert_eq!(derive_seed(42, &[1, 2, 3]), derive_seed(42, &[1, 2, 3]));
+        assert_ne!(derive_seed(42, &[1, 2, 3]), derive_seed(43, &[1, 2, 3]));
+        assert_ne!(derive_seed(42, &[1, 2, 3]), derive_seed(42, &[1, 3, 2]));
+        assert_ne!(derive_seed(42, &[0, 1]), derive_seed(42, &[1, 0]));
+    }
+
     #[test]
     fn test_walking_ones_fill_and_verify() {
         let mut block = vec![0u64; TEST_BLOCK_SIZE];
@@ -352,6 +800,73 @@ mod tests {
         assert_eq!(result.unwrap_err(), 100);
     }
 
+    #[test]
+    fn test_mismatches_reports_every_corruption() {
+        let mut block = vec![0u64; TEST_BLOCK_SIZE];
+        TestPattern::Sequential.fill_block(&mut block, 0);
+
+        block[3] = 0;
+        block[500] ^= 1 << 12;
+        block[1023] = u64::MAX;
+
+        let mismatches: Vec<Mismatch> = TestPattern::Sequential.mismatches(&block, 0).collect();
+        assert_eq!(
+            mismatches,
+            vec![
+                Mismatch {
+                    index: 3,
+                    expected: 3,
+                    actual: 0
+                },
+                Mismatch {
+                    index: 500,
+                    expected: 500,
+                    actual: 500 ^ (1 << 12)
+                },
+                Mismatch {
+                    index: 1023,
+                    expected: 1023,
+                    actual: u64::MAX
+                },
+            ]
+        );
+        assert_eq!(
+            TestPattern::Sequential
+                .mismatches(&block, 0)
+                .take(2)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_mismatches_random_pattern() {
+        let mut block = vec![0u64; TEST_BLOCK_SIZE];
+        TestPattern::RandomPattern.fill_block(&mut block, 7);
+        let original = block[700];
+        block[700] = !original;
+
+        let mismatches: Vec<Mismatch> = TestPattern::RandomPattern.mismatches(&block, 7).collect();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].index, 700);
+        assert_eq!(mismatches[0].expected, original);
+        assert_eq!(mismatches[0].actual, !original);
+    }
+
+    #[test]
+    fn test_mismatches_empty_on_good_block() {
+        for pattern in TestPattern::all_patterns() {
+            let mut block = vec![0u64; TEST_BLOCK_SIZE];
+            pattern.fill_block(&mut block, 99);
+            assert_eq!(
+                pattern.mismatches(&block, 99).count(),
+                0,
+                "Pattern {:?} reported mismatches",
+                pattern.name()
+            );
+        }
+    }
+
     #[test]
     fn test_all_patterns_fill_and_verify() {
         for pattern in TestPattern::all_patterns() {
@@ -385,4 +900,63 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_simd_fill_matches_scalar() {
+        // Lengths cover partial chunks and vectors; offsets misalign the start
+        for pattern in [
+            TestPattern::all_patterns(),
+            TestPattern::extended_patterns(),
+        ]
+        .concat()
+        {
+            for (level, mode) in SimdLevel::available()
+                .into_iter()
+                .flat_map(|level| [(level, WriteMode::Cached), (level, WriteMode::Streaming)])
+            {
+                for (offset, len) in [(0, 0), (0, 1), (1, 63), (3, 512), (5, 1500), (0, 4096)] {
+                    let mut reference = vec![0u64; offset + len];
+                    let mut vectorized = vec![0u64; offset + len];
+                    pattern.fill_block_with(
+                        SimdLevel::Scalar,
+                        WriteMode::Cached,
+                        &mut reference[offset..],
+                        42,
+                    );
+                    pattern.fill_block_with(level, mode, &mut vectorized[offset..], 42);
+                    assert_eq!(reference, vectorized, "{} with {} {}", pattern, level, mode);
+                    assert!(pattern
+                        .verify_block_with(level, &vectorized[offset..], 42)
+                        .is_ok());
+                    assert_eq!(pattern.mismatches(&vectorized[offset..], 42).count(), 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_verify_matches_scalar() {
+        for pattern in [
+            TestPattern::all_patterns(),
+            TestPattern::extended_patterns(),
+        ]
+        .concat()
+        {
+            let mut block = vec![0u64; 1500];
+            pattern.fill_block_with(SimdLevel::Scalar, WriteMode::Cached, &mut block, 7);
+            for index in [0, 1, 511, 512, 513, 1023, 1499] {
+                block[index] ^= 1 << (index % 64);
+                for level in SimdLevel::available() {
+                    assert_eq!(
+                        pattern.verify_block_with(level, &block, 7),
+                        Err(index),
+                        "{} with {}",
+                        pattern,
+                        level
+                    );
+                }
+                block[index] ^= 1 << (index % 64);
+            }
+        }
+    }
 }
@@ -0,0 +1,188 @@
+//! JUnit XML output.
+//!
+//! Each device becomes a `<testsuite>` and each pattern pass a `<testcase>`.
+//! Memory errors are `<failure>`s carrying the offset and values of the
+//! recorded mismatches; a device that could not finish testing gets an
+//! `<error>` case.
+
+use super::{Outcome, Report};
+use std::fmt::{self, Write};
+
+impl Report {
+    /// Renders the report as a JUnit XML document.
+    pub fn to_junit_xml(&self) -> String {
+        let mut xml = String::new();
+        self.write_junit(&mut xml)
+            .expect("writing to a String cannot fail");
+        xml
+    }
+
+    fn write_junit(&self, xml: &mut String) -> fmt::Result {
+        let suites = self.suites();
+        let tests: usize = suites.iter().map(|s| s.cases.len()).sum();
+        let failures: usize = suites.iter().map(|s| s.failures()).sum();
+        let errors: usize = suites.iter().map(|s| s.errors()).sum();
+
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            xml,
+            r#"<testsuites name="ferritest" tests="{}" failures="{}" errors="{}" time="{}">"#,
+            tests,
+            failures,
+            errors,
+            seconds(self.duration_ms)
+        )?;
+
+        for suite in &suites {
+            writeln!(
+                xml,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" errors="{}" time="{}">"#,
+                escape(&suite.name),
+                suite.cases.len(),
+                suite.failures(),
+                suite.errors(),
+                seconds(suite.device.duration_ms)
+            )?;
+            writeln!(xml, "    <properties>")?;
+            for (name, value) in [
+                ("seed", self.seed.to_string()),
+                ("status", self.status.to_string()),
+                ("device", suite.device.info.clone()),
+                ("bytes_tested", suite.device.totals.bytes_tested.to_string()),
+            ] {
+                writeln!(
+                    xml,
+                    r#"      <property name="{}" value="{}"/>"#,
+                    name,
+                    escape(&value)
+                )?;
+            }
+            writeln!(xml, "    </properties>")?;
+
+            for case in &suite.cases {
+                write!(
+                    xml,
+                    r#"    <testcase name="{}" classname="ferritest.{}" time="{}""#,
+                    escape(&case.name),
+                    suite.id,
+                    seconds(case.duration_ms)
+                )?;
+                match &case.outcome {
+                    Outcome::Passed => writeln!(xml, "/>")?,
+                    Outcome::Failed { records, .. } => {
+                        writeln!(xml, ">")?;
+                        write!(
+                            xml,
+                            r#"      <failure type="MemoryError" message="{}">"#,
+                            escape(&case.outcome.message())
+                        )?;
+                        for record in records {
+                            write!(xml, "\n{}", escape(&record.to_string()))?;
+                        }
+                        writeln!(xml, "</failure>")?;
+                        writeln!(xml, "    </testcase>")?;
+                    }
+                    Outcome::Error(_) => {
+                        writeln!(xml, ">")?;
+                        writeln!(
+                            xml,
+                            r#"      <error type="DeviceError" message="{}"/>"#,
+                            escape(&case.outcome.message())
+                        )?;
+                        writeln!(xml, "    </testcase>")?;
+                    }
+                }
+            }
+            writeln!(xml, "  </testsuite>")?;
+        }
+        writeln!(xml, "</testsuites>")
+    }
+}
+
+/// Formats milliseconds as JUnit's fractional seconds.
+fn seconds(ms: u64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// Escapes text for use in XML attributes and content.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{config, cpu_device, gpu_device};
+    use super::super::{ErrorRecord, Report};
+    use super::*;
+    use crate::error::MemoryErrorInfo;
+    use crate::patterns::TestPattern;
+    use std::time::Duration;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_passing_suite() {
+        let report = Report::new(
+            config(),
+            42,
+            vec![cpu_device(Vec::new())],
+            false,
+            Duration::from_millis(1500),
+        );
+        let xml = report.to_junit_xml();
+
+        assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(xml.contains(
+            r#"<testsuites name="ferritest" tests="2" failures="0" errors="0" time="1.500">"#
+        ));
+        assert!(xml.contains(r#"<testsuite name="CPU/RAM" tests="2""#));
+        assert!(xml.contains(r#"<property name="seed" value="42"/>"#));
+        assert!(xml.contains(
+            r#"<testcase name="walking-ones pass 0" classname="ferritest.cpu" time="0.040"/>"#
+        ));
+    }
+
+    #[test]
+    fn test_failures_and_errors() {
+        let info = MemoryErrorInfo::with_values(TestPattern::RandomPattern, 0x40, 1, 0xFF, 0xFE)
+            .with_pass(0);
+        let report = Report::new(
+            config(),
+            42,
+            vec![
+                cpu_device(vec![ErrorRecord::from(&info)]),
+                gpu_device("GPU device lost during testing"),
+            ],
+            false,
+            Duration::ZERO,
+        );
+        let xml = report.to_junit_xml();
+
+        assert!(xml.contains(r#"tests="3" failures="1" errors="1""#));
+        assert!(xml.contains(
+            r#"<failure type="MemoryError" message="1 mismatching word; first at offset 0x40: expected 0x00000000000000FF, got 0x00000000000000FE (source 1)">"#
+        ));
+        assert!(xml.contains(r#"<testsuite name="GPU 1: Test &lt;GPU&gt;""#));
+        assert!(xml.contains(r#"<testcase name="device" classname="ferritest.gpu1""#));
+        assert!(
+            xml.contains(r#"<error type="DeviceError" message="GPU device lost during testing"/>"#)
+        );
+    }
+}
@@ -0,0 +1,1422 @@
+//! Machine-readable test reports.
+//!
+//! A [`Report`] captures everything a run produced: the configuration, one
+//! [`DeviceReport`] per tester (the CPU or each GPU), their per-pass results,
+//! totals and recorded errors, and whether the run completed or was
+//! interrupted. It serializes to JSON with a versioned schema so tools can
+//! consume results without scraping the text summary.
+//!
+//! 64-bit values that can exceed 2^53 (the seed, expected/actual words and
+//! physical addresses) are written as strings so they survive JSON parsers
+//! that store numbers as doubles.
+//!
+//! The same model also renders as JUnit XML ([`Report::to_junit_xml`]) and TAP
+//! ([`Report::to_tap`]) for CI dashboards: each device becomes a suite and each
+//! pattern pass a case.
+
+mod junit;
+mod tap;
+
+use crate::buffer::HugePages;
+use crate::cache::{Latency, WriteMode};
+use crate::cpu::{CoreResult, CpuMemoryError, NodeResult, PageUsage, Residency};
+use crate::error::{
+    AddressLine, CopyMethod, CrossCheck, Hammering, InversionStep, MemoryErrorInfo, ModuloPhase,
+    TestKind,
+};
+use crate::patterns::TestPattern;
+use crate::stats::TestStats;
+use crate::traits::TestResult;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+/// Version of the report schema.
+///
+/// Bumped whenever a field is removed, renamed or changes meaning. New
+/// optional fields may be added without a bump.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Whether the run finished on its own or was stopped by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    /// Every tester ran to completion (or to its duration or error limit).
+    Complete,
+    /// The run was stopped early with Ctrl+C.
+    Interrupted,
+}
+
+impl fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Complete => write!(f, "complete"),
+            Self::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+/// Kind of memory a device report covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    /// System RAM tested from the CPU.
+    Cpu,
+    /// GPU VRAM.
+    Gpu,
+}
+
+/// Options the run was started with.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportConfig {
+    /// Requested amount of memory per device in megabytes.
+    pub memory_mb: usize,
+    /// Requested CPU thread count (`None` means one per CPU).
+    pub threads: Option<usize>,
+    /// Whether testing repeated until stopped.
+    pub continuous: bool,
+    /// Time limit in milliseconds, if any.
+    pub duration_ms: Option<u64>,
+    /// Selected patterns.
+    #[serde(serialize_with = "display_seq")]
+    pub patterns: Vec<TestPattern>,
+    /// Spacing of the modulo-N pattern, if it was selected.
+    pub modulo_n: Option<usize>,
+    /// Whether the address-line test ran after the patterns.
+    pub address_lines: bool,
+    /// Whether the block-move test ran after the address-line test.
+    pub block_move: bool,
+    /// Names of the March tests run after the patterns.
+    pub march_tests: Vec<String>,
+    /// Idle period of the retention test's first pass in milliseconds, if
+    /// it ran.
+    pub retention_ms: Option<u64>,
+    /// Milliseconds added to the retention idle period on each later pass.
+    pub retention_ramp_ms: Option<u64>,
+    /// Rowhammer time budget per pass in milliseconds, if it ran.
+    pub rowhammer_ms: Option<u64>,
+    /// Aggressor pairs hammered per pass, if the rowhammer test ran.
+    pub rowhammer_pairs: Option<usize>,
+    /// Whether testing continued after the first error.
+    pub keep_going: bool,
+    /// Error limit that stops the run, if any.
+    pub max_errors: Option<usize>,
+}
+
+/// Results of one pattern pass.
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternResult {
+    /// Pattern that was tested.
+    #[serde(serialize_with = "display")]
+    pub pattern: TestPattern,
+    /// Zero-based pass number.
+    pub pass: u64,
+    /// Bytes written and verified.
+    pub bytes_tested: u64,
+    /// Mismatching words found.
+    pub errors_found: u64,
+    /// Wall time of the pass in milliseconds.
+    pub duration_ms: u64,
+}
+
+impl From<&TestResult> for PatternResult {
+    fn from(result: &TestResult) -> Self {
+        Self {
+            pattern: result.pattern,
+            pass: result.pass,
+            bytes_tested: result.bytes_tested,
+            errors_found: result.errors_found,
+            duration_ms: result.duration_ms,
+        }
+    }
+}
+
+/// Counters accumulated over the whole run of one device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Totals {
+    /// Bytes written and verified.
+    pub bytes_tested: u64,
+    /// Blocks or passes that completed without error.
+    pub tests_completed: u64,
+    /// Errors counted (may exceed the number recorded).
+    pub errors_found: u64,
+}
+
+impl From<&TestStats> for Totals {
+    fn from(stats: &TestStats) -> Self {
+        Self {
+            bytes_tested: stats.get_bytes(),
+            tests_completed: stats.get_tests(),
+            errors_found: stats.get_errors(),
+        }
+    }
+}
+
+/// Pattern totals of one CPU core, when worker threads were pinned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CoreSummary {
+    /// CPU core ID.
+    pub core: usize,
+    /// Bytes tested by workers on this core.
+    pub bytes_tested: u64,
+    /// Errors found by workers on this core.
+    pub errors_found: u64,
+}
+
+impl From<&CoreResult> for CoreSummary {
+    fn from(result: &CoreResult) -> Self {
+        Self {
+            core: result.core,
+            bytes_tested: result.bytes_tested,
+            errors_found: result.errors_found,
+        }
+    }
+}
+
+/// Pattern totals of one NUMA node, when CPU memory was bound to nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct NodeSummary {
+    /// NUMA node ID.
+    pub node: usize,
+    /// Bytes tested in this node's memory.
+    pub bytes_tested: u64,
+    /// Errors found in this node's memory.
+    pub errors_found: u64,
+    /// Bytes that could not be bound to this node.
+    pub unbound_bytes: u64,
+}
+
+impl From<&NodeResult> for NodeSummary {
+    fn from(result: &NodeResult) -> Self {
+        Self {
+            node: result.node,
+            bytes_tested: result.bytes_tested,
+            errors_found: result.errors_found,
+            unbound_bytes: result.unbound_bytes,
+        }
+    }
+}
+
+/// How much of a CPU run's test memory was locked and resident in RAM.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ResidencySummary {
+    /// Bytes allocated for test blocks.
+    pub total_bytes: u64,
+    /// Bytes locked into RAM.
+    pub locked_bytes: u64,
+    /// Bytes resident in RAM at the lowest measurement, if measured.
+    pub resident_bytes: Option<u64>,
+    /// Share of test memory that stayed resident, in percent, if measured.
+    pub resident_percent: Option<f64>,
+}
+
+impl From<&Residency> for ResidencySummary {
+    fn from(residency: &Residency) -> Self {
+        Self {
+            total_bytes: residency.total_bytes,
+            locked_bytes: residency.locked_bytes,
+            resident_bytes: residency.resident_bytes,
+            resident_percent: residency.resident_percent(),
+        }
+    }
+}
+
+/// Which pages backed a CPU run's test memory.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PagesSummary {
+    /// Huge pages requested: "off", "thp", "2m" or "1g".
+    #[serde(serialize_with = "display")]
+    pub requested: HugePages,
+    /// Pages actually used, in the same terms; smaller than requested on fallback.
+    #[serde(serialize_with = "display")]
+    pub used: HugePages,
+    /// Size of the pages used in bytes.
+    pub page_size: usize,
+    /// Bytes backed by huge pages, if measured.
+    pub huge_bytes: Option<u64>,
+    /// Share of test memory backed by huge pages, in percent, if measured.
+    pub huge_percent: Option<f64>,
+}
+
+impl From<&PageUsage> for PagesSummary {
+    fn from(pages: &PageUsage) -> Self {
+        Self {
+            requested: pages.requested,
+            used: pages.backing.kind(),
+            page_size: pages.backing.page_size(),
+            huge_bytes: pages.huge_bytes,
+            huge_percent: pages.huge_percent(),
+        }
+    }
+}
+
+/// How a CPU run wrote blocks and pushed them out of the caches before
+/// verifying them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CacheSummary {
+    /// Cache bypass strategy: "clflushopt", "clflush", "dc civac" or "eviction".
+    pub strategy: &'static str,
+    /// Average read latency of cached memory in nanoseconds.
+    pub cached_read_ns: f64,
+    /// Average read latency right after a flush in nanoseconds.
+    pub flushed_read_ns: f64,
+    /// Whether flushed reads were clearly slower, i.e. verification read DRAM.
+    pub reaches_dram: bool,
+    /// Stores used to fill blocks with patterns: "cached" or "streaming".
+    pub write_mode: &'static str,
+}
+
+impl CacheSummary {
+    /// Summarizes a run's cache bypass latency and pattern write mode.
+    pub fn new(latency: &Latency, write_mode: WriteMode) -> Self {
+        Self {
+            strategy: latency.bypass.name(),
+            cached_read_ns: latency.cached_ns,
+            flushed_read_ns: latency.flushed_ns,
+            reaches_dram: latency.reaches_dram(),
+            write_mode: write_mode.slug(),
+        }
+    }
+}
+
+/// One recorded memory error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorRecord {
+    /// Test that detected the error: the pattern's slug, or "march",
+    /// "address-lines", "block-move", "retention" or "rowhammer".
+    pub test: &'static str,
+    /// Pattern that detected the error, if a pattern did.
+    #[serde(serialize_with = "display_opt")]
+    pub pattern: Option<TestPattern>,
+    /// Byte offset within the tested block or buffer.
+    pub offset: usize,
+    /// Index of the tested block among those of `source_id`.
+    pub block: usize,
+    /// Thread or GPU index that detected the error.
+    pub source_id: usize,
+    /// Value written, if known.
+    #[serde(serialize_with = "hex")]
+    pub expected: Option<u64>,
+    /// Value read back, if known.
+    #[serde(serialize_with = "hex")]
+    pub actual: Option<u64>,
+    /// Physical address of the failing word, if it could be resolved.
+    #[serde(serialize_with = "hex")]
+    pub physical_address: Option<u64>,
+    /// Pass in which the error was found, if tracked.
+    pub pass: Option<u64>,
+    /// March test element that caught the error, if any.
+    pub march: Option<String>,
+    /// CPU core that read the failing word, if known.
+    pub core: Option<usize>,
+    /// Re-test of the failing page from another core, if one was run.
+    pub cross_check: Option<CrossCheck>,
+    /// Address line implicated, if the address-line test caught the error.
+    pub address_line: Option<AddressLine>,
+    /// Milliseconds the word sat idle, if the retention test caught the error.
+    pub idle_ms: Option<u64>,
+    /// Rotation and walk, if a moving-inversions pattern caught the error.
+    pub inversion: Option<InversionStep>,
+    /// Offset phase, if the modulo-N pattern caught the error.
+    pub modulo: Option<ModuloPhase>,
+    /// Copy method, if the block-move test caught the error.
+    pub copy_method: Option<CopyMethod>,
+    /// Aggressor pair, if the rowhammer test caught the error.
+    pub hammering: Option<Hammering>,
+}
+
+impl From<&MemoryErrorInfo> for ErrorRecord {
+    fn from(info: &MemoryErrorInfo) -> Self {
+        let mut record = Self {
+            test: info.test.slug(),
+            pattern: info.test.pattern(),
+            offset: info.offset,
+            block: info.block,
+            source_id: info.source_id,
+            expected: info.expected,
+            actual: info.actual,
+            physical_address: info.physical_address,
+            pass: info.pass,
+            march: None,
+            core: info.core,
+            cross_check: info.cross_check,
+            address_line: None,
+            idle_ms: None,
+            inversion: None,
+            modulo: None,
+            copy_method: None,
+            hammering: None,
+        };
+        match &info.test {
+            TestKind::Pattern(_) => {}
+            TestKind::MovingInversions(_, step) => record.inversion = Some(*step),
+            TestKind::ModuloN(phase) => record.modulo = Some(*phase),
+            TestKind::March(failure) => record.march = Some(failure.clone()),
+            TestKind::AddressLines(line) => record.address_line = Some(*line),
+            TestKind::BlockMove(method) => record.copy_method = Some(*method),
+            TestKind::Retention(idle) => record.idle_ms = Some(idle.as_millis() as u64),
+            TestKind::Rowhammer(hammering) => record.hammering = Some(*hammering),
+        }
+        record
+    }
+}
+
+impl fmt::Display for ErrorRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = &self.address_line {
+            write!(f, "{}: ", line)?;
+        }
+        if let Some(step) = &self.inversion {
+            write!(f, "{}: ", step)?;
+        }
+        if let Some(phase) = &self.modulo {
+            write!(f, "{}: ", phase)?;
+        }
+        if let Some(method) = &self.copy_method {
+            write!(f, "{} block move: ", method)?;
+        }
+        if let Some(hammering) = &self.hammering {
+            write!(f, "{}: ", hammering)?;
+        }
+        if let Some(idle_ms) = self.idle_ms {
+            write!(
+                f,
+                "after {} idle: ",
+                humantime::format_duration(Duration::from_millis(idle_ms))
+            )?;
+        }
+        match &self.march {
+            Some(march) => write!(f, "{}", march)?,
+            None => write!(
+                f,
+                "offset 0x{:X}: expected {}, got {}",
+                self.offset,
+                word(self.expected),
+                word(self.actual)
+            )?,
+        }
+        write!(f, " (source {}", self.source_id)?;
+        if let Some(core) = self.core {
+            write!(f, ", core {}", core)?;
+        }
+        if let Some(addr) = self.physical_address {
+            write!(f, ", physical 0x{:X}", addr)?;
+        }
+        write!(f, ")")?;
+        if let Some(check) = &self.cross_check {
+            write!(f, ": {}", check)?;
+        }
+        Ok(())
+    }
+}
+
+fn word(value: Option<u64>) -> String {
+    match value {
+        Some(value) => format!("0x{:016X}", value),
+        None => "unknown".to_string(),
+    }
+}
+
+impl From<&CpuMemoryError> for ErrorRecord {
+    fn from(error: &CpuMemoryError) -> Self {
+        Self::from(&MemoryErrorInfo::from(error))
+    }
+}
+
+/// Results for a single tester.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceReport {
+    /// Kind of memory tested.
+    pub kind: DeviceKind,
+    /// GPU index, for GPU devices.
+    pub index: Option<usize>,
+    /// Device name ("CPU/RAM" or the GPU's name).
+    pub name: String,
+    /// Device description from the tester.
+    pub info: String,
+    /// Whether the device finished without memory errors or a fatal error.
+    pub passed: bool,
+    /// Fatal error that stopped testing this device, if any.
+    pub error: Option<String>,
+    /// Wall time spent on this device in milliseconds.
+    pub duration_ms: u64,
+    /// Run-wide counters.
+    pub totals: Totals,
+    /// Per-pass pattern results.
+    pub results: Vec<PatternResult>,
+    /// Per-core totals; empty unless CPU threads were pinned.
+    pub cores: Vec<CoreSummary>,
+    /// Per-node totals; empty unless CPU memory was bound to NUMA nodes.
+    pub nodes: Vec<NodeSummary>,
+    /// Locking and residency of the test memory; CPU runs only.
+    pub residency: Option<ResidencySummary>,
+    /// Pages backing the test memory; CPU runs only.
+    pub pages: Option<PagesSummary>,
+    /// Cache bypass used before verification; CPU runs only.
+    pub cache: Option<CacheSummary>,
+    /// Errors found by the address-line, block-move, March, retention and
+    /// rowhammer tests, by test name; not capped like `errors`. CPU runs only.
+    pub test_errors: BTreeMap<String, u64>,
+    /// Recorded errors.
+    pub errors: Vec<ErrorRecord>,
+}
+
+impl DeviceReport {
+    /// Creates a report for a device that ran its tests.
+    pub fn new(
+        kind: DeviceKind,
+        name: &str,
+        info: String,
+        duration: Duration,
+        totals: Totals,
+        results: &[TestResult],
+        errors: Vec<ErrorRecord>,
+    ) -> Self {
+        Self {
+            kind,
+            index: None,
+            name: name.to_string(),
+            info,
+            passed: totals.errors_found == 0 && errors.is_empty(),
+            error: None,
+            duration_ms: duration.as_millis() as u64,
+            totals,
+            results: results.iter().map(PatternResult::from).collect(),
+            cores: Vec::new(),
+            nodes: Vec::new(),
+            residency: None,
+            pages: None,
+            cache: None,
+            test_errors: BTreeMap::new(),
+            errors,
+        }
+    }
+
+    /// Creates a report for a device that could not be tested at all.
+    pub fn failed(kind: DeviceKind, name: &str, info: String, error: String) -> Self {
+        Self::new(
+            kind,
+            name,
+            info,
+            Duration::ZERO,
+            Totals::default(),
+            &[],
+            Vec::new(),
+        )
+        .with_error(error)
+    }
+
+    /// Marks the device as failed with a fatal error.
+    pub fn with_error(mut self, error: String) -> Self {
+        self.passed = false;
+        self.error = Some(error);
+        self
+    }
+
+    /// Sets the GPU index of this device.
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Attaches per-core totals of a pinned CPU run.
+    pub fn with_cores(mut self, cores: &[CoreResult]) -> Self {
+        self.cores = cores.iter().map(CoreSummary::from).collect();
+        self
+    }
+
+    /// Attaches per-node totals of a NUMA-bound CPU run.
+    pub fn with_nodes(mut self, nodes: &[NodeResult]) -> Self {
+        self.nodes = nodes.iter().map(NodeSummary::from).collect();
+        self
+    }
+
+    /// Attaches the locking and residency of a CPU run's test memory.
+    pub fn with_residency(mut self, residency: &Residency) -> Self {
+        self.residency = Some(ResidencySummary::from(residency));
+        self
+    }
+
+    /// Attaches the pages that backed a CPU run's test memory.
+    pub fn with_pages(mut self, pages: &PageUsage) -> Self {
+        self.pages = Some(PagesSummary::from(pages));
+        self
+    }
+
+    /// Attaches the error counts of a CPU run's tests other than the patterns.
+    pub fn with_test_errors(mut self, test_errors: &BTreeMap<String, u64>) -> Self {
+        self.test_errors = test_errors.clone();
+        self
+    }
+
+    /// Attaches the cache bypass a CPU run used, its measured effect and the
+    /// stores its pattern fills used.
+    pub fn with_cache(mut self, latency: &Latency, write_mode: WriteMode) -> Self {
+        self.cache = Some(CacheSummary::new(latency, write_mode));
+        self
+    }
+}
+
+/// Complete report of a ferritest run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// Schema version, see [`SCHEMA_VERSION`].
+    pub schema_version: u32,
+    /// Version of ferritest that produced the report.
+    pub ferritest_version: String,
+    /// Whether the run completed or was interrupted.
+    pub status: RunStatus,
+    /// Whether every device passed.
+    pub passed: bool,
+    /// Master seed; pass it to `--seed` to replay the run.
+    #[serde(serialize_with = "display")]
+    pub seed: u64,
+    /// Total wall time in milliseconds.
+    pub duration_ms: u64,
+    /// Options the run was started with.
+    pub config: ReportConfig,
+    /// One entry per tested device.
+    pub devices: Vec<DeviceReport>,
+}
+
+impl Report {
+    /// Assembles the report for a finished run.
+    pub fn new(
+        config: ReportConfig,
+        seed: u64,
+        devices: Vec<DeviceReport>,
+        interrupted: bool,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            ferritest_version: env!("CARGO_PKG_VERSION").to_string(),
+            status: if interrupted {
+                RunStatus::Interrupted
+            } else {
+                RunStatus::Complete
+            },
+            passed: devices.iter().all(|d| d.passed),
+            seed,
+            duration_ms: duration.as_millis() as u64,
+            config,
+            devices,
+        }
+    }
+
+    /// Returns the process exit code for this report (0 if every device passed).
+    pub fn exit_code(&self) -> i32 {
+        if self.passed {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Serializes the report as pretty-printed JSON, ending with a newline.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report contains only serializable values") + "\n"
+    }
+
+    /// Views each device as a test suite for the JUnit and TAP writers.
+    fn suites(&self) -> Vec<Suite<'_>> {
+        self.devices
+            .iter()
+            .map(|device| Suite {
+                id: match (device.kind, device.index) {
+                    (DeviceKind::Cpu, _) => "cpu".to_string(),
+                    (DeviceKind::Gpu, Some(index)) => format!("gpu{}", index),
+                    (DeviceKind::Gpu, None) => "gpu".to_string(),
+                },
+                name: match (device.kind, device.index) {
+                    (DeviceKind::Gpu, Some(index)) => format!("GPU {}: {}", index, device.name),
+                    _ => device.name.clone(),
+                },
+                device,
+                cases: self.cases(device),
+            })
+            .collect()
+    }
+
+    /// Builds one case per pattern pass, one per enabled address-line,
+    /// block-move, March, retention and rowhammer test, and one for a fatal
+    /// device error.
+    fn cases<'a>(&self, device: &'a DeviceReport) -> Vec<Case<'a>> {
+        let mut cases: Vec<Case<'a>> = device
+            .results
+            .iter()
+            .map(|result| {
+                let records = device
+                    .errors
+                    .iter()
+                    .filter(|e| {
+                        e.pattern == Some(result.pattern)
+                            && e.pass.is_none_or(|pass| pass == result.pass)
+                    })
+                    .collect();
+                Case {
+                    name: format!("{} pass {}", result.pattern, result.pass),
+                    duration_ms: result.duration_ms,
+                    outcome: Outcome::from_errors(result.errors_found, records),
+                }
+            })
+            .collect();
+
+        // The other tests run only on system RAM and have no pass results, so
+        // each enabled one gets a single case
+        let cpu = device.kind == DeviceKind::Cpu;
+        cases.extend(test_case(
+            device,
+            "address-lines",
+            cpu && self.config.address_lines,
+            |e| e.test == "address-lines",
+        ));
+        cases.extend(test_case(
+            device,
+            "block-move",
+            cpu && self.config.block_move,
+            |e| e.test == "block-move",
+        ));
+        for name in &self.config.march_tests {
+            let prefix = format!("{} M", name);
+            cases.extend(test_case(device, name, cpu, |e| {
+                e.test == "march" && e.march.as_ref().is_some_and(|m| m.starts_with(&prefix))
+            }));
+        }
+        cases.extend(test_case(
+            device,
+            "retention",
+            cpu && self.config.retention_ms.is_some(),
+            |e| e.test == "retention",
+        ));
+        cases.extend(test_case(
+            device,
+            "rowhammer",
+            cpu && self.config.rowhammer_ms.is_some(),
+            |e| e.test == "rowhammer",
+        ));
+
+        if let Some(error) = &device.error {
+            cases.push(Case {
+                name: "device".to_string(),
+                duration_ms: 0,
+                outcome: Outcome::Error(error),
+            });
+        }
+        cases
+    }
+}
+
+/// Builds the case of a test with no pass results, or `None` if it is not
+/// enabled.
+///
+/// `matches` picks the test's recorded errors. The device's uncapped count is
+/// used where known, since `--max-errors` caps the records.
+fn test_case<'a>(
+    device: &'a DeviceReport,
+    name: &str,
+    enabled: bool,
+    matches: impl Fn(&ErrorRecord) -> bool,
+) -> Option<Case<'a>> {
+    if !enabled {
+        return None;
+    }
+    let records: Vec<&ErrorRecord> = device.errors.iter().filter(|e| matches(e)).collect();
+    let errors_found = device
+        .test_errors
+        .get(name)
+        .copied()
+        .unwrap_or_default()
+        .max(records.len() as u64);
+    Some(Case {
+        name: name.to_string(),
+        duration_ms: 0,
+        outcome: Outcome::from_errors(errors_found, records),
+    })
+}
+
+/// A device viewed as a test suite.
+struct Suite<'a> {
+    /// Short identifier ("cpu", "gpu0").
+    id: String,
+    /// Display name ("CPU/RAM", "GPU 0: <name>").
+    name: String,
+    device: &'a DeviceReport,
+    cases: Vec<Case<'a>>,
+}
+
+impl Suite<'_> {
+    fn failures(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|c| matches!(c.outcome, Outcome::Failed { .. }))
+            .count()
+    }
+
+    fn errors(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|c| matches!(c.outcome, Outcome::Error(_)))
+            .count()
+    }
+}
+
+/// A single test case: a pattern pass, a March test or a device error.
+struct Case<'a> {
+    name: String,
+    duration_ms: u64,
+    outcome: Outcome<'a>,
+}
+
+/// Result of a test case.
+enum Outcome<'a> {
+    Passed,
+    /// Memory errors were found; `records` are the ones that were recorded.
+    Failed {
+        errors_found: u64,
+        records: Vec<&'a ErrorRecord>,
+    },
+    /// Testing the device failed.
+    Error(&'a str),
+}
+
+impl<'a> Outcome<'a> {
+    fn from_errors(errors_found: u64, records: Vec<&'a ErrorRecord>) -> Self {
+        if errors_found == 0 && records.is_empty() {
+            Outcome::Passed
+        } else {
+            Outcome::Failed {
+                errors_found: errors_found.max(records.len() as u64),
+                records,
+            }
+        }
+    }
+
+    /// Returns a one-line summary for failure messages.
+    fn message(&self) -> String {
+        match self {
+            Outcome::Passed => "passed".to_string(),
+            Outcome::Failed {
+                errors_found,
+                records,
+            } => {
+                let noun = if *errors_found == 1 { "word" } else { "words" };
+                match records.first() {
+                    Some(first) => {
+                        format!("{} mismatching {}; first at {}", errors_found, noun, first)
+                    }
+                    None => format!("{} mismatching {}", errors_found, noun),
+                }
+            }
+            Outcome::Error(error) => error.to_string(),
+        }
+    }
+}
+
+fn display<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn display_opt<T: Display, S: Serializer>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn display_seq<T: Display, S: Serializer>(values: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(values.iter().map(ToString::to_string))
+}
+
+fn hex<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.collect_str(&format_args!("0x{:016X}", value)),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Backing;
+    use crate::cache::CacheBypass;
+    use serde_json::Value;
+
+    pub(super) fn config() -> ReportConfig {
+        ReportConfig {
+            memory_mb: 256,
+            threads: Some(2),
+            continuous: false,
+            duration_ms: None,
+            patterns: vec![TestPattern::WalkingOnes, TestPattern::RandomPattern],
+            modulo_n: None,
+            address_lines: false,
+            block_move: false,
+            march_tests: Vec::new(),
+            retention_ms: None,
+            retention_ramp_ms: None,
+            rowhammer_ms: None,
+            rowhammer_pairs: None,
+            keep_going: false,
+            max_errors: None,
+        }
+    }
+
+    fn result(pattern: TestPattern, errors_found: u64) -> TestResult {
+        TestResult {
+            bytes_tested: 128 * 1024 * 1024,
+            errors_found,
+            pattern,
+            pass: 0,
+            duration_ms: 40,
+        }
+    }
+
+    pub(super) fn cpu_device(errors: Vec<ErrorRecord>) -> DeviceReport {
+        let totals = Totals {
+            bytes_tested: 256 * 1024 * 1024,
+            tests_completed: 4,
+            errors_found: errors.len() as u64,
+        };
+        DeviceReport::new(
+            DeviceKind::Cpu,
+            "CPU/RAM",
+            "2 threads, 64 MB block size".to_string(),
+            Duration::from_millis(80),
+            totals,
+            &[
+                result(TestPattern::WalkingOnes, 0),
+                result(TestPattern::RandomPattern, errors.len() as u64),
+            ],
+            errors,
+        )
+    }
+
+    pub(super) fn gpu_device(error: &str) -> DeviceReport {
+        DeviceReport::failed(
+            DeviceKind::Gpu,
+            "Test <GPU>",
+            "[1] Test <GPU> (Vulkan, DiscreteGpu)".to_string(),
+            error.to_string(),
+        )
+        .with_index(1)
+    }
+
+    #[test]
+    fn test_passing_report_json() {
+        let report = Report::new(
+            config(),
+            u64::MAX,
+            vec![cpu_device(Vec::new())],
+            false,
+            Duration::from_secs(1),
+        );
+        assert!(report.passed);
+        assert_eq!(report.exit_code(), 0);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["status"], "complete");
+        assert_eq!(json["passed"], true);
+        assert_eq!(json["seed"], "18446744073709551615");
+        assert_eq!(json["duration_ms"], 1000);
+        assert_eq!(
+            json["config"]["patterns"],
+            serde_json::json!(["walking-ones", "random"])
+        );
+        assert_eq!(json["config"]["duration_ms"], Value::Null);
+
+        let device = &json["devices"][0];
+        assert_eq!(device["kind"], "cpu");
+        assert_eq!(device["index"], Value::Null);
+        assert_eq!(device["totals"]["tests_completed"], 4);
+        assert_eq!(device["results"][1]["pattern"], "random");
+        assert_eq!(device["results"][1]["pass"], 0);
+        assert_eq!(device["errors"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_failing_report_json() {
+        let info = MemoryErrorInfo::with_values(TestPattern::RandomPattern, 0x40, 1, u64::MAX, 0)
+            .with_physical_address(0x1_2345_6040);
+        let report = Report::new(
+            config(),
+            7,
+            vec![cpu_device(vec![ErrorRecord::from(&info)])],
+            true,
+            Duration::from_secs(1),
+        );
+        assert!(!report.passed);
+        assert_eq!(report.exit_code(), 1);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["status"], "interrupted");
+        let error = &json["devices"][0]["errors"][0];
+        assert_eq!(error["test"], "random");
+        assert_eq!(error["pattern"], "random");
+        assert_eq!(error["offset"], 0x40);
+        assert_eq!(error["source_id"], 1);
+        assert_eq!(error["expected"], "0xFFFFFFFFFFFFFFFF");
+        assert_eq!(error["actual"], "0x0000000000000000");
+        assert_eq!(error["physical_address"], "0x0000000123456040");
+        assert_eq!(error["march"], Value::Null);
+        assert_eq!(error["core"], Value::Null);
+        assert_eq!(error["address_line"], Value::Null);
+        assert_eq!(error["idle_ms"], Value::Null);
+        assert_eq!(error["inversion"], Value::Null);
+        assert_eq!(error["modulo"], Value::Null);
+        assert_eq!(error["copy_method"], Value::Null);
+        assert_eq!(error["hammering"], Value::Null);
+        assert_eq!(json["devices"][0]["cores"], serde_json::json!([]));
+        assert_eq!(json["devices"][0]["nodes"], serde_json::json!([]));
+        assert_eq!(json["devices"][0]["residency"], Value::Null);
+        assert_eq!(json["devices"][0]["pages"], Value::Null);
+        assert_eq!(json["devices"][0]["cache"], Value::Null);
+    }
+
+    #[test]
+    fn test_pinned_cores_json() {
+        let info = MemoryErrorInfo::with_values(TestPattern::AllOnes, 0x40, 1, u64::MAX, 0)
+            .with_core(3)
+            .with_cross_check(CrossCheck {
+                core: 4,
+                reproduced: true,
+            });
+        let record = ErrorRecord::from(&info);
+        assert_eq!(
+            record.to_string(),
+            "offset 0x40: expected 0xFFFFFFFFFFFFFFFF, got 0x0000000000000000 (source 1, core 3): page also fails from core 4 (likely RAM)"
+        );
+
+        let cores = [CoreResult {
+            core: 3,
+            bytes_tested: 1024,
+            errors_found: 1,
+        }];
+        let device = cpu_device(vec![record]).with_cores(&cores);
+        let report = Report::new(config(), 7, vec![device], false, Duration::ZERO);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        let device = &json["devices"][0];
+        assert_eq!(
+            device["cores"],
+            serde_json::json!([{ "core": 3, "bytes_tested": 1024, "errors_found": 1 }])
+        );
+        assert_eq!(device["errors"][0]["core"], 3);
+        assert_eq!(
+            device["errors"][0]["cross_check"],
+            serde_json::json!({ "core": 4, "reproduced": true })
+        );
+    }
+
+    #[test]
+    fn test_address_line_errors() {
+        let line = AddressLine {
+            written_offset: 0,
+            bit: Some(9),
+        };
+        let info =
+            MemoryErrorInfo::with_values(TestKind::AddressLines(line), 0x200, 0, 0, u64::MAX);
+        let record = ErrorRecord::from(&info);
+        assert_eq!(
+            record.to_string(),
+            "address bit 9 (write to offset 0x0): offset 0x200: expected 0x0000000000000000, got 0xFFFFFFFFFFFFFFFF (source 0)"
+        );
+
+        let config = ReportConfig {
+            address_lines: true,
+            ..config()
+        };
+        let report = Report::new(
+            config,
+            7,
+            vec![cpu_device(vec![record])],
+            false,
+            Duration::ZERO,
+        );
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["config"]["address_lines"], true);
+        assert_eq!(json["devices"][0]["errors"][0]["test"], "address-lines");
+        assert_eq!(json["devices"][0]["errors"][0]["pattern"], Value::Null);
+        assert_eq!(
+            json["devices"][0]["errors"][0]["address_line"],
+            serde_json::json!({ "written_offset": 0, "bit": 9 })
+        );
+
+        let suites = report.suites();
+        let case = suites[0].cases.last().unwrap();
+        assert_eq!(case.name, "address-lines");
+        assert!(matches!(case.outcome, Outcome::Failed { .. }));
+    }
+
+    #[test]
+    fn test_retention_errors() {
+        let test = TestKind::Retention(Duration::from_secs(120));
+        let info = MemoryErrorInfo::with_values(test, 0x80, 0, u64::MAX, !4).with_pass(1);
+        let record = ErrorRecord::from(&info);
+        assert_eq!(
+            record.to_string(),
+            "after 2m idle: offset 0x80: expected 0xFFFFFFFFFFFFFFFF, got 0xFFFFFFFFFFFFFFFB (source 0)"
+        );
+
+        let config = ReportConfig {
+            retention_ms: Some(90_000),
+            ..config()
+        };
+        let report = Report::new(
+            config,
+            7,
+            vec![cpu_device(vec![record])],
+            false,
+            Duration::ZERO,
+        );
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["config"]["retention_ms"], 90_000);
+        assert_eq!(json["config"]["retention_ramp_ms"], Value::Null);
+        assert_eq!(json["devices"][0]["errors"][0]["idle_ms"], 120_000);
+        assert_eq!(json["devices"][0]["errors"][0]["pattern"], Value::Null);
+
+        let suites = report.suites();
+        let case = suites[0].cases.last().unwrap();
+        assert_eq!(case.name, "retention");
+        assert!(matches!(case.outcome, Outcome::Failed { .. }));
+    }
+
+    #[test]
+    fn test_block_move_errors() {
+        let test = TestKind::BlockMove(CopyMethod::RepMovsb);
+        let info = MemoryErrorInfo::with_values(test, 0x40, 0, 2, 0x100002).with_pass(0);
+        let record = ErrorRecord::from(&info);
+        assert_eq!(
+            record.to_string(),
+            "rep movsb block move: offset 0x40: expected 0x0000000000000002, got 0x0000000000100002 (source 0)"
+        );
+
+        let config = ReportConfig {
+            block_move: true,
+            ..config()
+        };
+        let report = Report::new(
+            config,
+            7,
+            vec![cpu_device(vec![record])],
+            false,
+            Duration::ZERO,
+        );
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["config"]["block_move"], true);
+        assert_eq!(json["devices"][0]["errors"][0]["copy_method"], "rep-movsb");
+        assert_eq!(json["devices"][0]["errors"][0]["test"], "block-move");
+        assert_eq!(json["devices"][0]["errors"][0]["pattern"], Value::Null);
+
+        let suites = report.suites();
+        let case = suites[0].cases.last().unwrap();
+        assert_eq!(case.name, "block-move");
+        assert!(matches!(case.outcome, Outcome::Failed { .. }));
+    }
+
+    #[test]
+    fn test_rowhammer_errors() {
+        let test = TestKind::Rowhammer(Hammering {
+            double_sided: true,
+            distances: [-0x40000, 0x40000],
+            activations: 1_200_000,
+        });
+        let info = MemoryErrorInfo::with_values(test, 0x2008, 0, u64::MAX, !0x10).with_pass(0);
+        let record = ErrorRecord::from(&info);
+        assert_eq!(
+            record.to_string(),
+            "double-sided hammering at -0x40000 and +0x40000 (1200000 activations): offset 0x2008: expected 0xFFFFFFFFFFFFFFFF, got 0xFFFFFFFFFFFFFFEF (source 0)"
+        );
+
+        let config = ReportConfig {
+            rowhammer_ms: Some(60_000),
+            rowhammer_pairs: Some(32),
+            ..config()
+        };
+        let report = Report::new(
+            config,
+            7,
+            vec![cpu_device(vec![record])],
+            false,
+            Duration::ZERO,
+        );
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["config"]["rowhammer_ms"], 60_000);
+        assert_eq!(json["config"]["rowhammer_pairs"], 32);
+        assert_eq!(json["devices"][0]["errors"][0]["test"], "rowhammer");
+        assert_eq!(
+            json["devices"][0]["errors"][0]["hammering"],
+            serde_json::json!({
+                "double_sided": true,
+                "distances": [-0x40000, 0x40000],
+                "activations": 1_200_000
+            })
+        );
+
+        let suites = report.suites();
+        let case = suites[0].cases.last().unwrap();
+        assert_eq!(case.name, "rowhammer");
+        assert!(matches!(case.outcome, Outcome::Failed { .. }));
+    }
+
+    #[test]
+    fn test_inversion_errors() {
+        let step = InversionStep {
+            rotation: 3,
+            inverted: true,
+        };
+        let test = TestKind::MovingInversions(TestPattern::MovingInversions32, step);
+        let info = MemoryErrorInfo::with_values(test, 0x18, 0, !1, !0).with_pass(0);
+        let record = ErrorRecord::from(&info);
+        assert_eq!(
+            record.to_string(),
+            "rotation 3, ⇓(r~P,wP): offset 0x18: expected 0xFFFFFFFFFFFFFFFE, got 0xFFFFFFFFFFFFFFFF (source 0)"
+        );
+
+        let json: Value = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["pattern"], "moving-inversions-32");
+        assert_eq!(
+            json["inversion"],
+            serde_json::json!({ "rotation": 3, "inverted": true })
+        );
+    }
+
+    #[test]
+    fn test_modulo_errors() {
+        let test = TestKind::ModuloN(ModuloPhase { offset: 7, n: 20 });
+        let info = MemoryErrorInfo::with_values(test, 0x178, 0, 5, 0x205).with_pass(0);
+        let record = ErrorRecord::from(&info);
+        assert_eq!(
+            record.to_string(),
+            "offset phase 7 of 20: offset 0x178: expected 0x0000000000000005, got 0x0000000000000205 (source 0)"
+        );
+
+        let json: Value = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["pattern"], "modulo-n");
+        assert_eq!(json["modulo"], serde_json::json!({ "offset": 7, "n": 20 }));
+    }
+
+    #[test]
+    fn test_residency_json() {
+        let residency = Residency {
+            total_bytes: 4096,
+            locked_bytes: 2048,
+            resident_bytes: Some(3072),
+        };
+        let device = cpu_device(Vec::new()).with_residency(&residency);
+        let report = Report::new(config(), 7, vec![device], false, Duration::ZERO);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(
+            json["devices"][0]["residency"],
+            serde_json::json!({
+                "total_bytes": 4096,
+                "locked_bytes": 2048,
+                "resident_bytes": 3072,
+                "resident_percent": 75.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_pages_json() {
+        let pages = PageUsage {
+            requested: HugePages::Huge1G,
+            backing: Backing::HugeTlb(2 << 20),
+            total_bytes: 8 << 20,
+            huge_bytes: Some(8 << 20),
+        };
+        let device = cpu_device(Vec::new()).with_pages(&pages);
+        let report = Report::new(config(), 7, vec![device], false, Duration::ZERO);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(
+            json["devices"][0]["pages"],
+            serde_json::json!({
+                "requested": "1g",
+                "used": "2m",
+                "page_size": 2097152,
+                "huge_bytes": 8388608,
+                "huge_percent": 100.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_cache_json() {
+        let latency = Latency {
+            bypass: CacheBypass::Clflushopt,
+            cached_ns: 2.5,
+            flushed_ns: 90.0,
+        };
+        let device = cpu_device(Vec::new()).with_cache(&latency, WriteMode::Streaming);
+        let report = Report::new(config(), 7, vec![device], false, Duration::ZERO);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(
+            json["devices"][0]["cache"],
+            serde_json::json!({
+                "strategy": "clflushopt",
+                "cached_read_ns": 2.5,
+                "flushed_read_ns": 90.0,
+                "reaches_dram": true,
+                "write_mode": "streaming"
+            })
+        );
+    }
+
+    #[test]
+    fn test_numa_nodes_json() {
+        let nodes = [
+            NodeResult {
+                node: 0,
+                bytes_tested: 2048,
+                errors_found: 0,
+                unbound_bytes: 0,
+            },
+            NodeResult {
+                node: 1,
+                bytes_tested: 2048,
+                errors_found: 5,
+                unbound_bytes: 1024,
+            },
+        ];
+        let device = cpu_device(Vec::new()).with_nodes(&nodes);
+        let report = Report::new(config(), 7, vec![device], false, Duration::ZERO);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(
+            json["devices"][0]["nodes"],
+            serde_json::json!([
+                { "node": 0, "bytes_tested": 2048, "errors_found": 0, "unbound_bytes": 0 },
+                { "node": 1, "bytes_tested": 2048, "errors_found": 5, "unbound_bytes": 1024 }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_cases_attribute_errors_to_passes() {
+        let mut device = cpu_device(Vec::new());
+        device.results = vec![
+            PatternResult::from(&result(TestPattern::AllOnes, 0)),
+            PatternResult {
+                pass: 1,
+                ..PatternResult::from(&result(TestPattern::AllOnes, 3))
+            },
+        ];
+        let info = MemoryErrorInfo::with_values(TestPattern::AllOnes, 8, 0, u64::MAX, 0);
+        let march = TestKind::March("March C- M1 ⇑(r0,w1) op 0 (r0) at word 0x1: ...".to_string());
+        device.errors = vec![
+            ErrorRecord::from(&info.with_pass(1)),
+            ErrorRecord::from(&MemoryErrorInfo::with_values(march, 8, 0, 0, 1)),
+        ];
+        let config = ReportConfig {
+            march_tests: vec!["March C-".to_string()],
+            ..config()
+        };
+        let report = Report::new(config, 7, vec![device], false, Duration::ZERO);
+
+        let suites = report.suites();
+        let cases = &suites[0].cases;
+        assert_eq!(cases.len(), 3);
+        assert!(matches!(cases[0].outcome, Outcome::Passed));
+        match &cases[1].outcome {
+            Outcome::Failed {
+                errors_found,
+                records,
+            } => {
+                assert_eq!(*errors_found, 3);
+                assert_eq!(records.len(), 1);
+                assert_eq!(records[0].pass, Some(1));
+            }
+            _ => panic!("expected pass 1 to fail"),
+        }
+        assert_eq!(cases[2].name, "March C-");
+        assert!(cases[2].outcome.message().starts_with("1 mismatching word"));
+        assert_eq!(suites[0].failures(), 2);
+    }
+
+    #[test]
+    fn test_cases_for_enabled_tests() {
+        let info = MemoryErrorInfo::with_values(
+            TestKind::Retention(Duration::from_secs(90)),
+            0x80,
+            0,
+            0,
+            1,
+        );
+        // Only one of the 40 retention errors was recorded
+        let test_errors = BTreeMap::from([("retention".to_string(), 40)]);
+        let cpu = cpu_device(vec![ErrorRecord::from(&info)]).with_test_errors(&test_errors);
+        let config = ReportConfig {
+            address_lines: true,
+            retention_ms: Some(90_000),
+            ..config()
+        };
+        let report = Report::new(
+            config,
+            7,
+            vec![cpu, gpu_device("lost")],
+            false,
+            Duration::ZERO,
+        );
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(
+            json["devices"][0]["test_errors"],
+            serde_json::json!({ "retention": 40 })
+        );
+
+        let suites = report.suites();
+        let names: Vec<&str> = suites[0].cases.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "walking-ones pass 0",
+                "random pass 0",
+                "address-lines",
+                "retention"
+            ]
+        );
+        assert!(matches!(suites[0].cases[2].outcome, Outcome::Passed));
+        match &suites[0].cases[3].outcome {
+            Outcome::Failed {
+                errors_found,
+                records,
+            } => {
+                assert_eq!(*errors_found, 40);
+                assert_eq!(records.len(), 1);
+            }
+            _ => panic!("expected retention to fail"),
+        }
+        // GPUs run only the patterns
+        assert_eq!(suites[1].cases.len(), 1);
+    }
+
+    #[test]
+    fn test_failed_device() {
+        let report = Report::new(
+            config(),
+            7,
+            vec![
+                cpu_device(Vec::new()),
+                gpu_device("GPU device lost during testing"),
+            ],
+            false,
+            Duration::ZERO,
+        );
+        assert!(!report.passed);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        let device = &json["devices"][1];
+        assert_eq!(device["kind"], "gpu");
+        assert_eq!(device["index"], 1);
+        assert_eq!(device["passed"], false);
+        assert_eq!(device["error"], "GPU device lost during testing");
+    }
+}
@@ -0,0 +1,151 @@
+//! TAP (Test Anything Protocol) version 13 output.
+//!
+//! Every pattern pass of every device is one test point, named after its
+//! device. Failing points carry a YAML block with the failure message and
+//! the recorded mismatches.
+
+use super::{Outcome, Report, RunStatus};
+use std::fmt::{self, Write};
+
+impl Report {
+    /// Renders the report as a TAP version 13 stream.
+    pub fn to_tap(&self) -> String {
+        let mut tap = String::new();
+        self.write_tap(&mut tap)
+            .expect("writing to a String cannot fail");
+        tap
+    }
+
+    fn write_tap(&self, tap: &mut String) -> fmt::Result {
+        let suites = self.suites();
+        let total: usize = suites.iter().map(|s| s.cases.len()).sum();
+
+        writeln!(tap, "TAP version 13")?;
+        writeln!(tap, "1..{}", total)?;
+        writeln!(
+            tap,
+            "# ferritest {}, seed {}",
+            self.ferritest_version, self.seed
+        )?;
+
+        let mut number = 0;
+        for suite in &suites {
+            writeln!(tap, "# {}: {}", suite.name, suite.device.info)?;
+            for case in &suite.cases {
+                number += 1;
+                let status = match case.outcome {
+                    Outcome::Passed => "ok",
+                    _ => "not ok",
+                };
+                writeln!(
+                    tap,
+                    "{} {} - {}",
+                    status,
+                    number,
+                    description(&format!("{}: {}", suite.name, case.name))
+                )?;
+
+                match &case.outcome {
+                    Outcome::Passed => {}
+                    Outcome::Failed { records, .. } => {
+                        writeln!(tap, "  ---")?;
+                        writeln!(tap, "  message: {}", quote(&case.outcome.message()))?;
+                        writeln!(tap, "  severity: fail")?;
+                        if !records.is_empty() {
+                            writeln!(tap, "  errors:")?;
+                            for record in records {
+                                writeln!(tap, "    - {}", quote(&record.to_string()))?;
+                            }
+                        }
+                        writeln!(tap, "  ...")?;
+                    }
+                    Outcome::Error(_) => {
+                        writeln!(tap, "  ---")?;
+                        writeln!(tap, "  message: {}", quote(&case.outcome.message()))?;
+                        writeln!(tap, "  severity: error")?;
+                        writeln!(tap, "  ...")?;
+                    }
+                }
+            }
+        }
+
+        if self.status == RunStatus::Interrupted {
+            writeln!(tap, "# Run interrupted with Ctrl+C; results are partial")?;
+        }
+        Ok(())
+    }
+}
+
+/// Escapes `#` so a test description is not parsed as a directive.
+fn description(text: &str) -> String {
+    text.replace('#', "\\#")
+}
+
+/// Quotes text as a YAML double-quoted scalar.
+///
+/// JSON strings are valid YAML, so serde_json handles the escaping.
+fn quote(text: &str) -> String {
+    serde_json::to_string(text).expect("strings always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{config, cpu_device, gpu_device};
+    use super::super::{ErrorRecord, Report};
+    use super::*;
+    use crate::error::MemoryErrorInfo;
+    use crate::patterns::TestPattern;
+    use std::time::Duration;
+
+    #[test]
+    fn test_passing_stream() {
+        let report = Report::new(
+            config(),
+            42,
+            vec![cpu_device(Vec::new())],
+            false,
+            Duration::ZERO,
+        );
+        let tap = report.to_tap();
+        let lines: Vec<&str> = tap.lines().collect();
+
+        assert_eq!(lines[0], "TAP version 13");
+        assert_eq!(lines[1], "1..2");
+        assert!(lines[2].ends_with("seed 42"));
+        assert_eq!(lines[4], "ok 1 - CPU/RAM: walking-ones pass 0");
+        assert_eq!(lines[5], "ok 2 - CPU/RAM: random pass 0");
+        assert!(!tap.contains("interrupted"));
+    }
+
+    #[test]
+    fn test_failures_and_errors() {
+        let info = MemoryErrorInfo::with_values(TestPattern::RandomPattern, 0x40, 1, 0xFF, 0xFE)
+            .with_pass(0)
+            .with_physical_address(0x1000);
+        let report = Report::new(
+            config(),
+            42,
+            vec![
+                cpu_device(vec![ErrorRecord::from(&info)]),
+                gpu_device("Buffer mapping failed: \"lost\""),
+            ],
+            true,
+            Duration::ZERO,
+        );
+        let tap = report.to_tap();
+
+        assert!(tap.contains("1..3\n"));
+        assert!(tap.contains(
+            "not ok 2 - CPU/RAM: random pass 0\n  ---\n  message: \"1 mismatching word; first at offset 0x40: expected 0x00000000000000FF, got 0x00000000000000FE (source 1, physical 0x1000)\"\n  severity: fail\n  errors:\n    - \"offset 0x40"
+        ));
+        assert!(tap.contains(
+            "not ok 3 - GPU 1: Test <GPU>: device\n  ---\n  message: \"Buffer mapping failed: \\\"lost\\\"\"\n  severity: error\n  ...\n"
+        ));
+        assert!(tap.ends_with("# Run interrupted with Ctrl+C; results are partial\n"));
+    }
+
+    #[test]
+    fn test_description_escapes_directives() {
+        assert_eq!(description("GPU #2"), "GPU \\#2");
+    }
+}
@@ -0,0 +1,122 @@
+//! Bit-fade (data retention) test.
+//!
+//! Every pattern is verified right after it is written, so a cell that leaks
+//! its charge over seconds or minutes still reads back correctly. The
+//! retention test fills the blocks with all zeros and then all ones, leaves
+//! them untouched for a configurable idle period and only then verifies
+//! them. With a ramp, each pass idles longer than the one before, so the
+//! recorded idle times show roughly when cells start to fade.
+
+use crate::patterns::TestPattern;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Patterns written before each idle period, in order.
+pub const PATTERNS: [TestPattern; 2] = [TestPattern::AllZeros, TestPattern::AllOnes];
+
+/// How often an idle period checks whether the run was stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Idle periods of the retention test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    /// Idle period of the first pass.
+    pub idle: Duration,
+    /// Added to the idle period on every later pass.
+    pub ramp: Duration,
+}
+
+impl Retention {
+    /// Creates a retention test idling for `idle` on every pass.
+    pub fn new(idle: Duration) -> Self {
+        Self {
+            idle,
+            ramp: Duration::ZERO,
+        }
+    }
+
+    /// Lengthens the idle period by `ramp` on each pass.
+    pub fn with_ramp(mut self, ramp: Duration) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    /// Returns the idle period of zero-based `pass`.
+    pub fn idle_for(&self, pass: u64) -> Duration {
+        let pass = u32::try_from(pass).unwrap_or(u32::MAX);
+        self.idle.saturating_add(self.ramp.saturating_mul(pass))
+    }
+}
+
+impl fmt::Display for Retention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} idle", humantime::format_duration(self.idle))?;
+        if !self.ramp.is_zero() {
+            write!(f, ", +{} per pass", humantime::format_duration(self.ramp))?;
+        }
+        Ok(())
+    }
+}
+
+/// Waits for `duration` without touching memory, checking `should_stop`
+/// every 100 ms.
+///
+/// # Returns
+/// * `Some(elapsed)` with the time actually spent idle
+/// * `None` if the run was stopped first
+pub fn idle(duration: Duration, should_stop: &AtomicBool) -> Option<Duration> {
+    let start = Instant::now();
+    loop {
+        if should_stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        let elapsed = start.elapsed();
+        if elapsed >= duration {
+            return Some(elapsed);
+        }
+        std::thread::sleep(POLL_INTERVAL.min(duration - elapsed));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_for() {
+        let retention = Retention::new(Duration::from_secs(90));
+        assert_eq!(retention.idle_for(0), Duration::from_secs(90));
+        assert_eq!(retention.idle_for(5), Duration::from_secs(90));
+
+        let ramped = retention.with_ramp(Duration::from_secs(30));
+        assert_eq!(ramped.idle_for(0), Duration::from_secs(90));
+        assert_eq!(ramped.idle_for(2), Duration::from_secs(150));
+        assert_eq!(
+            retention.with_ramp(Duration::MAX).idle_for(u64::MAX),
+            Duration::MAX
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let retention = Retention::new(Duration::from_secs(90));
+        assert_eq!(retention.to_string(), "1m 30s idle");
+        assert_eq!(
+            retention.with_ramp(Duration::from_secs(30)).to_string(),
+            "1m 30s idle, +30s per pass"
+        );
+    }
+
+    #[test]
+    fn test_idle() {
+        let should_stop = AtomicBool::new(false);
+        let idled = idle(Duration::from_millis(20), &should_stop).unwrap();
+        assert!(idled >= Duration::from_millis(20));
+
+        should_stop.store(true, Ordering::Relaxed);
+        let start = Instant::now();
+        assert_eq!(idle(Duration::from_secs(60), &should_stop), None);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
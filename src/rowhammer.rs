@@ -0,0 +1,367 @@
+//! Rowhammer (disturbance error) test.
+//!
+//! Reading a DRAM row over and over activates it each time, and on
+//! susceptible modules the activations leak charge out of the rows next to
+//! it faster than refresh restores it. The rowhammer test picks pairs of
+//! aggressor addresses, reads both in a tight loop with `clflush` after
+//! each read so every read reaches DRAM, and then checks the rest of the
+//! memory for flipped bits.
+//!
+//! When the page map resolves physical addresses, pairs are picked one row
+//! stride apart on either side of a victim row (double-sided hammering).
+//! Otherwise both aggressors are picked at random; a random pair lands in
+//! the same bank often enough to force row activations, but each aggressor
+//! only has itself to disturb its neighbors (single-sided hammering).
+
+use crate::cache;
+use crate::error::Hammering;
+use crate::pagemap::{self, PhysicalAddress};
+use crate::patterns::TestPattern;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Aggressor pairs hammered per pass when none are configured.
+pub const DEFAULT_PAIRS: usize = 32;
+
+/// Physical distance between adjacent rows of one bank assumed when
+/// picking double-sided pairs. It is common on dual-channel DDR4 systems;
+/// the real mapping depends on the memory controller.
+pub const ROW_STRIDE: usize = 256 * 1024;
+
+/// Patterns the memory holds while it is hammered, alternating by pass:
+/// true cells lose ones and anti-cells lose zeros.
+pub const PATTERNS: [TestPattern; 2] = [TestPattern::AllOnes, TestPattern::AllZeros];
+
+/// Reads of each aggressor between checks of the clock.
+const BATCH: u64 = 100_000;
+
+/// Random pages tried per double-sided pair before giving up on finding
+/// more.
+const ATTEMPTS_PER_PAIR: usize = 1000;
+
+/// Words per 64-byte cache line; aggressors start on a line.
+const LINE_WORDS: usize = 8;
+
+/// Returns whether the CLI offers the rowhammer test on this platform.
+///
+/// It needs `clflush` from user space and `/proc/self/pagemap`, so it is
+/// limited to Linux on x86_64.
+pub fn supported() -> bool {
+    cfg!(all(target_os = "linux", target_arch = "x86_64"))
+}
+
+/// Returns the pattern the memory holds during zero-based `pass`.
+pub fn pattern_for(pass: u64) -> TestPattern {
+    PATTERNS[(pass % PATTERNS.len() as u64) as usize]
+}
+
+/// Time budget and aggressor count of the rowhammer test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rowhammer {
+    /// Time spent hammering per pass, shared evenly between the pairs.
+    pub budget: Duration,
+    /// Aggressor pairs hammered per pass.
+    pub pairs: usize,
+}
+
+impl Rowhammer {
+    /// Creates a rowhammer test hammering the default number of pairs for
+    /// `budget` per pass.
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            pairs: DEFAULT_PAIRS,
+        }
+    }
+
+    /// Hammers `pairs` aggressor pairs per pass instead (at least one).
+    pub fn with_pairs(mut self, pairs: usize) -> Self {
+        self.pairs = pairs.max(1);
+        self
+    }
+
+    /// Returns how long each pair is hammered.
+    pub fn per_pair(&self) -> Duration {
+        self.budget / self.pairs.max(1) as u32
+    }
+}
+
+impl fmt::Display for Rowhammer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} aggressor pairs in {}",
+            self.pairs,
+            humantime::format_duration(self.budget)
+        )
+    }
+}
+
+/// Two aggressor words hammered together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggressorPair {
+    /// Word index of the first aggressor.
+    pub first: usize,
+    /// Word index of the second aggressor.
+    pub second: usize,
+    /// Whether the pair sandwiches a victim row rather than being random.
+    pub double_sided: bool,
+}
+
+impl AggressorPair {
+    /// Returns the virtual addresses of both aggressors in `memory`.
+    pub fn addresses(&self, memory: &[u64]) -> [usize; 2] {
+        [self.first, self.second].map(|index| std::ptr::from_ref(&memory[index]) as usize)
+    }
+}
+
+/// Picks `count` aggressor pairs in `memory`, resolving physical addresses
+/// with `translate`.
+///
+/// Pairs are double-sided while pages two row strides apart, with the page
+/// between them, can be found in `memory`; the rest are random. Huge pages
+/// make double-sided pairs much easier to find, since each one is
+/// physically contiguous.
+pub fn pick_pairs(
+    memory: &[u64],
+    count: usize,
+    seed: u64,
+    mut translate: impl FnMut(usize) -> PhysicalAddress,
+) -> Vec<AggressorPair> {
+    let page_size = pagemap::page_size();
+    let page_words = page_size / std::mem::size_of::<u64>();
+    let pages = memory.len() / page_words;
+    let base = memory.as_ptr() as usize;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let mut pairs = Vec::with_capacity(count);
+
+    // Stop at the first page that does not resolve: the page map is either
+    // readable for all of them or for none
+    let frames: Vec<u64> = (0..pages)
+        .map_while(|page| translate(base + page * page_size).known())
+        .map(|addr| addr / page_size as u64)
+        .collect();
+    if frames.len() == pages && pages > 0 {
+        let pages_by_frame: HashMap<u64, usize> = frames
+            .iter()
+            .enumerate()
+            .map(|(page, &frame)| (frame, page))
+            .collect();
+        // `page_size` is the base page size the page map translates, not the
+        // size of any huge pages backing `memory`, so a row spans whole frames
+        let row_frames = (ROW_STRIDE / page_size) as u64;
+        debug_assert!(row_frames > 0, "row stride is smaller than a page");
+        for _ in 0..count * ATTEMPTS_PER_PAIR {
+            if pairs.len() == count {
+                break;
+            }
+            let page = rng.gen_range(0..pages);
+            let frame = frames[page];
+            if !pages_by_frame.contains_key(&(frame + row_frames)) {
+                continue;
+            }
+            let Some(&second) = pages_by_frame.get(&(frame + 2 * row_frames)) else {
+                continue;
+            };
+            let line = rng.gen_range(0..page_words / LINE_WORDS) * LINE_WORDS;
+            pairs.push(AggressorPair {
+                first: page * page_words + line,
+                second: second * page_words + line,
+                double_sided: true,
+            });
+        }
+    }
+
+    let lines = memory.len() / LINE_WORDS;
+    while pairs.len() < count && lines > 1 {
+        let first = rng.gen_range(0..lines);
+        let second = rng.gen_range(0..lines);
+        if first != second {
+            pairs.push(AggressorPair {
+                first: first * LINE_WORDS,
+                second: second * LINE_WORDS,
+                double_sided: false,
+            });
+        }
+    }
+    pairs
+}
+
+/// Describes a flip at virtual address `victim`, found after reading the
+/// aggressors at virtual addresses `aggressors` `activations` times each.
+///
+/// Distances are physical if `translate` resolves every address and
+/// virtual otherwise.
+pub fn describe_flip(
+    aggressors: [usize; 2],
+    double_sided: bool,
+    activations: u64,
+    victim: usize,
+    mut translate: impl FnMut(usize) -> PhysicalAddress,
+) -> Hammering {
+    let virtual_distances = aggressors.map(|addr| addr as i64 - victim as i64);
+    let distances = match (
+        translate(victim).known(),
+        translate(aggressors[0]).known(),
+        translate(aggressors[1]).known(),
+    ) {
+        (Some(victim), Some(first), Some(second)) => {
+            [first as i64 - victim as i64, second as i64 - victim as i64]
+        }
+        _ => virtual_distances,
+    };
+    Hammering {
+        double_sided,
+        distances,
+        activations,
+    }
+}
+
+/// Hammers `pair` for `duration`, or until `should_stop` is set.
+///
+/// Returns the number of times each aggressor was read.
+pub fn hammer(
+    memory: &[u64],
+    pair: &AggressorPair,
+    duration: Duration,
+    should_stop: &AtomicBool,
+) -> u64 {
+    let (first, second) = (&memory[pair.first], &memory[pair.second]);
+    let start = Instant::now();
+    let mut reads = 0;
+    while start.elapsed() < duration && !should_stop.load(Ordering::Relaxed) {
+        hammer_words(first, second, BATCH);
+        reads += BATCH;
+    }
+    reads
+}
+
+/// Reads both words `reads` times, flushing each after every read.
+#[cfg(target_arch = "x86_64")]
+fn hammer_words(first: &u64, second: &u64, reads: u64) {
+    for _ in 0..reads {
+        cache::read_word(first);
+        cache::read_word(second);
+        // SAFETY: clflush is part of SSE2, which every x86_64 CPU has; both
+        // pointers come from live references and flushing leaves the
+        // contents intact.
+        unsafe {
+            std::arch::x86_64::_mm_clflush(std::ptr::from_ref(first).cast());
+            std::arch::x86_64::_mm_clflush(std::ptr::from_ref(second).cast());
+        }
+    }
+}
+
+/// Portable fallback through the cache module; [`supported`] keeps the CLI
+/// from relying on it.
+#[cfg(not(target_arch = "x86_64"))]
+fn hammer_words(first: &u64, second: &u64, reads: u64) {
+    for _ in 0..reads {
+        cache::read_word(first);
+        cache::read_word(second);
+        cache::flush(std::slice::from_ref(first));
+        cache::flush(std::slice::from_ref(second));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rowhammer_config() {
+        let rowhammer = Rowhammer::new(Duration::from_secs(64));
+        assert_eq!(rowhammer.pairs, DEFAULT_PAIRS);
+        assert_eq!(rowhammer.per_pair(), Duration::from_secs(2));
+        assert_eq!(rowhammer.with_pairs(0).pairs, 1);
+        assert_eq!(
+            rowhammer.with_pairs(4).to_string(),
+            "4 aggressor pairs in 1m 4s"
+        );
+    }
+
+    #[test]
+    fn test_pattern_for() {
+        assert_eq!(pattern_for(0), TestPattern::AllOnes);
+        assert_eq!(pattern_for(1), TestPattern::AllZeros);
+        assert_eq!(pattern_for(2), TestPattern::AllOnes);
+    }
+
+    #[test]
+    fn test_pick_double_sided_pairs() {
+        // Physically contiguous memory, as with a huge page
+        let memory = vec![0u64; 4 * ROW_STRIDE / 8];
+        let base = memory.as_ptr() as usize;
+        let pairs = pick_pairs(&memory, 8, 1, |vaddr| {
+            PhysicalAddress::Known(0x1_0000_0000 + (vaddr - base) as u64)
+        });
+
+        assert_eq!(pairs.len(), 8);
+        for pair in &pairs {
+            assert!(pair.double_sided);
+            assert_eq!(pair.second - pair.first, 2 * ROW_STRIDE / 8);
+            assert_eq!(pair.first % LINE_WORDS, 0);
+        }
+        let [first, second] = pairs[0].addresses(&memory);
+        assert_eq!(second - first, 2 * ROW_STRIDE);
+    }
+
+    #[test]
+    fn test_pick_random_pairs() {
+        let memory = vec![0u64; 4096];
+        let pairs = pick_pairs(&memory, 5, 1, |_| PhysicalAddress::NeedsPrivilege);
+
+        assert_eq!(pairs.len(), 5);
+        for pair in &pairs {
+            assert!(!pair.double_sided);
+            assert_ne!(pair.first, pair.second);
+            assert_eq!(pair.second % LINE_WORDS, 0);
+        }
+        assert_eq!(
+            pairs,
+            pick_pairs(&memory, 5, 1, |_| PhysicalAddress::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_describe_flip() {
+        let hammering = describe_flip([0x1000, 0x9000], false, 7, 0x2000, |_| {
+            PhysicalAddress::NeedsPrivilege
+        });
+        assert_eq!(hammering.distances, [-0x1000, 0x7000]);
+        assert_eq!(hammering.activations, 7);
+
+        // Physical distances win when every address resolves
+        let hammering = describe_flip([0x1000, 0x9000], true, 7, 0x2000, |vaddr| {
+            PhysicalAddress::Known(if vaddr == 0x9000 {
+                0x50000
+            } else {
+                vaddr as u64
+            })
+        });
+        assert_eq!(hammering.distances, [-0x1000, 0x4E000]);
+        assert!(hammering.double_sided);
+    }
+
+    #[test]
+    fn test_hammer() {
+        let memory = vec![u64::MAX; 4096];
+        let pair = AggressorPair {
+            first: 0,
+            second: 2048,
+            double_sided: false,
+        };
+        let should_stop = AtomicBool::new(false);
+        assert!(hammer(&memory, &pair, Duration::from_millis(10), &should_stop) >= BATCH);
+        assert!(memory.iter().all(|&word| word == u64::MAX));
+
+        should_stop.store(true, Ordering::Relaxed);
+        assert_eq!(
+            hammer(&memory, &pair, Duration::from_secs(60), &should_stop),
+            0
+        );
+    }
+}
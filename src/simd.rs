@@ -0,0 +1,437 @@
+//! Vectorized copy and compare kernels for filling and verifying test memory.
+//!
+//! Pattern fill and verify are bandwidth bound, so they move whole vectors:
+//! AVX-512, AVX2 or SSE2 on x86_64 and NEON on aarch64, picked at runtime
+//! from what the CPU supports. The scalar kernels are the reference every
+//! vector path must match. Test memory is still only touched with volatile
+//! loads and stores, as in the cache module, or with non-temporal stores in
+//! [`WriteMode::Streaming`].
+
+use crate::cache::{self, read_word, write_word, WriteMode};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Instruction set used to fill and verify test memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SimdLevel {
+    /// One 64-bit word at a time.
+    Scalar,
+    /// 128-bit SSE2 vectors (x86_64).
+    Sse2,
+    /// 256-bit AVX2 vectors (x86_64).
+    Avx2,
+    /// 512-bit AVX-512 vectors (x86_64).
+    Avx512,
+    /// 128-bit NEON vectors (aarch64).
+    Neon,
+}
+
+impl SimdLevel {
+    /// Returns the fastest level this CPU supports.
+    pub fn detect() -> Self {
+        static DETECTED: OnceLock<SimdLevel> = OnceLock::new();
+        *DETECTED.get_or_init(|| {
+            Self::available()
+                .into_iter()
+                .max_by_key(|level| level.lanes())
+                .unwrap_or(Self::Scalar)
+        })
+    }
+
+    /// Returns every level this CPU supports, starting with `Scalar`.
+    pub fn available() -> Vec<Self> {
+        [
+            Self::Scalar,
+            Self::Sse2,
+            Self::Avx2,
+            Self::Avx512,
+            Self::Neon,
+        ]
+        .into_iter()
+        .filter(|level| level.is_supported())
+        .collect()
+    }
+
+    /// Returns whether this CPU can run this level.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Self::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            Self::Sse2 => std::arch::is_x86_feature_detected!("sse2"),
+            #[cfg(target_arch = "x86_64")]
+            Self::Avx2 => std::arch::is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            Self::Avx512 => std::arch::is_x86_feature_detected!("avx512f"),
+            #[cfg(target_arch = "aarch64")]
+            Self::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Returns the number of 64-bit words in one vector.
+    pub fn lanes(&self) -> usize {
+        match self {
+            Self::Scalar => 1,
+            Self::Sse2 | Self::Neon => 2,
+            Self::Avx2 => 4,
+            Self::Avx512 => 8,
+        }
+    }
+
+    /// Returns the short name used in reports (e.g., "avx2").
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Scalar => "scalar",
+            Self::Sse2 => "sse2",
+            Self::Avx2 => "avx2",
+            Self::Avx512 => "avx512",
+            Self::Neon => "neon",
+        }
+    }
+}
+
+impl fmt::Display for SimdLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Copies `src` into `dst` using `level`, with regular or non-temporal
+/// stores depending on `mode`.
+///
+/// Falls back to scalar stores if this CPU does not support `level`.
+/// Non-temporal stores are fenced before this returns.
+///
+/// # Panics
+/// Panics if the slices differ in length.
+pub fn store(level: SimdLevel, mode: WriteMode, dst: &mut [u64], src: &[u64]) {
+    assert_eq!(dst.len(), src.len(), "store needs slices of equal length");
+    if !level.is_supported() {
+        return store_scalar(dst, src, mode);
+    }
+    match level {
+        // SAFETY (all arms): the CPU supports the kernel's target feature,
+        // checked above.
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Sse2 => unsafe { x86::store_sse2(dst, src, mode) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => unsafe { x86::store_avx2(dst, src, mode) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx512 => unsafe { x86::store_avx512(dst, src, mode) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => unsafe { arm::store_neon(dst, src, mode) },
+        _ => store_scalar(dst, src, mode),
+    }
+}
+
+/// Returns the index of the first word of `actual` that differs from
+/// `expected`, comparing with `level`.
+///
+/// Falls back to scalar loads if this CPU does not support `level`.
+///
+/// # Panics
+/// Panics if the slices differ in length.
+pub fn find_mismatch(level: SimdLevel, actual: &[u64], expected: &[u64]) -> Option<usize> {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "find_mismatch needs slices of equal length"
+    );
+    if !level.is_supported() {
+        return find_mismatch_scalar(actual, expected);
+    }
+    match level {
+        // SAFETY (all arms): the CPU supports the kernel's target feature,
+        // checked above.
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Sse2 => unsafe { x86::find_mismatch_sse2(actual, expected) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => unsafe { x86::find_mismatch_avx2(actual, expected) },
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx512 => unsafe { x86::find_mismatch_avx512(actual, expected) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => unsafe { arm::find_mismatch_neon(actual, expected) },
+        _ => find_mismatch_scalar(actual, expected),
+    }
+}
+
+/// Reference copy: one volatile or non-temporal store per word.
+///
+/// In streaming mode the fence at the end also covers every non-temporal
+/// store this thread made before the call.
+fn store_scalar(dst: &mut [u64], src: &[u64], mode: WriteMode) {
+    match mode {
+        WriteMode::Cached => {
+            for (word, &value) in dst.iter_mut().zip(src) {
+                write_word(word, value);
+            }
+        }
+        WriteMode::Streaming => {
+            for (word, &value) in dst.iter_mut().zip(src) {
+                // SAFETY: fenced below, before this function returns.
+                unsafe { cache::stream_word(word, value) };
+            }
+            cache::store_fence();
+        }
+    }
+}
+
+/// Reference compare: one volatile load per word.
+fn find_mismatch_scalar(actual: &[u64], expected: &[u64]) -> Option<usize> {
+    actual
+        .iter()
+        .zip(expected)
+        .position(|(word, &value)| read_word(word) != value)
+}
+
+/// Returns how many leading words of `memory` precede the first one aligned
+/// to `lanes` words, capped at its length.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn unaligned_head(memory: &[u64], lanes: usize) -> usize {
+    let bytes = lanes * std::mem::size_of::<u64>();
+    memory.as_ptr().align_offset(bytes).min(memory.len())
+}
+
+/// Words compared before checking for a mismatch: one 64-byte cache line.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const LINE_WORDS: usize = 8;
+
+/// Generates a store and a compare kernel for one vector type.
+///
+/// Test memory is accessed with aligned volatile vector loads and stores
+/// (or aligned non-temporal stores), the words before the first aligned
+/// vector (or cache line) and after the last one with scalar accesses, whose
+/// closing fence also orders the vector stores. `expected` may have any
+/// alignment. The compare kernel checks a whole cache line at a time and
+/// only looks for the failing word once a line differs.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+macro_rules! kernels {
+    (
+        $feature:literal, $vector:ty, $lanes:literal,
+        $store:ident, $find:ident,
+        load: $load:expr,
+        stream: $stream:expr,
+        zero: $zero:expr,
+        xor: $xor:expr,
+        or: $or:expr,
+        is_zero: $is_zero:expr $(,)?
+    ) => {
+        /// # Safety
+        /// The CPU must support the target feature.
+        #[target_feature(enable = $feature)]
+        pub unsafe fn $store(dst: &mut [u64], src: &[u64], mode: super::WriteMode) {
+            let head = super::unaligned_head(dst, $lanes);
+            let body = (dst.len() - head) / $lanes * $lanes;
+            super::store_scalar(&mut dst[..head], &src[..head], mode);
+            let to = dst[head..].as_mut_ptr();
+            let from = src[head..].as_ptr();
+            // SAFETY (both loops): `k + $lanes <= body`, so both vectors lie
+            // inside the slices; `to` is aligned to the vector size.
+            match mode {
+                super::WriteMode::Cached => {
+                    for k in (0..body).step_by($lanes) {
+                        unsafe {
+                            let value: $vector = $load(from.add(k));
+                            std::ptr::write_volatile(to.add(k) as *mut $vector, value);
+                        }
+                    }
+                }
+                super::WriteMode::Streaming => {
+                    for k in (0..body).step_by($lanes) {
+                        unsafe { $stream(to.add(k) as *mut $vector, $load(from.add(k))) };
+                    }
+                }
+            }
+            super::store_scalar(&mut dst[head + body..], &src[head + body..], mode);
+        }
+
+        /// # Safety
+        /// The CPU must support the target feature.
+        #[target_feature(enable = $feature)]
+        pub unsafe fn $find(actual: &[u64], expected: &[u64]) -> Option<usize> {
+            const VECTORS: usize = super::LINE_WORDS / $lanes;
+            let head = super::unaligned_head(actual, super::LINE_WORDS);
+            let body = (actual.len() - head) / super::LINE_WORDS * super::LINE_WORDS;
+            if let Some(index) = super::find_mismatch_scalar(&actual[..head], &expected[..head]) {
+                return Some(index);
+            }
+            let from = actual[head..].as_ptr();
+            let wanted = expected[head..].as_ptr();
+            for line in (0..body).step_by(super::LINE_WORDS) {
+                let mut diffs: [$vector; VECTORS] = [$zero(); VECTORS];
+                let mut any: $vector = $zero();
+                for (v, diff) in diffs.iter_mut().enumerate() {
+                    let k = line + v * $lanes;
+                    // SAFETY: `k + $lanes <= body`, so both vectors lie
+                    // inside the slices; `from` is aligned to a cache line.
+                    let (got, want): ($vector, $vector) = unsafe {
+                        (
+                            std::ptr::read_volatile(from.add(k) as *const $vector),
+                            $load(wanted.add(k)),
+                        )
+                    };
+                    *diff = $xor(got, want);
+                    any = $or(any, *diff);
+                }
+                if !$is_zero(any) {
+                    // SAFETY: VECTORS vectors of $lanes words are exactly
+                    // LINE_WORDS plain u64s.
+                    let words: [u64; super::LINE_WORDS] = unsafe { std::mem::transmute(diffs) };
+                    return words.iter().position(|&w| w != 0).map(|i| head + line + i);
+                }
+            }
+            super::find_mismatch_scalar(&actual[head + body..], &expected[head + body..])
+                .map(|index| head + body + index)
+        }
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    kernels!(
+        "sse2", __m128i, 2, store_sse2, find_mismatch_sse2,
+        load: |p: *const u64| _mm_loadu_si128(p as *const __m128i),
+        stream: |p: *mut __m128i, v| _mm_stream_si128(p, v),
+        zero: || _mm_setzero_si128(),
+        xor: |a, b| _mm_xor_si128(a, b),
+        or: |a, b| _mm_or_si128(a, b),
+        is_zero: |a| _mm_movemask_epi8(_mm_cmpeq_epi8(a, _mm_setzero_si128())) == 0xFFFF,
+    );
+
+    kernels!(
+        "avx2", __m256i, 4, store_avx2, find_mismatch_avx2,
+        load: |p: *const u64| _mm256_loadu_si256(p as *const __m256i),
+        stream: |p: *mut __m256i, v| _mm256_stream_si256(p, v),
+        zero: || _mm256_setzero_si256(),
+        xor: |a, b| _mm256_xor_si256(a, b),
+        or: |a, b| _mm256_or_si256(a, b),
+        is_zero: |a| _mm256_testz_si256(a, a) == 1,
+    );
+
+    kernels!(
+        "avx512f", __m512i, 8, store_avx512, find_mismatch_avx512,
+        load: |p: *const u64| _mm512_loadu_si512(p as *const __m512i),
+        stream: |p: *mut __m512i, v| _mm512_stream_si512(p, v),
+        zero: || _mm512_setzero_si512(),
+        xor: |a, b| _mm512_xor_si512(a, b),
+        or: |a, b| _mm512_or_si512(a, b),
+        is_zero: |a| _mm512_test_epi64_mask(a, a) == 0,
+    );
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arm {
+    use std::arch::aarch64::*;
+
+    kernels!(
+        "neon", uint64x2_t, 2, store_neon, find_mismatch_neon,
+        load: |p: *const u64| vld1q_u64(p),
+        stream: |p: *mut uint64x2_t, v| std::arch::asm!(
+            "stnp {lo}, {hi}, [{p}]",
+            p = in(reg) p,
+            lo = in(reg) vgetq_lane_u64::<0>(v),
+            hi = in(reg) vgetq_lane_u64::<1>(v),
+            options(nostack, preserves_flags),
+        ),
+        zero: || vdupq_n_u64(0),
+        xor: |a, b| veorq_u64(a, b),
+        or: |a, b| vorrq_u64(a, b),
+        is_zero: |a| vmaxvq_u32(vreinterpretq_u32_u64(a)) == 0,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Words with a different value at every index.
+    fn words(len: usize) -> Vec<u64> {
+        (0..len as u64)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        let level = SimdLevel::detect();
+        assert!(level.is_supported());
+        assert_eq!(SimdLevel::available()[0], SimdLevel::Scalar);
+        assert!(SimdLevel::available().contains(&level));
+        #[cfg(target_arch = "x86_64")]
+        assert!(level >= SimdLevel::Sse2);
+    }
+
+    #[test]
+    fn test_store_matches_scalar() {
+        let src = words(300);
+        for (level, mode) in SimdLevel::available()
+            .into_iter()
+            .flat_map(|level| [(level, WriteMode::Cached), (level, WriteMode::Streaming)])
+        {
+            // Every start offset and length around the vector size
+            for offset in 0..8 {
+                for len in [0, 1, 7, 8, 9, 63, 64, 65, 200] {
+                    let mut dst = vec![0u64; 300];
+                    store(level, mode, &mut dst[offset..offset + len], &src[..len]);
+                    assert_eq!(
+                        &dst[offset..offset + len],
+                        &src[..len],
+                        "{} {} {}",
+                        level,
+                        mode,
+                        len
+                    );
+                    assert!(dst[..offset].iter().all(|&w| w == 0));
+                    assert!(dst[offset + len..].iter().all(|&w| w == 0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_mismatch_matches_scalar() {
+        let expected = words(256);
+        for level in SimdLevel::available() {
+            for offset in 0..8 {
+                let len = 256 - offset;
+                let mut actual = expected.clone();
+                assert_eq!(
+                    find_mismatch(level, &actual[offset..], &expected[..len]),
+                    find_mismatch_scalar(&actual[offset..], &expected[..len])
+                );
+                // A single flipped bit in every position of the slice
+                actual[offset..].copy_from_slice(&expected[..len]);
+                for index in 0..len {
+                    actual[offset + index] ^= 1 << (index % 64);
+                    assert_eq!(
+                        find_mismatch(level, &actual[offset..], &expected[..len]),
+                        Some(index),
+                        "{} offset {}",
+                        level,
+                        offset
+                    );
+                    actual[offset + index] = expected[index];
+                }
+                assert_eq!(
+                    find_mismatch(level, &actual[offset..], &expected[..len]),
+                    None
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_mismatch_reports_first() {
+        let expected = words(64);
+        let mut actual = expected.clone();
+        actual[21] = 0;
+        actual[22] = 0;
+        actual[40] = 0;
+        for level in SimdLevel::available() {
+            assert_eq!(find_mismatch(level, &actual, &expected), Some(21));
+        }
+    }
+}
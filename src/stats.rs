@@ -7,6 +7,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 /// Thread-safe statistics tracker for memory testing.
 ///
 /// Uses atomic operations for lock-free concurrent updates from multiple threads.
+#[derive(Debug)]
 pub struct TestStats {
     bytes_tested: AtomicU64,
     errors_found: AtomicU64,
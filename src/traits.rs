@@ -3,16 +3,16 @@
 //! This module defines the `MemoryTester` trait that both CPU and GPU
 //! implementations must satisfy for unified testing.
 
-use crate::error::FerritestError;
+use crate::error::{FerritestError, MemoryErrorInfo};
 use crate::patterns::TestPattern;
 use crate::stats::TestStats;
+use std::any::Any;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
 
 /// Result of a single test pass.
 #[derive(Debug, Clone)]
-#[allow(dead_code)] // Will be used in Phase 2
 pub struct TestResult {
     /// Number of bytes tested in this pass.
     pub bytes_tested: u64,
@@ -20,13 +20,14 @@ pub struct TestResult {
     pub errors_found: u64,
     /// The pattern used for this test.
     pub pattern: TestPattern,
+    /// Zero-based pass (iteration over all patterns) this result belongs to.
+    pub pass: u64,
     /// Duration of the test in milliseconds.
     pub duration_ms: u64,
 }
 
 /// Configuration for a test run.
 #[derive(Debug, Clone)]
-#[allow(dead_code)] // Will be used in Phase 2
 pub struct TestConfig {
     /// Amount of memory to test in megabytes.
     pub memory_mb: usize,
@@ -40,6 +41,8 @@ pub struct TestConfig {
     pub threads: Option<usize>,
     /// Enable verbose output.
     pub verbose: bool,
+    /// Master seed from which every pattern seed is derived.
+    pub seed: u64,
 }
 
 impl Default for TestConfig {
@@ -51,19 +54,23 @@ impl Default for TestConfig {
             timeout: None,
             threads: None,
             verbose: false,
+            seed: rand::random(),
         }
     }
 }
 
 /// Progress update for UI callbacks.
+///
+/// Sent after every block; counts restart at zero with each pass.
 #[derive(Debug, Clone)]
-#[allow(dead_code)] // Will be used in Phase 2
 pub struct ProgressUpdate {
+    /// Worker thread sending the update.
+    pub worker: usize,
     /// Name of the current pattern being tested.
     pub pattern_name: String,
-    /// Bytes processed so far.
+    /// Bytes processed so far in this pass.
     pub bytes_processed: u64,
-    /// Total bytes to process.
+    /// Total bytes to process in this pass.
     pub total_bytes: u64,
     /// Current test pass number.
     pub current_pass: u64,
@@ -73,7 +80,6 @@ pub struct ProgressUpdate {
 ///
 /// Both CPU (RAM) and GPU (VRAM) testers implement this trait,
 /// allowing for polymorphic dispatch and unified test orchestration.
-#[allow(dead_code)] // Will be used in Phase 2
 pub trait MemoryTester: Send + Sync {
     /// Returns the name of this tester (e.g., "CPU/RAM", "GPU/VRAM").
     fn name(&self) -> &'static str;
@@ -100,6 +106,21 @@ pub trait MemoryTester: Send + Sync {
         stats: Arc<TestStats>,
         should_stop: Arc<AtomicBool>,
     ) -> Result<Vec<TestResult>, FerritestError>;
+
+    /// Returns the errors recorded by the last `run_tests` call.
+    ///
+    /// The default records none; testers that record errors should override it.
+    fn recorded_errors(&self) -> Vec<MemoryErrorInfo> {
+        Vec::new()
+    }
+
+    /// Returns the tester as `Any`, so callers holding a `dyn MemoryTester`
+    /// can reach the concrete tester's own results after a run.
+    ///
+    /// The default returns `None`.
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -126,6 +147,7 @@ mod tests {
             timeout: Some(Duration::from_secs(60)),
             threads: Some(4),
             verbose: true,
+            seed: 42,
         };
         assert_eq!(config.memory_mb, 512);
         assert_eq!(config.patterns.len(), 2);
@@ -133,6 +155,7 @@ mod tests {
         assert_eq!(config.timeout, Some(Duration::from_secs(60)));
         assert_eq!(config.threads, Some(4));
         assert!(config.verbose);
+        assert_eq!(config.seed, 42);
     }
 
     #[test]
@@ -141,6 +164,7 @@ mod tests {
             bytes_tested: 1024 * 1024 * 1024,
             errors_found: 0,
             pattern: TestPattern::WalkingOnes,
+            pass: 0,
             duration_ms: 1500,
         };
         assert_eq!(result.bytes_tested, 1024 * 1024 * 1024);
@@ -152,6 +176,7 @@ mod tests {
     #[test]
     fn test_progress_update() {
         let update = ProgressUpdate {
+            worker: 0,
             pattern_name: "All Zeros".to_string(),
             bytes_processed: 512 * 1024 * 1024,
             total_bytes: 1024 * 1024 * 1024,